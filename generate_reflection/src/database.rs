@@ -4,8 +4,8 @@ use std::{
 };
 
 use rbx_reflection::{
-    ClassDescriptor, DataType, PropertyDescriptor, PropertyKind, PropertySerialization,
-    PropertyTag, ReflectionDatabase as Database, Scriptability,
+    ClassDescriptor, DataType, EnumDescriptor, PropertyDescriptor, PropertyKind,
+    PropertySerialization, PropertyTag, ReflectionDatabase as Database, Scriptability,
 };
 use rbx_types::VariantType;
 use serde::{Deserialize, Serialize};
@@ -25,8 +25,8 @@ impl ReflectionDatabase {
         Self(Database::new())
     }
 
-    /// Adds all of the classes from the given API dump to the reflection
-    /// database.
+    /// Adds all of the classes and enums from the given API dump to the
+    /// reflection database.
     pub fn populate_from_dump(&mut self, dump: &Dump) -> Result<(), Error> {
         for dump_class in &dump.classes {
             let superclass = if dump_class.superclass == "<<<ROOT>>>" {
@@ -103,6 +103,20 @@ impl ReflectionDatabase {
                 .insert(Cow::Owned(dump_class.name.clone()), class);
         }
 
+        for dump_enum in &dump.enums {
+            let mut descriptor = EnumDescriptor::new(dump_enum.name.clone());
+
+            for item in &dump_enum.items {
+                descriptor
+                    .items
+                    .insert(Cow::Owned(item.name.clone()), item.value);
+            }
+
+            self.0
+                .enums
+                .insert(Cow::Owned(dump_enum.name.clone()), descriptor);
+        }
+
         Ok(())
     }
 
@@ -241,16 +255,20 @@ fn variant_type_from_str(value: &str) -> Option<VariantType> {
         "ColorSequence" => VariantType::ColorSequence,
         "Content" => VariantType::Content,
         "Faces" => VariantType::Faces,
+        "Font" => VariantType::Font,
         "Instance" => VariantType::Ref,
         "NumberRange" => VariantType::NumberRange,
         "NumberSequence" => VariantType::NumberSequence,
+        "OptionalCoordinateFrame" => VariantType::OptionalCFrame,
         "PhysicalProperties" => VariantType::PhysicalProperties,
         "Ray" => VariantType::Ray,
         "Rect" => VariantType::Rect,
         "Region3" => VariantType::Region3,
         "Region3int16" => VariantType::Region3int16,
+        "SecurityCapabilities" => VariantType::SecurityCapabilities,
         "UDim" => VariantType::UDim,
         "UDim2" => VariantType::UDim2,
+        "UniqueId" => VariantType::UniqueId,
         "Vector2" => VariantType::Vector2,
         "Vector2int16" => VariantType::Vector2int16,
         "Vector3" => VariantType::Vector3,
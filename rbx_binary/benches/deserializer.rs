@@ -1,5 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
 
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
 pub fn de_folders_100(c: &mut Criterion) {
     static BUFFER: &[u8] = include_bytes!("../bench-files/folders-100.rbxm");
 
@@ -30,15 +32,64 @@ pub fn de_modulescripts_100_lines_100(c: &mut Criterion) {
     });
 }
 
+/// Builds a large model with many classes, and therefore many chunks, whose
+/// contents are big enough for decompression to take a meaningful amount of
+/// time.
+fn large_model() -> Vec<u8> {
+    let source: String = (0..100)
+        .map(|i| format!("local value{} = {} -- line {}\n", i, i * 7, i))
+        .collect();
+
+    let classes = ["ModuleScript", "Script", "LocalScript"];
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children((0..3000).map(|i| {
+            InstanceBuilder::new("Folder")
+                .with_name(format!("Folder {}", i))
+                .with_child(
+                    InstanceBuilder::new(classes[i % classes.len()])
+                        .with_name(format!("Script {}", i))
+                        .with_property("Source", format!("-- {}\n{}", i, source)),
+                )
+                .with_child(
+                    InstanceBuilder::new("StringValue").with_property("Value", source.clone()),
+                )
+                .with_child(InstanceBuilder::new("BoolValue").with_property("Value", i % 2 == 0))
+        })),
+    );
+
+    let mut buffer = Vec::new();
+    rbx_binary::to_writer_default(&mut buffer, &tree, tree.root().children()).unwrap();
+    buffer
+}
+
+pub fn de_large_model_threads(c: &mut Criterion) {
+    let buffer = large_model();
+
+    for &threads in &[1, 2, 4] {
+        c.bench_function(
+            &format!("Deserialize large model with {} threads", threads),
+            |b| {
+                b.iter(|| {
+                    rbx_binary::Deserializer::new(buffer.as_slice())
+                        .threads(threads)
+                        .deserialize()
+                        .unwrap();
+                });
+            },
+        );
+    }
+}
+
 #[inline(always)]
 fn deserialize_bench(buffer: &[u8]) {
-    rbx_binary::decode(buffer).unwrap();
+    rbx_binary::from_reader_default(buffer).unwrap();
 }
 
 criterion_group!(
     deserializer,
     de_folders_100,
     de_deep_folders_100,
-    de_modulescripts_100_lines_100
+    de_modulescripts_100_lines_100,
+    de_large_model_threads
 );
 criterion_main!(deserializer);
@@ -1,8 +1,10 @@
+use std::collections::HashSet;
+
 use criterion::{criterion_group, criterion_main, Criterion};
 
 use rbx_dom_weak::{InstanceBuilder, WeakDom};
 
-pub fn ser_folders_100(c: &mut Criterion) {
+fn folders_100() -> WeakDom {
     let mut tree = WeakDom::new(InstanceBuilder::new("Folder").with_name("Container"));
     let root_ref = tree.root_ref();
 
@@ -13,19 +15,52 @@ pub fn ser_folders_100(c: &mut Criterion) {
         );
     }
 
+    tree
+}
+
+pub fn ser_folders_100(c: &mut Criterion) {
+    let tree = folders_100();
+    let root_ref = tree.root_ref();
+
     let mut buffer = Vec::new();
 
     // Encode once into the buffer to pre-size it.
-    rbx_binary::encode(&tree, &[root_ref], &mut buffer).unwrap();
+    rbx_binary::to_writer_default(&mut buffer, &tree, &[root_ref]).unwrap();
     buffer.clear();
 
     c.bench_function("Serialize 100 Folders", |b| {
         b.iter(|| {
-            rbx_binary::encode(&tree, &[root_ref], &mut buffer).unwrap();
+            rbx_binary::to_writer_default(&mut buffer, &tree, &[root_ref]).unwrap();
+            buffer.clear();
+        });
+    });
+}
+
+pub fn ser_folders_100_incremental(c: &mut Criterion) {
+    let tree = folders_100();
+    let root_ref = tree.root_ref();
+
+    let serializer = rbx_binary::Serializer::new();
+    let mut cache = rbx_binary::SerializerCache::new();
+    let dirty = HashSet::new();
+
+    let mut buffer = Vec::new();
+
+    // Encode once to fill the cache and pre-size the buffer.
+    serializer
+        .serialize_incremental(&mut buffer, &tree, &[root_ref], &mut cache, &dirty)
+        .unwrap();
+    buffer.clear();
+
+    c.bench_function("Serialize 100 unchanged Folders incrementally", |b| {
+        b.iter(|| {
+            serializer
+                .serialize_incremental(&mut buffer, &tree, &[root_ref], &mut cache, &dirty)
+                .unwrap();
             buffer.clear();
         });
     });
 }
 
-criterion_group!(serializer, ser_folders_100);
+criterion_group!(serializer, ser_folders_100, ser_folders_100_incremental);
 criterion_main!(serializer);
@@ -6,6 +6,13 @@ use std::{
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
+/// The magic number that begins data compressed with the LZ4 frame format.
+const LZ4_FRAME_MAGIC: &[u8] = &[0x04, 0x22, 0x4d, 0x18];
+
+/// LZ4 can't expand data by more than this factor, which lets us reject chunks
+/// whose declared length could never have come from their compressed data.
+const LZ4_MAX_RATIO: u64 = 255;
+
 /// Represents one chunk from a binary model file.
 #[derive(Debug)]
 pub struct Chunk {
@@ -15,25 +22,104 @@ pub struct Chunk {
 
 impl Chunk {
     /// Reads and decodes a `Chunk` from the given reader.
-    pub fn decode<R: Read>(mut reader: R) -> io::Result<Chunk> {
+    pub fn decode<R: Read>(reader: R) -> io::Result<Chunk> {
+        RawChunk::read(reader)?.decompress()
+    }
+}
+
+/// A chunk that has been read from a binary model file, but whose contents
+/// have not been decompressed yet.
+///
+/// Reading a chunk only requires its header, so chunks can be read one after
+/// another and decompressed separately, possibly on other threads.
+#[derive(Debug)]
+pub struct RawChunk {
+    header: ChunkHeader,
+    payload: Vec<u8>,
+}
+
+impl RawChunk {
+    /// Reads a chunk's header and its data, compressed or not, from the given
+    /// reader.
+    pub fn read<R: Read>(mut reader: R) -> io::Result<RawChunk> {
         let header = decode_chunk_header(&mut reader)?;
 
         log::trace!("{}", header);
 
+        let payload_len = if header.compressed_len == 0 {
+            // A compressed length of zero means that the chunk's data is stored
+            // as-is, immediately following the header.
+            header.len
+        } else {
+            header.compressed_len
+        };
+
+        // The length comes from the file, so we let the buffer grow as data is
+        // actually read instead of allocating all of it up front.
+        let mut payload = Vec::new();
+        reader.take(payload_len as u64).read_to_end(&mut payload)?;
+
+        if header.compressed_len != 0 && payload.len() != header.compressed_len as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "{} was truncated: expected {} compressed bytes, got {}",
+                    header,
+                    header.compressed_len,
+                    payload.len()
+                ),
+            ));
+        }
+
+        Ok(RawChunk { header, payload })
+    }
+
+    /// The 4-byte name of the chunk, like `INST` or `PROP`.
+    pub fn name(&self) -> &[u8; 4] {
+        &self.header.name
+    }
+
+    /// Decompresses the chunk's data, if necessary, and checks that it has the
+    /// length declared by the chunk's header.
+    pub fn decompress(self) -> io::Result<Chunk> {
+        let header = self.header;
+
+        if header.compressed_len != 0
+            && header.len as u64 > (header.compressed_len as u64 + 1) * LZ4_MAX_RATIO
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} declared a length of {} bytes, which is too long for its compressed data",
+                    header, header.len
+                ),
+            ));
+        }
+
         let data = if header.compressed_len == 0 {
-            let mut data = Vec::with_capacity(header.len as usize);
-            reader.take(header.len as u64).read_to_end(&mut data)?;
+            self.payload
+        } else if self.payload.starts_with(LZ4_FRAME_MAGIC) {
+            // Roblox writes raw LZ4 blocks, but some tools write chunks using
+            // the LZ4 frame format instead, which we can detect by its magic
+            // number.
+            let mut data = Vec::new();
+            lz4::Decoder::new(self.payload.as_slice())?.read_to_end(&mut data)?;
             data
         } else {
-            let mut compressed_data = Vec::with_capacity(header.compressed_len as usize);
-            reader
-                .take(header.compressed_len as u64)
-                .read_to_end(&mut compressed_data)?;
-
-            lz4::block::decompress(&compressed_data, Some(header.len as i32))?
+            lz4::block::decompress(&self.payload, Some(header.len as i32))?
         };
 
-        assert_eq!(data.len(), header.len as usize);
+        if data.len() != header.len as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{} had the wrong length: expected {} bytes, got {}",
+                    header,
+                    header.len,
+                    data.len()
+                ),
+            ));
+        }
 
         Ok(Chunk {
             name: header.name,
@@ -43,7 +129,7 @@ impl Chunk {
 }
 
 /// The compression format of a chunk in the binary model format.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChunkCompression {
     /// The contents of the chunk should be LZ4 compressed.
     Compressed,
@@ -59,7 +145,7 @@ pub enum ChunkCompression {
 /// automatically.
 #[must_use]
 pub struct ChunkBuilder {
-    chunk_name: &'static [u8],
+    chunk_name: [u8; 4],
     compression: ChunkCompression,
     buffer: Vec<u8>,
 }
@@ -67,9 +153,9 @@ pub struct ChunkBuilder {
 impl ChunkBuilder {
     /// Creates a new `ChunkBuilder` with the given name and compression
     /// setting.
-    pub fn new(chunk_name: &'static [u8], compression: ChunkCompression) -> Self {
+    pub fn new(chunk_name: &[u8; 4], compression: ChunkCompression) -> Self {
         ChunkBuilder {
-            chunk_name,
+            chunk_name: *chunk_name,
             compression,
             buffer: Vec::new(),
         }
@@ -77,7 +163,7 @@ impl ChunkBuilder {
 
     /// Consume the chunk and write it to the given writer.
     pub fn dump<W: Write>(self, mut writer: W) -> io::Result<()> {
-        writer.write_all(self.chunk_name)?;
+        writer.write_all(&self.chunk_name)?;
 
         match self.compression {
             ChunkCompression::Compressed => {
@@ -167,3 +253,83 @@ fn decode_chunk_header<R: Read>(source: &mut R) -> io::Result<ChunkHeader> {
         reserved,
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chunk_header(name: &[u8; 4], compressed_len: u32, len: u32) -> Vec<u8> {
+        let mut buffer = name.to_vec();
+        buffer.write_u32::<LittleEndian>(compressed_len).unwrap();
+        buffer.write_u32::<LittleEndian>(len).unwrap();
+        buffer.write_u32::<LittleEndian>(0).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn decode_uncompressed() {
+        let mut buffer = chunk_header(b"META", 0, 5);
+        buffer.extend_from_slice(b"hello");
+
+        let chunk = Chunk::decode(buffer.as_slice()).unwrap();
+        assert_eq!(&chunk.name, b"META");
+        assert_eq!(chunk.data, b"hello");
+    }
+
+    #[test]
+    fn decode_uncompressed_truncated() {
+        let mut buffer = chunk_header(b"META", 0, 10);
+        buffer.extend_from_slice(b"hello");
+
+        assert!(Chunk::decode(buffer.as_slice()).is_err());
+    }
+
+    #[test]
+    fn decode_huge_lengths() {
+        // A chunk claiming far more data than the rest of the file holds.
+        let buffer = chunk_header(b"META", 0, std::u32::MAX);
+        assert!(Chunk::decode(buffer.as_slice()).is_err());
+
+        let buffer = chunk_header(b"META", std::u32::MAX, 5);
+        assert!(Chunk::decode(buffer.as_slice()).is_err());
+
+        // A compressed chunk claiming to decompress to far more data than LZ4
+        // could produce from it.
+        let compressed = lz4::block::compress(b"hello", None, false).unwrap();
+        let mut buffer = chunk_header(b"INST", compressed.len() as u32, std::u32::MAX);
+        buffer.extend_from_slice(&compressed);
+        assert!(Chunk::decode(buffer.as_slice()).is_err());
+
+        let mut buffer = chunk_header(b"INST", compressed.len() as u32, std::i32::MAX as u32);
+        buffer.extend_from_slice(&compressed);
+        assert!(Chunk::decode(buffer.as_slice()).is_err());
+    }
+
+    #[test]
+    fn decode_block() {
+        let data = b"block compressed block compressed block compressed";
+        let compressed = lz4::block::compress(data, None, false).unwrap();
+
+        let mut buffer = chunk_header(b"INST", compressed.len() as u32, data.len() as u32);
+        buffer.extend_from_slice(&compressed);
+
+        let chunk = Chunk::decode(buffer.as_slice()).unwrap();
+        assert_eq!(chunk.data, &data[..]);
+    }
+
+    #[test]
+    fn decode_frame() {
+        let data = b"frame compressed frame compressed frame compressed";
+
+        let mut encoder = lz4::EncoderBuilder::new().build(Vec::new()).unwrap();
+        encoder.write_all(data).unwrap();
+        let (compressed, result) = encoder.finish();
+        result.unwrap();
+
+        let mut buffer = chunk_header(b"PROP", compressed.len() as u32, data.len() as u32);
+        buffer.extend_from_slice(&compressed);
+
+        let chunk = Chunk::decode(buffer.as_slice()).unwrap();
+        assert_eq!(chunk.data, &data[..]);
+    }
+}
@@ -4,6 +4,7 @@ use std::{
 };
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use rbx_dom_weak::types::{Matrix3, Vector3};
 use rbx_reflection::{PropertyDescriptor, PropertyKind, PropertySerialization};
 
 pub static FILE_MAGIC_HEADER: &[u8] = b"<roblox!";
@@ -20,8 +21,12 @@ pub trait RbxReadExt: Read {
     fn read_binary_string(&mut self) -> io::Result<Vec<u8>> {
         let length = self.read_u32::<LittleEndian>()?;
 
-        let mut value = Vec::with_capacity(length as usize);
+        // The length comes from the file, so we can't trust it enough to
+        // preallocate a buffer for it. Instead, we read what's there and check
+        // that it was all present.
+        let mut value = Vec::new();
         self.take(length as u64).read_to_end(&mut value)?;
+        check_string_length(length, value.len())?;
 
         Ok(value)
     }
@@ -32,8 +37,9 @@ pub trait RbxReadExt: Read {
     fn read_string(&mut self) -> io::Result<String> {
         let length = self.read_u32::<LittleEndian>()?;
 
-        let mut value = String::with_capacity(length as usize);
+        let mut value = String::new();
         self.take(length as u64).read_to_string(&mut value)?;
+        check_string_length(length, value.len())?;
 
         Ok(value)
     }
@@ -58,6 +64,53 @@ pub trait RbxReadExt: Read {
         Ok(())
     }
 
+    fn read_interleaved_u32_array(&mut self, output: &mut [u32]) -> io::Result<()> {
+        let len = output.len();
+        let mut buffer = vec![0; mem::size_of_val(output)];
+        self.read_exact(&mut buffer)?;
+
+        for (i, value) in output.iter_mut().enumerate() {
+            let v0 = buffer[i] as u32;
+            let v1 = buffer[i + len] as u32;
+            let v2 = buffer[i + len * 2] as u32;
+            let v3 = buffer[i + len * 3] as u32;
+
+            *value = (v0 << 24) | (v1 << 16) | (v2 << 8) | v3;
+        }
+
+        Ok(())
+    }
+
+    fn read_interleaved_i64_array(&mut self, output: &mut [i64]) -> io::Result<()> {
+        let len = output.len();
+        let mut buffer = vec![0; mem::size_of_val(output)];
+        self.read_exact(&mut buffer)?;
+
+        for (i, value) in output.iter_mut().enumerate() {
+            let mut bytes = [0; 8];
+            for (j, byte) in bytes.iter_mut().enumerate() {
+                *byte = buffer[i + len * j];
+            }
+
+            *value = untransform_i64(i64::from_be_bytes(bytes));
+        }
+
+        Ok(())
+    }
+
+    /// Read an array of floats in the interleaved format Roblox uses, where
+    /// the sign bit of each float is moved to the least significant bit.
+    fn read_interleaved_f32_array(&mut self, output: &mut [f32]) -> io::Result<()> {
+        let mut buffer = vec![0; output.len()];
+        self.read_interleaved_u32_array(&mut buffer)?;
+
+        for (value, bits) in output.iter_mut().zip(buffer) {
+            *value = f32::from_bits(bits.rotate_right(1));
+        }
+
+        Ok(())
+    }
+
     fn read_referent_array(&mut self, output: &mut [i32]) -> io::Result<()> {
         self.read_interleaved_i32_array(output)?;
 
@@ -74,6 +127,22 @@ pub trait RbxReadExt: Read {
 
 impl<R> RbxReadExt for R where R: Read {}
 
+/// Returns an error if a length-prefixed string ended before its declared
+/// length, which happens when a file is truncated or corrupt.
+fn check_string_length(declared: u32, actual: usize) -> io::Result<()> {
+    if actual == declared as usize {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+                "string declared a length of {} bytes, but only {} bytes remained",
+                declared, actual
+            ),
+        ))
+    }
+}
+
 pub trait RbxWriteExt: Write {
     fn write_binary_string(&mut self, value: &[u8]) -> io::Result<()> {
         self.write_u32::<LittleEndian>(value.len() as u32)?;
@@ -106,6 +175,44 @@ pub trait RbxWriteExt: Write {
         Ok(())
     }
 
+    fn write_interleaved_u32_array<I>(&mut self, values: I) -> io::Result<()>
+    where
+        I: Iterator<Item = u32>,
+    {
+        let values: Vec<_> = values.collect();
+
+        for shift in &[24, 16, 8, 0] {
+            for value in values.iter().copied() {
+                self.write_u8((value >> shift) as u8)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_interleaved_i64_array<I>(&mut self, values: I) -> io::Result<()>
+    where
+        I: Iterator<Item = i64>,
+    {
+        let values: Vec<_> = values.collect();
+
+        for shift in &[56, 48, 40, 32, 24, 16, 8, 0] {
+            for value in values.iter().copied() {
+                let encoded = transform_i64(value) >> shift;
+                self.write_u8(encoded as u8)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_interleaved_f32_array<I>(&mut self, values: I) -> io::Result<()>
+    where
+        I: Iterator<Item = f32>,
+    {
+        self.write_interleaved_u32_array(values.map(|value| value.to_bits().rotate_left(1)))
+    }
+
     fn write_referents<I>(&mut self, values: I) -> io::Result<()>
     where
         I: Iterator<Item = i32>,
@@ -123,6 +230,56 @@ pub trait RbxWriteExt: Write {
 
 impl<W> RbxWriteExt for W where W: Write {}
 
+/// Finds the rotation matrix described by one of the special rotation IDs that
+/// the binary format uses for axis-aligned CFrames.
+///
+/// Each ID encodes the directions of the right and up vectors of the
+/// rotation, each given as a NormalId: `id = 6 * right + up + 1`.
+pub fn special_rotation(id: u8) -> Option<Matrix3> {
+    let index = id.checked_sub(1)?;
+    let right_id = index / 6;
+    let up_id = index % 6;
+
+    // The right and up vectors can't be parallel to each other.
+    if right_id >= 6 || right_id % 3 == up_id % 3 {
+        return None;
+    }
+
+    let right = normal_id_vector(right_id);
+    let up = normal_id_vector(up_id);
+    let back = Vector3::new(
+        right.y * up.z - right.z * up.y,
+        right.z * up.x - right.x * up.z,
+        right.x * up.y - right.y * up.x,
+    );
+
+    Some(Matrix3 {
+        x: Vector3::new(right.x, up.x, back.x),
+        y: Vector3::new(right.y, up.y, back.y),
+        z: Vector3::new(right.z, up.z, back.z),
+    })
+}
+
+/// Finds the special rotation ID for the given rotation matrix, if it's one of
+/// the 24 axis-aligned rotations that the binary format can encode as a single
+/// byte. This is the inverse of `special_rotation`.
+pub fn special_rotation_id(rotation: &Matrix3) -> Option<u8> {
+    (1..=36).find(|&id| special_rotation(id).as_ref() == Some(rotation))
+}
+
+/// The unit vector pointing in the direction of the given NormalId.
+fn normal_id_vector(id: u8) -> Vector3 {
+    match id {
+        0 => Vector3::new(1.0, 0.0, 0.0),
+        1 => Vector3::new(0.0, 1.0, 0.0),
+        2 => Vector3::new(0.0, 0.0, 1.0),
+        3 => Vector3::new(-1.0, 0.0, 0.0),
+        4 => Vector3::new(0.0, -1.0, 0.0),
+        5 => Vector3::new(0.0, 0.0, -1.0),
+        _ => unreachable!("invalid NormalId {}", id),
+    }
+}
+
 /// Applies the integer transformation generally used in property data in the
 /// Roblox binary format.
 pub fn transform_i32(value: i32) -> i32 {
@@ -134,6 +291,16 @@ pub fn untransform_i32(value: i32) -> i32 {
     ((value as u32) >> 1) as i32 ^ -(value & 1)
 }
 
+/// The 64-bit version of `transform_i32`.
+pub fn transform_i64(value: i64) -> i64 {
+    (value << 1) ^ (value >> 63)
+}
+
+/// The inverse of `transform_i64`.
+pub fn untransform_i64(value: i64) -> i64 {
+    ((value as u64) >> 1) as i64 ^ -(value & 1)
+}
+
 pub fn find_canonical_property_descriptor(
     class_name: &str,
     property_name: &str,
@@ -239,3 +406,70 @@ fn find_property_descriptors(
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn special_rotation_id_round_trip() {
+        let mut count = 0;
+
+        for id in 0..=std::u8::MAX {
+            if let Some(rotation) = special_rotation(id) {
+                assert_eq!(special_rotation_id(&rotation), Some(id));
+                count += 1;
+            }
+        }
+
+        assert_eq!(count, 24);
+        assert_eq!(special_rotation_id(&Matrix3::identity()), Some(2));
+    }
+
+    #[test]
+    fn read_string_length_out_of_bounds() {
+        let mut buffer = Vec::new();
+        buffer.write_u32::<LittleEndian>(std::u32::MAX).unwrap();
+        buffer.extend_from_slice(b"hello");
+
+        assert!(buffer.as_slice().read_binary_string().is_err());
+        assert!(buffer.as_slice().read_string().is_err());
+    }
+
+    #[test]
+    fn read_string_exact_length() {
+        let mut buffer = Vec::new();
+        buffer.write_string("hello").unwrap();
+
+        assert_eq!(buffer.as_slice().read_string().unwrap(), "hello");
+    }
+
+    #[test]
+    fn interleaved_i64_round_trip() {
+        let values = [
+            0,
+            1,
+            -1,
+            std::i64::MIN,
+            std::i64::MAX,
+            0x0123_4567_89ab_cdef,
+        ];
+
+        let mut buffer = Vec::new();
+        buffer
+            .write_interleaved_i64_array(values.iter().copied())
+            .unwrap();
+        assert_eq!(buffer.len(), values.len() * 8);
+
+        // The first byte of each value is the most significant byte of its
+        // transformed representation.
+        assert_eq!(&buffer[..values.len()], &[0, 0, 0, 0xff, 0xff, 0x02]);
+
+        let mut output = [0; 6];
+        buffer
+            .as_slice()
+            .read_interleaved_i64_array(&mut output)
+            .unwrap();
+        assert_eq!(output, values);
+    }
+}
@@ -1,23 +1,31 @@
 use std::{
-    collections::{HashMap, VecDeque},
-    convert::TryInto,
+    collections::{HashMap, HashSet, VecDeque},
+    convert::{TryFrom, TryInto},
     io::{self, Read},
-    str,
+    mem, str,
+    sync::{mpsc, Arc, Mutex},
+    thread,
 };
 
 use byteorder::{LittleEndian, ReadBytesExt};
 use rbx_dom_weak::{
-    types::{Ref, Variant, VariantType},
-    InstanceBuilder, WeakDom,
+    types::{
+        Attributes, Axes, BrickColor, CFrame, Color3, ColorSequence, ColorSequenceKeypoint,
+        CustomPhysicalProperties, Faces, Font, FontStyle, FontWeight, Matrix3, NumberRange,
+        NumberSequence, NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Ref,
+        SecurityCapabilities, SharedString, Tags, UniqueId, UnknownValue, Variant, VariantType,
+        Vector2, Vector3, Vector3int16,
+    },
+    InstanceBuilder, UnknownChunk, WeakDom,
 };
 use rbx_reflection::DataType;
 use thiserror::Error;
 
 use crate::{
-    chunk::Chunk,
+    chunk::{Chunk, RawChunk},
     core::{
-        find_canonical_property_descriptor, RbxReadExt, FILE_MAGIC_HEADER, FILE_SIGNATURE,
-        FILE_VERSION,
+        find_canonical_property_descriptor, special_rotation, RbxReadExt, FILE_MAGIC_HEADER,
+        FILE_SIGNATURE, FILE_VERSION,
     },
     types::Type,
 };
@@ -48,13 +56,23 @@ pub(crate) enum InnerError {
     #[error("Invalid file header")]
     BadHeader,
 
+    #[error(
+        "File header declared {num_types} types and {num_instances} instances, but the file contained {actual_types} types and {actual_instances} instances"
+    )]
+    HeaderCountMismatch {
+        num_types: u32,
+        num_instances: u32,
+        actual_types: usize,
+        actual_instances: usize,
+    },
+
     #[error("Unknown file version {version}. Known versions are: 0")]
     UnknownFileVersion { version: u16 },
 
     #[error("Unknown version {version} for chunk {chunk_name}")]
     UnknownChunkVersion {
         chunk_name: &'static str,
-        version: u8,
+        version: u32,
     },
 
     #[error(
@@ -67,39 +85,582 @@ pub(crate) enum InnerError {
         actual_type_name: String,
     },
 
+    #[error("Class {class_name} is not in the reflection database")]
+    UnknownClass { class_name: String },
+
+    #[error("Expected the file to be a {expected:?}, but it was a {actual:?}")]
+    WrongModelKind {
+        expected: ModelKind,
+        actual: ModelKind,
+    },
+
     #[error("File referred to type ID {type_id}, which was not declared")]
     InvalidTypeId { type_id: u32 },
+
+    #[error(
+        "PROP chunk for type ID {type_id} appeared after instances of that type were already streamed"
+    )]
+    PropChunkAfterStream { type_id: u32 },
+
+    #[error(
+        "Property {type_name}.{prop_name} referred to SharedString {index}, which was not declared"
+    )]
+    InvalidSharedStringIndex {
+        type_name: String,
+        prop_name: String,
+        index: u32,
+    },
+
+    #[error("Invalid CFrame rotation ID {id}")]
+    InvalidRotationId { id: u8 },
+
+    #[error("Expected OptionalCFrame to contain a column of type {expected:?}, but it was type ID {actual}")]
+    InvalidOptionalCFrameFormat { expected: Type, actual: u8 },
+
+    #[error("Invalid font weight {value}")]
+    InvalidFontWeight { value: u16 },
+
+    #[error("Invalid font style {value}")]
+    InvalidFontStyle { value: u8 },
+
+    #[error("Invalid BrickColor number {value}")]
+    InvalidBrickColor { value: u32 },
+
+    #[error("Invalid Axes bitmask {value}")]
+    InvalidAxes { value: u8 },
+
+    #[error("Invalid Faces bitmask {value}")]
+    InvalidFaces { value: u8 },
+
+    #[error("PRNT chunk referred to instance {referent}, which was not declared")]
+    InvalidParentReferent { referent: i32 },
+
+    #[error("PRNT chunk gave instance {referent} more than one parent")]
+    DuplicateParent { referent: i32 },
+
+    #[error(
+        "{count} instances were not reachable from the top of the file, because they were missing from the PRNT chunk or their parents formed a cycle"
+    )]
+    UnreachableInstances { count: usize },
+
+    #[error(
+        "{chunk_name} chunk declared {count} values, which don't fit in the {remaining} bytes remaining"
+    )]
+    LengthOutOfBounds {
+        chunk_name: &'static str,
+        count: u32,
+        remaining: usize,
+    },
 }
 
 pub(crate) fn decode<R: Read>(reader: R) -> Result<WeakDom, Error> {
     Ok(decode_inner(reader)?)
 }
 
+/// The magic number that begins gzip streams.
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The magic number that begins zstd frames.
+#[cfg(feature = "zstd")]
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub(crate) fn decode_auto<R: Read>(mut reader: R) -> Result<WeakDom, Error> {
+    // Every supported magic number fits in four bytes. They're put back in
+    // front of the rest of the reader once we know what they are.
+    let mut magic = Vec::with_capacity(4);
+    reader
+        .by_ref()
+        .take(4)
+        .read_to_end(&mut magic)
+        .map_err(InnerError::from)?;
+    let reader = magic.as_slice().chain(reader);
+
+    #[cfg(feature = "gzip")]
+    {
+        if magic.starts_with(GZIP_MAGIC) {
+            return decode(flate2::read::GzDecoder::new(reader));
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    {
+        if magic.starts_with(ZSTD_MAGIC) {
+            return decode(zstd::Decoder::new(reader).map_err(InnerError::from)?);
+        }
+    }
+
+    decode(reader)
+}
+
+#[cfg(feature = "mmap")]
+pub(crate) fn decode_file(path: &std::path::Path) -> Result<WeakDom, Error> {
+    let file = std::fs::File::open(path).map_err(InnerError::from)?;
+
+    // Safety: the mapping is only read from for the duration of this function.
+    // If another process changes the file while it's being read, we may decode
+    // garbage, but the deserializer treats its input as untrusted anyway.
+    let map = unsafe { memmap2::Mmap::map(&file) }.map_err(InnerError::from)?;
+
+    // The file is read from front to back, so the OS can read ahead and drop
+    // pages behind us. This is just a hint, so failing to apply it is fine.
+    #[cfg(unix)]
+    let _ = map.advise(memmap2::Advice::Sequential);
+
+    decode(&map[..])
+}
+
 pub(crate) fn decode_inner<R: Read>(reader: R) -> Result<WeakDom, InnerError> {
+    Ok(decode_with_options(reader, 1, false, false, None)?.0)
+}
+
+fn decode_with_options<R: Read>(
+    reader: R,
+    threads: usize,
+    preserve_unknown: bool,
+    strict_classes: bool,
+    expected_kind: Option<ModelKind>,
+) -> Result<(WeakDom, ModelKind), InnerError> {
     let mut deserializer = BinaryDeserializer::new(reader)?;
+    deserializer.preserve_unknown = preserve_unknown;
+    deserializer.strict_classes = strict_classes;
+
+    if threads <= 1 {
+        loop {
+            let chunk = Chunk::decode(&mut deserializer.input)?;
+
+            if deserializer.decode_chunk(&chunk)? {
+                break;
+            }
+        }
+    } else {
+        // Chunks can only be found by reading the one before them, so we read
+        // every chunk up front and then decompress them in parallel.
+        let mut raw_chunks = Vec::new();
+        loop {
+            let raw_chunk = RawChunk::read(&mut deserializer.input)?;
+            let is_end = raw_chunk.name() == b"END\0";
+            raw_chunks.push(raw_chunk);
+
+            if is_end {
+                break;
+            }
+        }
+
+        decompress_in_parallel(raw_chunks, threads, |chunk| {
+            deserializer.decode_chunk(&chunk)?;
+            Ok(())
+        })?;
+    }
+
+    let kind = deserializer.kind();
+    if let Some(expected) = expected_kind {
+        if kind != expected {
+            return Err(InnerError::WrongModelKind {
+                expected,
+                actual: kind,
+            });
+        }
+    }
+
+    deserializer.check_header_counts()?;
+    deserializer.construct_tree()?;
 
-    loop {
-        let chunk = Chunk::decode(&mut deserializer.input)?;
+    Ok((deserializer.finish(), kind))
+}
+
+/// Decompresses the given chunks using a pool of `threads` worker threads,
+/// calling `handle` on this thread with each decompressed chunk in the same
+/// order that they appeared in the file.
+///
+/// Chunks that finish decompressing early are held onto until every chunk
+/// before them has been handled, so the result is the same regardless of how
+/// the work was scheduled.
+fn decompress_in_parallel<F>(
+    raw_chunks: Vec<RawChunk>,
+    threads: usize,
+    mut handle: F,
+) -> Result<(), InnerError>
+where
+    F: FnMut(Chunk) -> Result<(), InnerError>,
+{
+    let queue = Arc::new(Mutex::new(raw_chunks.into_iter().enumerate()));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let queue = Arc::clone(&queue);
+            let sender = sender.clone();
+
+            thread::spawn(move || loop {
+                let next = queue.lock().unwrap().next();
+
+                match next {
+                    Some((index, raw_chunk)) => {
+                        // If sending fails, decoding stopped early because
+                        // of an error, so there's no point in continuing.
+                        if sender.send((index, raw_chunk.decompress())).is_err() {
+                            break;
+                        }
+                    }
+                    None => break,
+                }
+            })
+        })
+        .collect();
+
+    // Every worker has its own sender, so dropping ours lets the loop below
+    // end once all of the workers are done.
+    drop(sender);
+
+    let mut result = Ok(());
+    let mut finished = HashMap::new();
+    let mut next_index = 0;
+
+    'receive: for (index, chunk) in &receiver {
+        finished.insert(index, chunk);
+
+        while let Some(chunk) = finished.remove(&next_index) {
+            next_index += 1;
+
+            if let Err(err) = chunk.map_err(InnerError::from).and_then(&mut handle) {
+                result = Err(err);
+                break 'receive;
+            }
+        }
+    }
+
+    // Dropping the receiver stops any workers that are still running.
+    drop(receiver);
+
+    for worker in workers {
+        worker
+            .join()
+            .expect("a chunk decompression thread panicked");
+    }
+
+    result
+}
+
+/// Whether a binary file is a place or a model. Both are stored in the same
+/// format, but places are made up of services, like `Workspace`, while models
+/// contain ordinary instances.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelKind {
+    /// A place file, usually with the `.rbxl` extension. Places mark at least
+    /// one class of instance as a service.
+    Place,
+
+    /// A model file, usually with the `.rbxm` extension. Models don't contain
+    /// any services.
+    Model,
+}
+
+/// A deserializer for Roblox binary models and places that gives more control
+/// over how the file is decoded than [`from_reader_default`][from_reader_default].
+///
+/// [from_reader_default]: fn.from_reader_default.html
+pub struct Deserializer<R> {
+    reader: R,
+    threads: usize,
+    preserve_unknown: bool,
+    strict_classes: bool,
+    expected_kind: Option<ModelKind>,
+}
+
+impl<R: Read> Deserializer<R> {
+    /// Creates a new `Deserializer` that will read from the given reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            threads: 1,
+            preserve_unknown: false,
+            strict_classes: false,
+            expected_kind: None,
+        }
+    }
+
+    /// Sets the number of threads used to decompress chunks when calling
+    /// [`deserialize`](#method.deserialize). Defaults to 1, which decodes the
+    /// whole file on the current thread.
+    ///
+    /// With more than one thread, every chunk is read into memory before any
+    /// are decompressed, and the decompressed chunks are then decoded in the
+    /// order they appear in the file. This produces the same `WeakDom` as
+    /// decoding with a single thread, but holds onto the compressed contents
+    /// of the file while decoding.
+    pub fn threads(self, threads: usize) -> Self {
+        Self { threads, ..self }
+    }
+
+    /// Sets whether properties with types that rbx_binary can't decode should
+    /// be kept as `Variant::Unknown` values. Defaults to `false`, which skips
+    /// those properties.
+    ///
+    /// This lets files from newer versions of Roblox be loaded, edited, and
+    /// saved again without losing those properties. Since the layout of an
+    /// unknown type isn't known, each instance's value is an even share of the
+    /// property's bytes, or all of them for the first instance if they can't
+    /// be shared evenly. The serializer writes the values of every instance
+    /// back out in order, so the property only survives unchanged as long as
+    /// the same instances of its class are serialized in the same order.
+    pub fn preserve_unknown(self, preserve_unknown: bool) -> Self {
+        Self {
+            preserve_unknown,
+            ..self
+        }
+    }
+
+    /// Sets whether decoding should fail when the file contains an instance
+    /// whose class isn't in the reflection database. Defaults to `false`, which
+    /// decodes those instances like any other.
+    ///
+    /// This is useful for catching corrupt files. Files saved by a newer
+    /// version of Roblox may contain classes added since the reflection
+    /// database was generated, which will also fail to decode.
+    pub fn strict_classes(self, strict_classes: bool) -> Self {
+        Self {
+            strict_classes,
+            ..self
+        }
+    }
+
+    /// Sets the kind of file that's expected. Decoding a file of any other kind
+    /// returns an error, which prevents loading a place as a model by mistake.
+    /// By default, both kinds of file are accepted.
+    ///
+    /// The kind of a file is decided by whether it marks any class of instance
+    /// as a service. Only [`deserialize`](#method.deserialize) and
+    /// [`deserialize_with_kind`](#method.deserialize_with_kind) check it.
+    pub fn expect_kind(self, kind: ModelKind) -> Self {
+        Self {
+            expected_kind: Some(kind),
+            ..self
+        }
+    }
+
+    /// Decodes the whole file into a `WeakDom`.
+    pub fn deserialize(self) -> Result<WeakDom, Error> {
+        Ok(self.deserialize_with_kind()?.0)
+    }
+
+    /// Decodes the whole file into a `WeakDom` like
+    /// [`deserialize`](#method.deserialize), also returning whether the file
+    /// was a place or a model.
+    ///
+    /// The root of the `WeakDom` is always a `DataModel`, so the kind of file
+    /// can't be told from the tree alone.
+    pub fn deserialize_with_kind(self) -> Result<(WeakDom, ModelKind), Error> {
+        Ok(decode_with_options(
+            self.reader,
+            self.threads,
+            self.preserve_unknown,
+            self.strict_classes,
+            self.expected_kind,
+        )?)
+    }
+
+    /// Decodes the file incrementally, returning an iterator that yields each
+    /// instance as soon as all of its properties have been read instead of
+    /// building a `WeakDom`.
+    ///
+    /// Instances are yielded one class at a time, in the order their
+    /// properties appear in the file. Because the binary format only describes
+    /// the hierarchy at the very end of the file, streamed instances carry no
+    /// parent or child information. For the same reason, `Ref` properties are
+    /// not included in streamed instances.
+    ///
+    /// ## Memory
+    /// The binary format stores properties column-wise, so every instance of a
+    /// class is filled in at once. The stream keeps at most one class worth of
+    /// partially decoded instances in memory, plus one decompressed chunk and
+    /// the referent list from each `INST` chunk. Instances are released as soon
+    /// as they are yielded, so filtering the stream keeps memory usage
+    /// proportional to the largest class in the file rather than the whole
+    /// file.
+    pub fn stream(self) -> Result<InstanceStream<R>, Error> {
+        let mut deserializer = BinaryDeserializer::new(self.reader)?;
+        deserializer.preserve_unknown = self.preserve_unknown;
+        deserializer.strict_classes = self.strict_classes;
+
+        Ok(InstanceStream {
+            deserializer,
+            current_type: None,
+            streamed_types: HashSet::new(),
+            ready: VecDeque::new(),
+            finished: false,
+        })
+    }
+}
+
+/// An instance yielded by an [`InstanceStream`][InstanceStream].
+///
+/// [InstanceStream]: struct.InstanceStream.html
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamedInstance {
+    /// A referent freshly generated for this instance.
+    pub referent: Ref,
+
+    /// The instance's name, taken from its `Name` property.
+    pub name: String,
+
+    /// The instance's ClassName.
+    pub class: String,
+
+    /// All of the instance's properties other than `Name`.
+    pub properties: HashMap<String, Variant>,
+}
+
+/// An iterator over the instances in a binary model or place, created by
+/// [`Deserializer::stream`][Deserializer::stream].
+///
+/// [Deserializer::stream]: struct.Deserializer.html#method.stream
+pub struct InstanceStream<R> {
+    deserializer: BinaryDeserializer<R>,
+
+    /// The type whose PROP chunks are currently being read. Its instances are
+    /// yielded once a chunk for a different type shows up.
+    current_type: Option<u32>,
+
+    /// All of the types whose instances have already been handed out.
+    streamed_types: HashSet<u32>,
+
+    /// Instances that are fully decoded and waiting to be yielded.
+    ready: VecDeque<StreamedInstance>,
+
+    finished: bool,
+}
+
+impl<R: Read> InstanceStream<R> {
+    /// Reads and decodes the next chunk from the file, queuing up any
+    /// instances that are now complete.
+    fn advance(&mut self) -> Result<(), InnerError> {
+        let chunk = Chunk::decode(&mut self.deserializer.input)?;
 
         match &chunk.name {
-            b"META" => deserializer.decode_meta_chunk(&chunk.data)?,
-            b"INST" => deserializer.decode_inst_chunk(&chunk.data)?,
-            b"PROP" => deserializer.decode_prop_chunk(&chunk.data)?,
-            b"PRNT" => deserializer.decode_prnt_chunk(&chunk.data)?,
+            b"META" => self.deserializer.decode_meta_chunk(&chunk.data)?,
+            b"SSTR" => self.deserializer.decode_sstr_chunk(&chunk.data)?,
+            b"INST" => self.deserializer.decode_inst_chunk(&chunk.data)?,
+            b"PROP" => {
+                let type_id = chunk.data.as_slice().read_u32::<LittleEndian>()?;
+
+                if self.current_type != Some(type_id) {
+                    if let Some(previous_type) = self.current_type.take() {
+                        self.queue_type(previous_type);
+                    }
+
+                    if self.streamed_types.contains(&type_id) {
+                        return Err(InnerError::PropChunkAfterStream { type_id });
+                    }
+
+                    self.current_type = Some(type_id);
+                }
+
+                self.deserializer.decode_prop_chunk(&chunk.data)?;
+            }
+            b"PRNT" => {
+                // Hierarchy information isn't exposed by the stream, but every
+                // property has been read by the time we get here.
+                self.queue_remaining_types();
+            }
             b"END\0" => {
-                deserializer.decode_end_chunk(&chunk.data)?;
-                break;
+                self.deserializer.decode_end_chunk(&chunk.data)?;
+                self.queue_remaining_types();
+                self.finished = true;
             }
             _ => match str::from_utf8(&chunk.name) {
                 Ok(name) => log::info!("Unknown binary chunk name {}", name),
                 Err(_) => log::info!("Unknown binary chunk name {:?}", chunk.name),
             },
         }
+
+        Ok(())
+    }
+
+    /// Moves every instance of the given type out of the deserializer and into
+    /// the queue of instances ready to be yielded.
+    fn queue_type(&mut self, type_id: u32) {
+        if !self.streamed_types.insert(type_id) {
+            return;
+        }
+
+        let type_info = match self.deserializer.type_infos.get_mut(&type_id) {
+            Some(type_info) => type_info,
+            None => return,
+        };
+
+        for referent in type_info.referents.drain(..) {
+            let instance = match self.deserializer.instances_by_ref.remove(&referent) {
+                Some(instance) => instance,
+                None => continue,
+            };
+
+            let mut name = type_info.type_name.clone();
+            let mut properties = HashMap::with_capacity(instance.properties.len());
+
+            for (prop_key, prop_value) in instance.properties {
+                if prop_key.as_str() == "Name" {
+                    if let Variant::String(value) = prop_value {
+                        name = value;
+                    } else {
+                        panic!("Name property was defined as a non-string type.");
+                    }
+                } else {
+                    properties.insert(prop_key, prop_value);
+                }
+            }
+
+            self.ready.push_back(StreamedInstance {
+                referent: Ref::new(),
+                name,
+                class: type_info.type_name.clone(),
+                properties,
+            });
+        }
+    }
+
+    /// Queues up the instances of every type that hasn't been streamed yet,
+    /// including types that had no PROP chunks at all.
+    fn queue_remaining_types(&mut self) {
+        if let Some(current_type) = self.current_type.take() {
+            self.queue_type(current_type);
+        }
+
+        let mut remaining: Vec<u32> = self
+            .deserializer
+            .type_infos
+            .keys()
+            .copied()
+            .filter(|type_id| !self.streamed_types.contains(type_id))
+            .collect();
+        remaining.sort_unstable();
+
+        for type_id in remaining {
+            self.queue_type(type_id);
+        }
     }
+}
+
+impl<R: Read> Iterator for InstanceStream<R> {
+    type Item = Result<StreamedInstance, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(instance) = self.ready.pop_front() {
+                return Some(Ok(instance));
+            }
 
-    deserializer.construct_tree();
+            if self.finished {
+                return None;
+            }
 
-    Ok(deserializer.finish())
+            if let Err(err) = self.advance() {
+                self.finished = true;
+                return Some(Err(err.into()));
+            }
+        }
+    }
 }
 
 struct BinaryDeserializer<R> {
@@ -110,10 +671,18 @@ struct BinaryDeserializer<R> {
     /// the user.
     tree: WeakDom,
 
+    /// The file's header, which declares how many types and instances the
+    /// file's chunks should describe.
+    header: FileHeader,
+
     /// The metadata contained in the file, which affects how some constructs
     /// are interpreted by Roblox.
     metadata: HashMap<String, String>,
 
+    /// The SharedString values declared by the file's SSTR chunk, in order.
+    /// PROP chunks refer to these by index.
+    shared_strings: Vec<SharedString>,
+
     /// All of the instance types described by the file so far.
     type_infos: HashMap<u32, TypeInfo>,
 
@@ -123,6 +692,22 @@ struct BinaryDeserializer<R> {
     /// Referents for all of the instances with no parent, in order they appear
     /// in the file.
     root_instance_refs: Vec<i32>,
+
+    /// Whether properties with types we can't decode should be kept as
+    /// `Variant::Unknown` instead of being skipped.
+    preserve_unknown: bool,
+
+    /// Whether instances with classes missing from the reflection database
+    /// should cause an error.
+    strict_classes: bool,
+
+    /// Whether any INST chunk so far has marked its class as a service, which
+    /// only places do.
+    has_services: bool,
+
+    /// Chunks with names we don't recognize, in the order they appear in the
+    /// file. These are kept on the tree so they can be written back out.
+    unknown_chunks: Vec<UnknownChunk>,
 }
 
 /// All the information contained in the header before any chunks are read from
@@ -151,6 +736,10 @@ struct TypeInfo {
     referents: Vec<i32>,
 }
 
+/// The largest number of entries that containers are pre-sized to hold based
+/// on counts from the file header.
+const MAX_PRESIZED_LEN: u32 = 1 << 16;
+
 /// Contains all the information we need to gather in order to construct an
 /// instance. Incrementally built up by the deserializer as we decode different
 /// chunks.
@@ -161,10 +750,19 @@ struct Instance {
     /// Referents for the children of this instance.
     children: Vec<i32>,
 
+    /// Whether the PRNT chunk has given this instance a parent yet, which may
+    /// be the top of the file.
+    has_parent: bool,
+
     /// The properties found for this instance so far from the PROP chunk. Using
     /// a Vec preserves order in the unlikely event of a collision and is also
     /// compact storage since we don't need to look up properties by key.
     properties: Vec<(String, Variant)>,
+
+    /// The `Ref` properties found for this instance, pointing to other
+    /// instances by their referent in the file. These can only be turned into
+    /// `Variant::Ref` values once every instance has been constructed.
+    ref_properties: Vec<(String, i32)>,
 }
 
 impl<R: Read> BinaryDeserializer<R> {
@@ -173,19 +771,41 @@ impl<R: Read> BinaryDeserializer<R> {
 
         let header = FileHeader::decode(&mut input)?;
 
-        let type_infos = HashMap::with_capacity(header.num_types as usize);
-        let instances_by_ref = HashMap::with_capacity(1 + header.num_instances as usize);
+        // The header's counts come from the file, so a corrupt header could
+        // ask for far more memory than the file could ever need. They're only
+        // a hint, so we cap them and let the maps grow past that if needed.
+        let num_types = header.num_types.min(MAX_PRESIZED_LEN) as usize;
+        let num_instances = header.num_instances.min(MAX_PRESIZED_LEN) as usize;
+
+        let type_infos = HashMap::with_capacity(num_types);
+        let instances_by_ref = HashMap::with_capacity(1 + num_instances);
 
         Ok(BinaryDeserializer {
             input,
             tree,
+            header,
             metadata: HashMap::new(),
+            shared_strings: Vec::new(),
             type_infos,
             instances_by_ref,
             root_instance_refs: Vec::new(),
+            preserve_unknown: false,
+            strict_classes: false,
+            has_services: false,
+            unknown_chunks: Vec::new(),
         })
     }
 
+    /// Whether the file is a place or a model, judging by the INST chunks read
+    /// so far.
+    fn kind(&self) -> ModelKind {
+        if self.has_services {
+            ModelKind::Place
+        } else {
+            ModelKind::Model
+        }
+    }
+
     fn decode_meta_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
         let len = chunk.read_u32::<LittleEndian>()?;
         self.metadata.reserve(len as usize);
@@ -200,6 +820,65 @@ impl<R: Read> BinaryDeserializer<R> {
         Ok(())
     }
 
+    /// Decodes a single chunk of any kind, returning whether it was the END
+    /// chunk that marks the end of the file.
+    fn decode_chunk(&mut self, chunk: &Chunk) -> Result<bool, InnerError> {
+        match &chunk.name {
+            b"META" => self.decode_meta_chunk(&chunk.data)?,
+            b"SSTR" => self.decode_sstr_chunk(&chunk.data)?,
+            b"INST" => self.decode_inst_chunk(&chunk.data)?,
+            b"PROP" => self.decode_prop_chunk(&chunk.data)?,
+            b"PRNT" => self.decode_prnt_chunk(&chunk.data)?,
+            b"END\0" => {
+                self.decode_end_chunk(&chunk.data)?;
+                return Ok(true);
+            }
+            _ => {
+                match str::from_utf8(&chunk.name) {
+                    Ok(name) => log::info!("Unknown binary chunk name {}", name),
+                    Err(_) => log::info!("Unknown binary chunk name {:?}", chunk.name),
+                }
+
+                self.unknown_chunks.push(UnknownChunk {
+                    name: chunk.name,
+                    data: chunk.data.clone(),
+                });
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn decode_sstr_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
+        let version = chunk.read_u32::<LittleEndian>()?;
+
+        if version != 0 {
+            return Err(InnerError::UnknownChunkVersion {
+                chunk_name: "SSTR",
+                version,
+            });
+        }
+
+        let num_entries = chunk.read_u32::<LittleEndian>()?;
+
+        log::trace!("SSTR chunk ({} shared strings)", num_entries);
+
+        for _ in 0..num_entries {
+            // Roblox stores an MD5 hash of each value here. SharedString
+            // computes its own hash, so we skip over it.
+            let mut _hash = [0; 16];
+            chunk.read_exact(&mut _hash)?;
+
+            let data = chunk.read_binary_string()?;
+
+            // SharedString::new deduplicates values, so identical buffers will
+            // share the same allocation.
+            self.shared_strings.push(SharedString::new(data));
+        }
+
+        Ok(())
+    }
+
     fn decode_inst_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
         let type_id = chunk.read_u32::<LittleEndian>()?;
         let type_name = chunk.read_string()?;
@@ -214,10 +893,26 @@ impl<R: Read> BinaryDeserializer<R> {
             number_instances,
         );
 
+        if self.strict_classes
+            && !rbx_reflection_database::get()
+                .classes
+                .contains_key(type_name.as_str())
+        {
+            return Err(InnerError::UnknownClass {
+                class_name: type_name,
+            });
+        }
+
+        check_length(chunk, "INST", number_instances, mem::size_of::<i32>())?;
+
         let mut referents = vec![0; number_instances as usize];
         chunk.read_referent_array(&mut referents)?;
 
-        // TODO: Check object_format and check for service markers if it's 1?
+        // An object format of 1 marks the class as a service. It's followed by
+        // a service marker for each instance, which we don't need to read.
+        if object_format == 1 {
+            self.has_services = true;
+        }
 
         for &referent in &referents {
             self.instances_by_ref.insert(
@@ -225,7 +920,9 @@ impl<R: Read> BinaryDeserializer<R> {
                 Instance {
                     type_id,
                     children: Vec::new(),
+                    has_parent: false,
                     properties: Vec::new(),
+                    ref_properties: Vec::new(),
                 },
             );
         }
@@ -245,13 +942,37 @@ impl<R: Read> BinaryDeserializer<R> {
     fn decode_prop_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
         let type_id = chunk.read_u32::<LittleEndian>()?;
         let prop_name = chunk.read_string()?;
-        let binary_type: Type = chunk.read_u8()?.try_into().unwrap();
+        let type_value = chunk.read_u8()?;
 
         let type_info = self
             .type_infos
             .get(&type_id)
             .ok_or(InnerError::InvalidTypeId { type_id })?;
 
+        let binary_type = match Type::try_from(type_value) {
+            Ok(binary_type) => binary_type,
+            Err(_) => {
+                if self.preserve_unknown {
+                    preserve_unknown_values(
+                        &mut self.instances_by_ref,
+                        &type_info.referents,
+                        &prop_name,
+                        type_value,
+                        chunk,
+                    );
+                } else {
+                    log::warn!(
+                        "Unknown prop type {:#x} for {}.{}, skipping property",
+                        type_value,
+                        type_info.type_name,
+                        prop_name
+                    );
+                }
+
+                return Ok(());
+            }
+        };
+
         log::trace!(
             "PROP chunk ({}.{}, instance type {}, prop type {}",
             type_info.type_name,
@@ -261,7 +982,7 @@ impl<R: Read> BinaryDeserializer<R> {
         );
 
         let canonical_name;
-        let canonical_type;
+        let mut canonical_type;
 
         match find_canonical_property_descriptor(&type_info.type_name, &prop_name) {
             Some(descriptor) => {
@@ -284,12 +1005,25 @@ impl<R: Read> BinaryDeserializer<R> {
             None => {
                 canonical_name = prop_name.clone();
 
-                match binary_type.to_default_rbx_type() {
-                    Some(rbx_type) => canonical_type = rbx_type,
-                    None => {
-                        log::warn!("Unsupported prop type {:?}, skipping property", binary_type);
-
-                        return Ok(());
+                // The reflection database doesn't know about attributes yet,
+                // so we special case the property that Roblox stores them in.
+                if prop_name == "AttributesSerialize" && binary_type == Type::String {
+                    canonical_type = VariantType::Attributes;
+                } else if prop_name == "Name" && binary_type == Type::String {
+                    // Every instance has a Name, even ones whose class isn't in
+                    // the reflection database, and it's always a string.
+                    canonical_type = VariantType::String;
+                } else {
+                    match binary_type.to_default_rbx_type() {
+                        Some(rbx_type) => canonical_type = rbx_type,
+                        None => {
+                            log::warn!(
+                                "Unsupported prop type {:?}, skipping property",
+                                binary_type
+                            );
+
+                            return Ok(());
+                        }
                     }
                 }
 
@@ -297,6 +1031,12 @@ impl<R: Read> BinaryDeserializer<R> {
             }
         }
 
+        // The reflection database describes tags as a BinaryString, but we
+        // decode them into a list that's easier to work with.
+        if canonical_name == "Tags" && canonical_type == VariantType::BinaryString {
+            canonical_type = VariantType::Tags;
+        }
+
         match binary_type {
             Type::String => match canonical_type {
                 VariantType::String => {
@@ -313,7 +1053,7 @@ impl<R: Read> BinaryDeserializer<R> {
                     for referent in &type_info.referents {
                         let instance = self.instances_by_ref.get_mut(referent).unwrap();
                         let value = chunk.read_string()?;
-                        let rbx_value = Variant::String(value);
+                        let rbx_value = Variant::Content(value.into());
                         instance
                             .properties
                             .push((canonical_name.clone(), rbx_value));
@@ -329,11 +1069,51 @@ impl<R: Read> BinaryDeserializer<R> {
                             .push((canonical_name.clone(), rbx_value));
                     }
                 }
+                VariantType::Attributes => {
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let value = chunk.read_binary_string()?;
+
+                        // Attributes can hold types that we don't understand
+                        // yet. Rather than failing, we keep those attributes
+                        // in their serialized form so that they round-trip.
+                        let rbx_value = match Attributes::from_reader(value.as_slice()) {
+                            Ok(attributes) => Variant::Attributes(attributes),
+                            Err(err) => {
+                                log::warn!(
+                                    "Could not decode attributes on {}, keeping them as a BinaryString: {}",
+                                    type_info.type_name,
+                                    err
+                                );
+                                Variant::BinaryString(value.into())
+                            }
+                        };
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), rbx_value));
+                    }
+                }
+                VariantType::Tags => {
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let value = chunk.read_binary_string()?;
+                        // Tags that wouldn't be written back out the same
+                        // way are kept as a BinaryString instead.
+                        let rbx_value = match Tags::decode(&value) {
+                            Some(tags) => Variant::Tags(tags),
+                            None => Variant::BinaryString(value.into()),
+                        };
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), rbx_value));
+                    }
+                }
                 invalid_type => {
                     return Err(InnerError::PropTypeMismatch {
                         type_name: type_info.type_name.clone(),
                         prop_name,
-                        valid_type_names: "String, Content, or BinaryString",
+                        valid_type_names: "String, Content, BinaryString, Attributes, or Tags",
                         actual_type_name: format!("{:?}", invalid_type),
                     });
                 }
@@ -358,41 +1138,529 @@ impl<R: Read> BinaryDeserializer<R> {
                     });
                 }
             },
-            Type::Int32 => {}
-            Type::Float32 => {}
-            Type::Float64 => {}
-            Type::UDim => {}
-            Type::UDim2 => {}
-            Type::Ray => {}
-            Type::Faces => {}
-            Type::Axes => {}
-            Type::BrickColor => {}
-            Type::Color3 => {}
-            Type::Vector2 => {}
-            Type::Vector3 => {}
-            Type::CFrame => {}
-            Type::Enum => {}
-            Type::Ref => {}
-            Type::Vector3int16 => {}
-            Type::NumberSequence => {}
-            Type::ColorSequence => {}
-            Type::NumberRange => {}
-            Type::Rect => {}
-            Type::PhysicalProperties => {}
-            Type::Color3uint8 => {}
-            Type::Int64 => {}
-        }
-
-        Ok(())
-    }
+            Type::Ray => match canonical_type {
+                VariantType::Ray => {
+                    // Ray values are not interleaved.
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let origin = Vector3::new(
+                            chunk.read_f32::<LittleEndian>()?,
+                            chunk.read_f32::<LittleEndian>()?,
+                            chunk.read_f32::<LittleEndian>()?,
+                        );
+                        let direction = Vector3::new(
+                            chunk.read_f32::<LittleEndian>()?,
+                            chunk.read_f32::<LittleEndian>()?,
+                            chunk.read_f32::<LittleEndian>()?,
+                        );
 
-    fn decode_prnt_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
-        let version = chunk.read_u8()?;
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), Ray::new(origin, direction).into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "Ray",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::Faces => match canonical_type {
+                VariantType::Faces => {
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let bits = chunk.read_u8()?;
+                        let value = Faces::from_bits(bits)
+                            .ok_or(InnerError::InvalidFaces { value: bits })?;
 
-        if version != 0 {
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), value.into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "Faces",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::Axes => match canonical_type {
+                VariantType::Axes => {
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let bits = chunk.read_u8()?;
+                        let value =
+                            Axes::from_bits(bits).ok_or(InnerError::InvalidAxes { value: bits })?;
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), value.into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "Axes",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::BrickColor => match canonical_type {
+                VariantType::BrickColor => {
+                    let mut values = vec![0; type_info.referents.len()];
+                    chunk.read_interleaved_u32_array(&mut values)?;
+
+                    for (referent, value) in type_info.referents.iter().zip(values) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let color = value
+                            .try_into()
+                            .ok()
+                            .and_then(BrickColor::from_number)
+                            .ok_or(InnerError::InvalidBrickColor { value })?;
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), Variant::BrickColor(color)));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "BrickColor",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::CFrame => match canonical_type {
+                VariantType::CFrame => {
+                    let values = read_cframe_array(&mut chunk, type_info.referents.len())?;
+
+                    for (referent, value) in type_info.referents.iter().zip(values) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), Variant::CFrame(value)));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "CFrame",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::Ref => match canonical_type {
+                VariantType::Ref => {
+                    let mut values = vec![0; type_info.referents.len()];
+                    chunk.read_referent_array(&mut values)?;
+
+                    for (referent, value) in type_info.referents.iter().zip(values) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        instance
+                            .ref_properties
+                            .push((canonical_name.clone(), value));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "Ref",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::Vector3int16 => match canonical_type {
+                VariantType::Vector3int16 => {
+                    // Unlike most other types, Vector3int16 values are not
+                    // interleaved. Each value is stored as its three components
+                    // in order.
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let x = chunk.read_i16::<LittleEndian>()?;
+                        let y = chunk.read_i16::<LittleEndian>()?;
+                        let z = chunk.read_i16::<LittleEndian>()?;
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), Vector3int16::new(x, y, z).into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "Vector3int16",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::NumberSequence => match canonical_type {
+                VariantType::NumberSequence => {
+                    // Each value is a keypoint count followed by that many
+                    // keypoints, none of which are interleaved.
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let num_keypoints = chunk.read_u32::<LittleEndian>()?;
+                        check_length(chunk, "PROP", num_keypoints, 3 * mem::size_of::<f32>())?;
+                        let mut keypoints = Vec::with_capacity(num_keypoints as usize);
+
+                        for _ in 0..num_keypoints {
+                            keypoints.push(NumberSequenceKeypoint::new(
+                                chunk.read_f32::<LittleEndian>()?,
+                                chunk.read_f32::<LittleEndian>()?,
+                                chunk.read_f32::<LittleEndian>()?,
+                            ));
+                        }
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), NumberSequence { keypoints }.into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "NumberSequence",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::ColorSequence => match canonical_type {
+                VariantType::ColorSequence => {
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let num_keypoints = chunk.read_u32::<LittleEndian>()?;
+                        check_length(chunk, "PROP", num_keypoints, 5 * mem::size_of::<f32>())?;
+                        let mut keypoints = Vec::with_capacity(num_keypoints as usize);
+
+                        for _ in 0..num_keypoints {
+                            let time = chunk.read_f32::<LittleEndian>()?;
+                            let color = Color3::new(
+                                chunk.read_f32::<LittleEndian>()?,
+                                chunk.read_f32::<LittleEndian>()?,
+                                chunk.read_f32::<LittleEndian>()?,
+                            );
+
+                            // ColorSequence keypoints have an envelope just
+                            // like NumberSequence keypoints, but it's unused.
+                            let _envelope = chunk.read_f32::<LittleEndian>()?;
+
+                            keypoints.push(ColorSequenceKeypoint::new(time, color));
+                        }
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), ColorSequence { keypoints }.into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "ColorSequence",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::NumberRange => match canonical_type {
+                VariantType::NumberRange => {
+                    // NumberRange values are not interleaved.
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let min = chunk.read_f32::<LittleEndian>()?;
+                        let max = chunk.read_f32::<LittleEndian>()?;
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), NumberRange::new(min, max).into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "NumberRange",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::Rect => match canonical_type {
+                VariantType::Rect => {
+                    let len = type_info.referents.len();
+                    let mut min_x = vec![0.0; len];
+                    let mut min_y = vec![0.0; len];
+                    let mut max_x = vec![0.0; len];
+                    let mut max_y = vec![0.0; len];
+
+                    chunk.read_interleaved_f32_array(&mut min_x)?;
+                    chunk.read_interleaved_f32_array(&mut min_y)?;
+                    chunk.read_interleaved_f32_array(&mut max_x)?;
+                    chunk.read_interleaved_f32_array(&mut max_y)?;
+
+                    for (i, referent) in type_info.referents.iter().enumerate() {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let value = Rect::new(
+                            Vector2::new(min_x[i], min_y[i]),
+                            Vector2::new(max_x[i], max_y[i]),
+                        );
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), value.into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "Rect",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::PhysicalProperties => match canonical_type {
+                VariantType::PhysicalProperties => {
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+
+                        // Each value starts with a flag indicating whether it
+                        // has custom physical properties. Only custom values
+                        // are followed by their components.
+                        let rbx_value = if chunk.read_u8()? != 0 {
+                            PhysicalProperties::Custom(CustomPhysicalProperties {
+                                density: chunk.read_f32::<LittleEndian>()?,
+                                friction: chunk.read_f32::<LittleEndian>()?,
+                                elasticity: chunk.read_f32::<LittleEndian>()?,
+                                friction_weight: chunk.read_f32::<LittleEndian>()?,
+                                elasticity_weight: chunk.read_f32::<LittleEndian>()?,
+                            })
+                        } else {
+                            PhysicalProperties::Default
+                        };
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), rbx_value.into()));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "PhysicalProperties",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            // These types aren't decoded by rbx_binary yet.
+            Type::Int32
+            | Type::Float32
+            | Type::Float64
+            | Type::UDim
+            | Type::UDim2
+            | Type::Color3
+            | Type::Vector2
+            | Type::Vector3
+            | Type::Enum
+            | Type::Color3uint8
+            | Type::Int64 => {
+                if self.preserve_unknown {
+                    preserve_unknown_values(
+                        &mut self.instances_by_ref,
+                        &type_info.referents,
+                        &canonical_name,
+                        type_value,
+                        chunk,
+                    );
+                }
+            }
+            Type::SharedString => match canonical_type {
+                VariantType::SharedString => {
+                    let mut indices = vec![0; type_info.referents.len()];
+                    chunk.read_interleaved_u32_array(&mut indices)?;
+
+                    for (referent, index) in type_info.referents.iter().zip(indices) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let value = self.shared_strings.get(index as usize).ok_or_else(|| {
+                            InnerError::InvalidSharedStringIndex {
+                                type_name: type_info.type_name.clone(),
+                                prop_name: prop_name.clone(),
+                                index,
+                            }
+                        })?;
+                        let rbx_value = Variant::SharedString(value.clone());
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), rbx_value));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "SharedString",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::OptionalCFrame => match canonical_type {
+                VariantType::OptionalCFrame => {
+                    // OptionalCFrame values are stored as a column of CFrame
+                    // values followed by a column of bools saying whether each
+                    // value is present. Each column is prefixed with its type.
+                    let cframe_type = chunk.read_u8()?;
+                    if cframe_type != Type::CFrame as u8 {
+                        return Err(InnerError::InvalidOptionalCFrameFormat {
+                            expected: Type::CFrame,
+                            actual: cframe_type,
+                        });
+                    }
+
+                    let values = read_cframe_array(&mut chunk, type_info.referents.len())?;
+
+                    let bool_type = chunk.read_u8()?;
+                    if bool_type != Type::Bool as u8 {
+                        return Err(InnerError::InvalidOptionalCFrameFormat {
+                            expected: Type::Bool,
+                            actual: bool_type,
+                        });
+                    }
+
+                    for (referent, value) in type_info.referents.iter().zip(values) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        let value = if chunk.read_bool()? {
+                            Some(value)
+                        } else {
+                            None
+                        };
+
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), Variant::OptionalCFrame(value)));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "OptionalCFrame",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::UniqueId => match canonical_type {
+                VariantType::UniqueId => {
+                    let values = read_unique_id_array(&mut chunk, type_info.referents.len())?;
+
+                    for (referent, value) in type_info.referents.iter().zip(values) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), Variant::UniqueId(value)));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "UniqueId",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::Font => match canonical_type {
+                VariantType::Font => {
+                    for referent in &type_info.referents {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+
+                        let family = chunk.read_string()?;
+                        let weight = chunk.read_u16::<LittleEndian>()?;
+                        let style = chunk.read_u8()?;
+                        let cached_face_id = chunk.read_string()?;
+
+                        let weight = FontWeight::from_u16(weight)
+                            .ok_or(InnerError::InvalidFontWeight { value: weight })?;
+                        let style = FontStyle::from_u8(style)
+                            .ok_or(InnerError::InvalidFontStyle { value: style })?;
+
+                        // An empty CachedFaceId means that Roblox never
+                        // resolved this font to a face.
+                        let cached_face_id = if cached_face_id.is_empty() {
+                            None
+                        } else {
+                            Some(cached_face_id)
+                        };
+
+                        let rbx_value = Variant::Font(Font {
+                            family,
+                            weight,
+                            style,
+                            cached_face_id,
+                        });
+                        instance
+                            .properties
+                            .push((canonical_name.clone(), rbx_value));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "Font",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+            Type::SecurityCapabilities => match canonical_type {
+                VariantType::SecurityCapabilities => {
+                    // The bitmask is stored like an Int64, so its bits have to
+                    // be reinterpreted after undoing the integer transform.
+                    let mut values = vec![0; type_info.referents.len()];
+                    chunk.read_interleaved_i64_array(&mut values)?;
+
+                    for (referent, value) in type_info.referents.iter().zip(values) {
+                        let instance = self.instances_by_ref.get_mut(referent).unwrap();
+                        instance.properties.push((
+                            canonical_name.clone(),
+                            Variant::SecurityCapabilities(SecurityCapabilities::from_bits(
+                                value as u64,
+                            )),
+                        ));
+                    }
+                }
+                invalid_type => {
+                    return Err(InnerError::PropTypeMismatch {
+                        type_name: type_info.type_name.clone(),
+                        prop_name,
+                        valid_type_names: "SecurityCapabilities",
+                        actual_type_name: format!("{:?}", invalid_type),
+                    });
+                }
+            },
+        }
+
+        Ok(())
+    }
+
+    fn decode_prnt_chunk(&mut self, mut chunk: &[u8]) -> Result<(), InnerError> {
+        let version = chunk.read_u8()?;
+
+        if version != 0 {
             return Err(InnerError::UnknownChunkVersion {
                 chunk_name: "PRNT",
-                version,
+                version: version.into(),
             });
         }
 
@@ -400,17 +1668,36 @@ impl<R: Read> BinaryDeserializer<R> {
 
         log::trace!("PRNT chunk ({} instances)", number_objects);
 
+        check_length(chunk, "PRNT", number_objects, 2 * mem::size_of::<i32>())?;
+
         let mut subjects = vec![0; number_objects as usize];
         let mut parents = vec![0; number_objects as usize];
 
         chunk.read_referent_array(&mut subjects)?;
         chunk.read_referent_array(&mut parents)?;
 
+        // Roblox has only ever written version 0 of this chunk. Entries that
+        // don't line up with the instances in the file would otherwise produce
+        // a tree that's silently wrong, so we reject them instead.
         for (id, parent_ref) in subjects.iter().copied().zip(parents.iter().copied()) {
+            let subject = self
+                .instances_by_ref
+                .get_mut(&id)
+                .ok_or(InnerError::InvalidParentReferent { referent: id })?;
+
+            if subject.has_parent {
+                return Err(InnerError::DuplicateParent { referent: id });
+            }
+            subject.has_parent = true;
+
             if parent_ref == -1 {
                 self.root_instance_refs.push(id);
             } else {
-                let instance = self.instances_by_ref.get_mut(&parent_ref).unwrap();
+                let instance = self.instances_by_ref.get_mut(&parent_ref).ok_or(
+                    InnerError::InvalidParentReferent {
+                        referent: parent_ref,
+                    },
+                )?;
                 instance.children.push(id);
             }
         }
@@ -430,7 +1717,9 @@ impl<R: Read> BinaryDeserializer<R> {
 
     /// Combines together all the decoded information to build and emplace
     /// instances in our tree.
-    fn construct_tree(&mut self) {
+    ///
+    /// Returns an error if any instance in the file didn't end up in the tree.
+    fn construct_tree(&mut self) -> Result<(), InnerError> {
         log::trace!("Constructing tree from deserialized data");
 
         // Track all the instances we need to construct. Order of construction
@@ -446,8 +1735,13 @@ impl<R: Read> BinaryDeserializer<R> {
             instances_to_construct.push_back((referent, root_ref));
         }
 
+        // Ref properties are resolved after the whole tree exists, since they
+        // can point to instances that haven't been constructed yet.
+        let mut referent_to_id = HashMap::with_capacity(self.instances_by_ref.len());
+
         while let Some((referent, parent_ref)) = instances_to_construct.pop_front() {
             let id = self.construct_and_insert_instance(referent, parent_ref);
+            referent_to_id.insert(referent, id);
 
             if let Some(instance) = self.instances_by_ref.get(&referent) {
                 for &referent in &instance.children {
@@ -455,6 +1749,34 @@ impl<R: Read> BinaryDeserializer<R> {
                 }
             }
         }
+
+        if referent_to_id.len() != self.instances_by_ref.len() {
+            return Err(InnerError::UnreachableInstances {
+                count: self.instances_by_ref.len() - referent_to_id.len(),
+            });
+        }
+
+        for (referent, id) in &referent_to_id {
+            let instance = self.instances_by_ref.get_mut(referent).unwrap();
+            if instance.ref_properties.is_empty() {
+                continue;
+            }
+
+            let constructed = self.tree.get_by_ref_mut(*id).unwrap();
+            for (prop_name, target) in instance.ref_properties.drain(..) {
+                // Referents that don't belong to any instance in the file,
+                // including -1, are null refs.
+                let value = referent_to_id
+                    .get(&target)
+                    .copied()
+                    .unwrap_or_else(Ref::none);
+                constructed
+                    .properties
+                    .insert(prop_name, Variant::Ref(value));
+            }
+        }
+
+        Ok(())
     }
 
     fn construct_and_insert_instance(&mut self, referent: i32, parent_ref: Ref) -> Ref {
@@ -483,11 +1805,160 @@ impl<R: Read> BinaryDeserializer<R> {
         self.tree.insert(parent_ref, builder)
     }
 
-    fn finish(self) -> WeakDom {
+    /// Checks that the file's chunks described as many types and instances as
+    /// its header declared.
+    fn check_header_counts(&self) -> Result<(), InnerError> {
+        if self.type_infos.len() != self.header.num_types as usize
+            || self.instances_by_ref.len() != self.header.num_instances as usize
+        {
+            return Err(InnerError::HeaderCountMismatch {
+                num_types: self.header.num_types,
+                num_instances: self.header.num_instances,
+                actual_types: self.type_infos.len(),
+                actual_instances: self.instances_by_ref.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn finish(mut self) -> WeakDom {
+        self.tree.metadata_mut().extend(self.metadata);
+        self.tree.unknown_chunks_mut().extend(self.unknown_chunks);
         self.tree
     }
 }
 
+/// Checks that a chunk has enough bytes left to hold `count` values of
+/// `value_size` bytes each. Counts come from the file, so this keeps a corrupt
+/// count from turning into a huge allocation before we fail to read it.
+fn check_length(
+    chunk: &[u8],
+    chunk_name: &'static str,
+    count: u32,
+    value_size: usize,
+) -> Result<(), InnerError> {
+    if (count as usize).saturating_mul(value_size) > chunk.len() {
+        return Err(InnerError::LengthOutOfBounds {
+            chunk_name,
+            count,
+            remaining: chunk.len(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Stores the values of a property whose type we can't decode as
+/// `Variant::Unknown`, splitting the chunk's remaining bytes evenly between
+/// the instances of the type if possible. Otherwise, the first instance gets
+/// all of the bytes, so writing every instance's value back out in order
+/// always reproduces the original chunk. Each value records its position in
+/// the chunk so that the serializer can refuse to write a column whose
+/// instances have changed.
+fn preserve_unknown_values(
+    instances_by_ref: &mut HashMap<i32, Instance>,
+    referents: &[i32],
+    prop_name: &str,
+    type_value: u8,
+    chunk: &[u8],
+) {
+    if referents.is_empty() {
+        return;
+    }
+
+    let share = chunk.len() / referents.len();
+    let value_len = if share * referents.len() == chunk.len() {
+        share
+    } else {
+        chunk.len()
+    };
+
+    let mut remaining = chunk;
+    for (i, referent) in referents.iter().enumerate() {
+        let (data, rest) = remaining.split_at(value_len.min(remaining.len()));
+        remaining = rest;
+
+        let instance = instances_by_ref.get_mut(referent).unwrap();
+        let value = UnknownValue::in_column(type_value, data.into(), i, referents.len());
+        instance
+            .properties
+            .push((prop_name.to_owned(), Variant::Unknown(value)));
+    }
+}
+
+/// Reads `len` CFrame values in the column format used by the binary format: a
+/// rotation for each value, followed by interleaved arrays of each position
+/// component.
+fn read_cframe_array(chunk: &mut &[u8], len: usize) -> Result<Vec<CFrame>, InnerError> {
+    let mut rotations = Vec::with_capacity(len);
+
+    for _ in 0..len {
+        let id = chunk.read_u8()?;
+
+        let rotation = if id == 0 {
+            let mut components = [0.0; 9];
+            for component in &mut components {
+                *component = chunk.read_f32::<LittleEndian>()?;
+            }
+
+            Matrix3 {
+                x: Vector3::new(components[0], components[1], components[2]),
+                y: Vector3::new(components[3], components[4], components[5]),
+                z: Vector3::new(components[6], components[7], components[8]),
+            }
+        } else {
+            special_rotation(id).ok_or(InnerError::InvalidRotationId { id })?
+        };
+
+        rotations.push(rotation);
+    }
+
+    let mut x = vec![0.0; len];
+    let mut y = vec![0.0; len];
+    let mut z = vec![0.0; len];
+    chunk.read_interleaved_f32_array(&mut x)?;
+    chunk.read_interleaved_f32_array(&mut y)?;
+    chunk.read_interleaved_f32_array(&mut z)?;
+
+    Ok(rotations
+        .into_iter()
+        .enumerate()
+        .map(|(i, rotation)| CFrame::new(Vector3::new(x[i], y[i], z[i]), rotation))
+        .collect())
+}
+
+/// Reads `len` UniqueId values in the format used by the binary format. Each
+/// value is 16 big-endian bytes: the random number rotated left by one bit,
+/// then the time, then the index. The bytes are interleaved across all values,
+/// like other column types.
+fn read_unique_id_array(chunk: &mut &[u8], len: usize) -> Result<Vec<UniqueId>, InnerError> {
+    let mut buffer = vec![0; len * 16];
+    chunk.read_exact(&mut buffer)?;
+
+    Ok((0..len)
+        .map(|i| {
+            let mut bytes = [0; 16];
+            for (j, byte) in bytes.iter_mut().enumerate() {
+                *byte = buffer[j * len + i];
+            }
+
+            let mut random = [0; 8];
+            random.copy_from_slice(&bytes[0..8]);
+            let mut time = [0; 4];
+            time.copy_from_slice(&bytes[8..12]);
+            let mut index = [0; 4];
+            index.copy_from_slice(&bytes[12..16]);
+
+            UniqueId::new(
+                u32::from_be_bytes(index),
+                u32::from_be_bytes(time),
+                i64::from_be_bytes(random).rotate_right(1),
+            )
+        })
+        .collect())
+}
+
 impl FileHeader {
     pub(crate) fn decode<R: Read>(mut source: R) -> Result<Self, InnerError> {
         let mut magic_header = [0; 8];
@@ -31,7 +31,15 @@ pub mod text_format {
     pub use crate::text_deserializer::*;
 }
 
-pub use crate::{deserializer::Error as DecodeError, serializer::Error as EncodeError};
+pub use crate::{
+    deserializer::{
+        Deserializer, Error as DecodeError, InstanceStream, ModelKind, StreamedInstance,
+    },
+    serializer::{Compression, Error as EncodeError, Serializer, SerializerCache},
+};
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub use crate::serializer::FileCompression;
 
 /// Decodes an binary format model or place from something that implements the
 /// `std::io::Read` trait.
@@ -39,6 +47,40 @@ pub fn from_reader_default<R: Read>(reader: R) -> Result<WeakDom, DecodeError> {
     decode(reader)
 }
 
+/// Decodes a binary format file from something that implements the
+/// `std::io::Read` trait, returning an error if it isn't the given kind of
+/// file. This prevents loading a place as a model, or the other way around.
+///
+/// See [`Deserializer::expect_kind`](struct.Deserializer.html#method.expect_kind).
+pub fn from_reader_as<R: Read>(kind: ModelKind, reader: R) -> Result<WeakDom, DecodeError> {
+    Deserializer::new(reader).expect_kind(kind).deserialize()
+}
+
+/// Decodes a binary format model or place from the file at the given path.
+///
+/// The file is memory-mapped and decoded in place instead of being read into
+/// a buffer first, which keeps peak memory usage down for large places.
+///
+/// Requires the `mmap` feature.
+#[cfg(feature = "mmap")]
+pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<WeakDom, DecodeError> {
+    deserializer::decode_file(path.as_ref())
+}
+
+/// Decodes a binary format model or place from something that implements the
+/// `std::io::Read` trait, decompressing it first if it was compressed as a
+/// whole with gzip or zstd, like by
+/// [`to_writer_compressed`](fn.to_writer_compressed.html).
+///
+/// The compression format is detected from the start of the file. Only formats
+/// whose feature is enabled are recognized; anything else is decoded as-is.
+///
+/// Requires the `gzip` or `zstd` feature.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub fn from_reader_auto<R: Read>(reader: R) -> Result<WeakDom, DecodeError> {
+    deserializer::decode_auto(reader)
+}
+
 /// Serializes a subset of the given DOM to a binary format model or place,
 /// writing to something that implements the `std::io::Write` trait.
 pub fn to_writer_default<W: Write>(
@@ -48,3 +90,20 @@ pub fn to_writer_default<W: Write>(
 ) -> Result<(), EncodeError> {
     encode(dom, refs, writer)
 }
+
+/// Serializes a subset of the given DOM to a binary format model or place,
+/// then compresses the whole file with the given format before writing it to
+/// something that implements the `std::io::Write` trait.
+///
+/// Use [`from_reader_auto`](fn.from_reader_auto.html) to read the file back.
+///
+/// Requires the `gzip` or `zstd` feature.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub fn to_writer_compressed<W: Write>(
+    writer: W,
+    dom: &WeakDom,
+    refs: &[Ref],
+    compression: FileCompression,
+) -> Result<(), EncodeError> {
+    serializer::encode_compressed(dom, refs, writer, compression)
+}
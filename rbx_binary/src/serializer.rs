@@ -1,14 +1,23 @@
 use std::{
     borrow::{Borrow, Cow},
-    collections::{BTreeMap, HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt,
     io::{self, Write},
+    iter,
+    sync::Arc,
     u32,
 };
 
 use byteorder::{LittleEndian, WriteBytesExt};
 use rbx_dom_weak::{
-    types::{BinaryString, Ref, Variant, VariantType},
-    WeakDom,
+    types::{
+        AttributeError, Attributes, Axes, BinaryString, BrickColor, CFrame, Color3, ColorSequence,
+        ColorSequenceKeypoint, Content, Faces, Font, Matrix3, NumberRange, NumberSequence,
+        NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Ref, SecurityCapabilities,
+        SharedString, SharedStringHash, Tags, UniqueId, Variant, VariantType, Vector2, Vector3,
+        Vector3int16,
+    },
+    Instance, WeakDom,
 };
 use rbx_reflection::{ClassDescriptor, ClassTag, DataType};
 use thiserror::Error;
@@ -16,8 +25,8 @@ use thiserror::Error;
 use crate::{
     chunk::{ChunkBuilder, ChunkCompression},
     core::{
-        find_serialized_property_descriptor, RbxWriteExt, FILE_MAGIC_HEADER, FILE_SIGNATURE,
-        FILE_VERSION,
+        find_serialized_property_descriptor, special_rotation_id, RbxWriteExt, FILE_MAGIC_HEADER,
+        FILE_SIGNATURE, FILE_VERSION,
     },
     types::Type,
 };
@@ -40,7 +49,7 @@ impl From<InnerError> for Error {
 }
 
 #[derive(Debug, Error)]
-enum InnerError {
+pub(crate) enum InnerError {
     #[error(transparent)]
     Io {
         #[from]
@@ -59,38 +68,447 @@ enum InnerError {
         instance_full_name: String,
     },
 
-    #[error("Unsupported property type: {type_name}.{prop_name} is of type {prop_type}")]
+    #[error(
+        "failed to serialize property '{prop_name}' ({prop_type}) on instance of class \
+        '{type_name}': this property type is not supported"
+    )]
     UnsupportedPropType {
         type_name: String,
         prop_name: String,
         prop_type: String,
     },
 
+    #[error("Could not serialize attributes on instance {instance_full_name}: {source}")]
+    InvalidAttributes {
+        instance_full_name: String,
+        source: AttributeError,
+    },
+
     #[error("The instance with referent {referent:?} was not present in the dom.")]
     InvalidInstanceId { referent: Ref },
+
+    #[error("Unsupported file format version {version}. Supported versions are: 0")]
+    UnsupportedFormatVersion { version: u16 },
+
+    #[error(
+        "Property {type_name}.{prop_name} on instance {instance_full_name} refers to \
+        {target:?}, which is not being serialized"
+    )]
+    InvalidRef {
+        type_name: String,
+        prop_name: String,
+        instance_full_name: String,
+        target: Ref,
+    },
+
+    #[error(
+        "Property {type_name}.{prop_name} has a type that rbx_binary can't encode, so it must be \
+        present on every instance of {type_name}, but it was missing from {instance_full_name}"
+    )]
+    MissingUnknownValue {
+        type_name: String,
+        prop_name: String,
+        instance_full_name: String,
+    },
+
+    #[error(
+        "Property {type_name}.{prop_name} on instance {instance_full_name} has a type that \
+        rbx_binary can't encode, so instances of {type_name} must be serialized in the same set \
+        and order they were deserialized in"
+    )]
+    UnknownValuesChanged {
+        type_name: String,
+        prop_name: String,
+        instance_full_name: String,
+    },
 }
 
 /// Serializes instances from an `WeakDom` into a writer in Roblox's binary
 /// model format.
 pub fn encode<W: Write>(dom: &WeakDom, refs: &[Ref], writer: W) -> Result<(), Error> {
-    let mut serializer = BinarySerializer::new(dom, writer);
+    Serializer::new().serialize(writer, dom, refs)
+}
+
+/// Serializes instances from an `WeakDom` into a writer in Roblox's binary
+/// model format, compressing the whole file with the given format.
+///
+/// Chunks are written uncompressed, since compressing the whole file makes
+/// compressing each chunk redundant.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+pub(crate) fn encode_compressed<W: Write>(
+    dom: &WeakDom,
+    refs: &[Ref],
+    writer: W,
+    compression: FileCompression,
+) -> Result<(), Error> {
+    let serializer = Serializer::new().compression(Compression::None);
+
+    match compression {
+        #[cfg(feature = "gzip")]
+        FileCompression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            serializer.serialize(&mut encoder, dom, refs)?;
+            encoder.finish().map_err(InnerError::from)?;
+        }
+
+        #[cfg(feature = "zstd")]
+        FileCompression::Zstd => {
+            let mut encoder = zstd::Encoder::new(writer, 0).map_err(InnerError::from)?;
+            serializer.serialize(&mut encoder, dom, refs)?;
+            encoder.finish().map_err(InnerError::from)?;
+        }
+    }
+
+    Ok(())
+}
 
-    serializer.add_instances(refs)?;
+/// The compression applied to an entire file by
+/// [`to_writer_compressed`][to_writer_compressed], on top of the binary
+/// format. [`from_reader_auto`][from_reader_auto] can read files written with
+/// any of these.
+///
+/// Each format requires the feature of the same name.
+///
+/// [to_writer_compressed]: fn.to_writer_compressed.html
+/// [from_reader_auto]: fn.from_reader_auto.html
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FileCompression {
+    /// The file is compressed with gzip.
+    #[cfg(feature = "gzip")]
+    Gzip,
+
+    /// The file is compressed with zstd.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
 
-    log::debug!("Type info discovered: {:#?}", serializer.type_infos);
+/// The compression applied to chunks written by a [`Serializer`][Serializer].
+///
+/// [Serializer]: struct.Serializer.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Compression {
+    /// Chunks are compressed with LZ4, which is what Roblox Studio does.
+    Lz4,
+
+    /// Chunks are written uncompressed. This produces larger files, but makes
+    /// the contents of each chunk easier to inspect.
+    None,
+}
 
-    serializer.generate_referents();
+/// A serializer for Roblox binary models and places that gives more control
+/// over the output than [`to_writer_default`][to_writer_default].
+///
+/// [to_writer_default]: fn.to_writer_default.html
+#[derive(Clone)]
+pub struct Serializer {
+    compression: Compression,
+    format_version: u16,
+    validate_refs: bool,
+    skip_non_serialized: bool,
+    on_progress: Option<Arc<ProgressCallback>>,
+    map_property: Option<Arc<MapPropertyCallback>>,
+}
 
-    log::trace!("Referents constructed: {:#?}", serializer.id_to_referent);
+type ProgressCallback = dyn Fn(usize, usize) + Send + Sync;
+type MapPropertyCallback = dyn Fn(&str, &str, &Variant) -> Option<Variant> + Send + Sync;
+
+impl fmt::Debug for Serializer {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("Serializer")
+            .field("compression", &self.compression)
+            .field("format_version", &self.format_version)
+            .field("validate_refs", &self.validate_refs)
+            .field("skip_non_serialized", &self.skip_non_serialized)
+            .field("on_progress", &self.on_progress.is_some())
+            .field("map_property", &self.map_property.is_some())
+            .finish()
+    }
+}
 
-    serializer.write_header()?;
-    serializer.serialize_metadata()?;
-    serializer.serialize_instances()?;
-    serializer.serialize_properties()?;
-    serializer.serialize_parents()?;
-    serializer.serialize_end()?;
+impl Serializer {
+    /// Creates a new `Serializer` with the default options.
+    pub fn new() -> Self {
+        Self {
+            compression: Compression::Lz4,
+            format_version: FILE_VERSION,
+            validate_refs: false,
+            skip_non_serialized: false,
+            on_progress: None,
+            map_property: None,
+        }
+    }
 
-    Ok(())
+    /// Sets the compression that should be applied to each chunk. Defaults to
+    /// `Compression::Lz4`.
+    pub fn compression(self, compression: Compression) -> Self {
+        Self {
+            compression,
+            ..self
+        }
+    }
+
+    /// Sets the format version written into the file header. Defaults to 0.
+    ///
+    /// Every binary file Roblox has written so far uses version 0, which is
+    /// the only version rbx_binary supports. Changes to the format since then
+    /// have been made by adding new property types and versioning individual
+    /// chunks like `SSTR` and `PRNT`, not by changing the header version.
+    /// Pinning the version means that if a future version is supported, files
+    /// written by this serializer won't change without opting in.
+    ///
+    /// Serializing with any other version returns an error.
+    pub fn format_version(self, format_version: u16) -> Self {
+        Self {
+            format_version,
+            ..self
+        }
+    }
+
+    /// Sets whether `Ref` properties should be checked before anything is
+    /// written. Defaults to `false`.
+    ///
+    /// When enabled, serializing returns an error if any `Ref` property points
+    /// to an instance that isn't one of the instances being serialized or one
+    /// of their descendants. `Ref::none()` is always valid.
+    ///
+    /// When disabled, these properties are written as null refs.
+    pub fn validate_refs(self, validate_refs: bool) -> Self {
+        Self {
+            validate_refs,
+            ..self
+        }
+    }
+
+    /// Sets whether properties that the reflection database says Roblox
+    /// doesn't persist should be left out. Defaults to `false`.
+    ///
+    /// The database bundled with rbx_binary marks some properties that are
+    /// persisted, like `BasePart.Size`, as not serializing without naming
+    /// the property they're persisted as, so enabling this drops them too.
+    pub fn skip_non_serialized(self, skip_non_serialized: bool) -> Self {
+        Self {
+            skip_non_serialized,
+            ..self
+        }
+    }
+
+    /// Sets a callback that reports how far along serialization is. It's
+    /// called with the number of instances whose properties have been written
+    /// so far and the total number of instances being serialized, once after
+    /// each class of instance is written. The last call reports that every
+    /// instance has been written.
+    ///
+    /// The callback is invoked synchronously on the thread that's
+    /// serializing, so it should return quickly.
+    pub fn on_progress<F>(self, callback: F) -> Self
+    where
+        F: Fn(usize, usize) + Send + Sync + 'static,
+    {
+        Self {
+            on_progress: Some(Arc::new(callback)),
+            ..self
+        }
+    }
+
+    /// Sets a callback that can change or remove property values as they're
+    /// written, without changing the DOM. It's called with the ClassName of
+    /// the instance, the name of the property, and its value, once for each
+    /// property of each instance being serialized.
+    ///
+    /// Returning `Some` writes the returned value instead. It must still have
+    /// a type that the property can be written as. Returning `None` leaves the
+    /// property out, as if the instance didn't have it. Like other missing
+    /// properties, that means it's written with its default value if other
+    /// instances of the same class still have it.
+    ///
+    /// Instances' names aren't properties in rbx_dom_weak, so they're never
+    /// passed to the callback.
+    pub fn map_property<F>(self, callback: F) -> Self
+    where
+        F: Fn(&str, &str, &Variant) -> Option<Variant> + Send + Sync + 'static,
+    {
+        Self {
+            map_property: Some(Arc::new(callback)),
+            ..self
+        }
+    }
+
+    /// Serializes instances from a `WeakDom` into the given writer, including
+    /// all of their descendants.
+    pub fn serialize<W: Write>(&self, writer: W, dom: &WeakDom, refs: &[Ref]) -> Result<(), Error> {
+        self.serialize_inner(writer, dom, refs, None)
+    }
+
+    /// Serializes instances like [`serialize`](#method.serialize), reusing
+    /// property chunks stored in `cache` by previous calls where possible.
+    ///
+    /// `dirty` must contain every instance that has had a property changed
+    /// since the last time `cache` was used. The property chunks for a class
+    /// are only reused if the same instances of that class are being
+    /// serialized in the same order and none of them are dirty, so adding,
+    /// removing, or moving instances doesn't need to be reported. Properties
+    /// that refer to other instances or to shared strings are always encoded
+    /// again, since their encoding depends on the rest of the file.
+    ///
+    /// The output is identical to `serialize` as long as `dirty` is accurate.
+    /// If a [`map_property`](#method.map_property) callback is set, nothing is
+    /// reused and the cache is emptied.
+    pub fn serialize_incremental<W: Write>(
+        &self,
+        writer: W,
+        dom: &WeakDom,
+        refs: &[Ref],
+        cache: &mut SerializerCache,
+        dirty: &HashSet<Ref>,
+    ) -> Result<(), Error> {
+        self.serialize_inner(writer, dom, refs, Some((cache, dirty)))
+    }
+
+    fn serialize_inner<W: Write>(
+        &self,
+        writer: W,
+        dom: &WeakDom,
+        refs: &[Ref],
+        mut cache: Option<(&mut SerializerCache, &HashSet<Ref>)>,
+    ) -> Result<(), Error> {
+        if self.format_version != FILE_VERSION {
+            return Err(InnerError::UnsupportedFormatVersion {
+                version: self.format_version,
+            }
+            .into());
+        }
+
+        let mapped_properties = self
+            .map_property
+            .as_deref()
+            .map(|map_property| map_properties(dom, refs, map_property));
+
+        // The callback can return different values for properties that
+        // haven't changed, so chunks can't be reused while it's set. Any
+        // chunks cached before now can't be trusted afterwards either, since
+        // they wouldn't reflect changes reported as dirty during this call.
+        if mapped_properties.is_some() {
+            if let Some((cache, _)) = &mut cache {
+                cache.props.clear();
+            }
+        }
+
+        // Skipped properties aren't written, so their cached chunks would miss
+        // any changes reported as dirty during this call.
+        if self.skip_non_serialized {
+            if let Some((cache, _)) = &mut cache {
+                let database = rbx_reflection_database::get();
+                cache.props.retain(|(class_name, prop_name), _| {
+                    database.property_serializes(class_name, prop_name)
+                });
+            }
+        }
+
+        let mut serializer = BinarySerializer::new(dom, writer, self.compression, cache);
+        serializer.on_progress = self.on_progress.as_deref();
+        serializer.skip_non_serialized = self.skip_non_serialized;
+        serializer.mapped_properties = mapped_properties.as_ref();
+
+        serializer.add_instances(refs)?;
+
+        if self.validate_refs {
+            serializer.validate_refs()?;
+        }
+
+        log::debug!("Type info discovered: {:#?}", serializer.type_infos);
+
+        serializer.generate_referents();
+
+        log::trace!("Referents constructed: {:#?}", serializer.id_to_referent);
+
+        serializer.write_header()?;
+        serializer.serialize_metadata()?;
+        serializer.serialize_shared_strings()?;
+        serializer.serialize_instances()?;
+        serializer.serialize_properties()?;
+        serializer.serialize_parents()?;
+        serializer.serialize_unknown_chunks()?;
+        serializer.serialize_end()?;
+
+        Ok(())
+    }
+}
+
+impl Default for Serializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Runs the given callback over every property of the given instances and
+/// their descendants, collecting the properties that should be written in
+/// place of each instance's own.
+fn map_properties(
+    dom: &WeakDom,
+    refs: &[Ref],
+    map_property: &MapPropertyCallback,
+) -> HashMap<Ref, HashMap<String, Variant>> {
+    let mut mapped = HashMap::new();
+
+    for &root in refs {
+        // Missing instances are reported when type info is collected.
+        if dom.get_by_ref(root).is_none() {
+            continue;
+        }
+
+        for referent in iter::once(root).chain(dom.descendants(root)) {
+            let instance = dom.get_by_ref(referent).unwrap();
+            let properties = instance
+                .properties
+                .iter()
+                .filter_map(|(name, value)| {
+                    let value = map_property(&instance.class, name, value)?;
+                    Some((name.clone(), value))
+                })
+                .collect();
+
+            mapped.insert(referent, properties);
+        }
+    }
+
+    mapped
+}
+
+/// Holds encoded property chunks between calls to
+/// [`Serializer::serialize_incremental`][serialize_incremental] so that chunks
+/// for unchanged instances don't need to be encoded and compressed again.
+///
+/// A cache should only be used with one DOM.
+///
+/// [serialize_incremental]: struct.Serializer.html#method.serialize_incremental
+#[derive(Debug, Default)]
+pub struct SerializerCache {
+    /// Encoded PROP chunks, keyed by class name and property name.
+    props: HashMap<(String, String), CachedPropChunk>,
+}
+
+impl SerializerCache {
+    /// Creates a new, empty `SerializerCache`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug)]
+struct CachedPropChunk {
+    /// Everything about the chunk besides its values that affects the bytes
+    /// that get written. If any of these change, the chunk can't be reused.
+    type_id: u32,
+    prop_type: Type,
+    compression: ChunkCompression,
+    object_refs: Vec<Ref>,
+
+    /// The chunk, including its header, exactly as it was written.
+    contents: Vec<u8>,
 }
 
 /// Represents all of the state during a single serialization session. A new
@@ -121,6 +539,32 @@ struct BinarySerializer<'a, W> {
     /// The next type ID that should be assigned if a type is discovered and
     /// added to the serializer.
     next_type_id: u32,
+
+    /// All of the unique SharedString values that we'll be writing into the
+    /// SSTR chunk, in the order they were discovered.
+    shared_strings: Vec<SharedString>,
+
+    /// A map from the hash of each SharedString to its index in
+    /// `shared_strings`, which is how PROP chunks refer to them.
+    shared_string_ids: HashMap<SharedStringHash, u32>,
+
+    /// The compression to apply to chunks that contain instance data.
+    compression: ChunkCompression,
+
+    /// If this is an incremental serialization, the cache of property chunks
+    /// to read from and update, and the instances that have changed since the
+    /// cache was last used.
+    cache: Option<(&'a mut SerializerCache, &'a HashSet<Ref>)>,
+
+    /// The callback to report progress to, if one was given.
+    on_progress: Option<&'a ProgressCallback>,
+
+    /// If a `map_property` callback was given, the properties to write for
+    /// each instance instead of the ones the instance has.
+    mapped_properties: Option<&'a HashMap<Ref, HashMap<String, Variant>>>,
+
+    /// Whether properties that Roblox doesn't persist are left out.
+    skip_non_serialized: bool,
 }
 
 /// An instance class that our serializer knows about. We should have one struct
@@ -154,10 +598,25 @@ struct TypeInfo {
 struct PropInfo {
     prop_type: Type,
     default_value: Cow<'static, Variant>,
+
+    /// For properties holding `Variant::Unknown` values, the type ID that's
+    /// written in place of `prop_type`. Values of these properties are written
+    /// back out exactly as they were read.
+    unknown_type: Option<u8>,
 }
 
 impl<'a, W: Write> BinarySerializer<'a, W> {
-    fn new(dom: &'a WeakDom, output: W) -> Self {
+    fn new(
+        dom: &'a WeakDom,
+        output: W,
+        compression: Compression,
+        cache: Option<(&'a mut SerializerCache, &'a HashSet<Ref>)>,
+    ) -> Self {
+        let compression = match compression {
+            Compression::Lz4 => ChunkCompression::Compressed,
+            Compression::None => ChunkCompression::Uncompressed,
+        };
+
         BinarySerializer {
             dom,
             output,
@@ -165,27 +624,120 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
             id_to_referent: HashMap::new(),
             type_infos: BTreeMap::new(),
             next_type_id: 0,
+            shared_strings: Vec::new(),
+            shared_string_ids: HashMap::new(),
+            compression,
+            cache,
+            on_progress: None,
+            mapped_properties: None,
+            skip_non_serialized: false,
         }
     }
 
+    /// Returns the properties that should be written for the given instance,
+    /// which are the instance's own unless they've been mapped.
+    fn properties_of(&self, referent: Ref, instance: &'a Instance) -> &'a HashMap<String, Variant> {
+        match self.mapped_properties {
+            Some(mapped) => &mapped[&referent],
+            None => &instance.properties,
+        }
+    }
+
+    /// Tells whether the given property should be left out of the file
+    /// because Roblox doesn't persist it.
+    fn is_skipped(&self, class_name: &str, prop_name: &str) -> bool {
+        self.skip_non_serialized
+            && !rbx_reflection_database::get().property_serializes(class_name, prop_name)
+    }
+
     /// Mark the given instance IDs and all of their descendants as intended for
     /// serialization with this serializer.
     fn add_instances(&mut self, referents: &[Ref]) -> Result<(), InnerError> {
         let mut to_visit = VecDeque::new();
         to_visit.extend(referents);
 
+        let dom = self.dom;
+
         while let Some(referent) = to_visit.pop_front() {
             self.relevant_instances.push(referent);
             self.collect_type_info(referent)?;
 
             // TODO: Turn into error
-            let instance = self.dom.get_by_ref(referent).unwrap();
+            let instance = dom.get_by_ref(referent).unwrap();
             to_visit.extend(instance.children());
+
+            for prop_value in self.properties_of(referent, instance).values() {
+                if let Variant::SharedString(value) = prop_value {
+                    self.add_shared_string(value);
+                }
+            }
+        }
+
+        // Instances that are missing a SharedString property will be written
+        // with the property's default value, so those need to be present in
+        // the SSTR chunk too.
+        let default_shared_strings: Vec<SharedString> = self
+            .type_infos
+            .values()
+            .flat_map(|type_info| type_info.properties.values())
+            .filter_map(|prop_info| match prop_info.default_value.as_ref() {
+                Variant::SharedString(value) => Some(value.clone()),
+                _ => None,
+            })
+            .collect();
+
+        for value in &default_shared_strings {
+            self.add_shared_string(value);
         }
 
         Ok(())
     }
 
+    /// Checks that every `Ref` property on the instances being serialized
+    /// points to another instance being serialized.
+    fn validate_refs(&self) -> Result<(), InnerError> {
+        let relevant: HashSet<Ref> = self.relevant_instances.iter().copied().collect();
+
+        for &referent in &self.relevant_instances {
+            let instance = self.dom.get_by_ref(referent).unwrap();
+            let properties = self.properties_of(referent, instance);
+
+            let mut prop_names: Vec<&String> = properties.keys().collect();
+            prop_names.sort();
+
+            for prop_name in prop_names {
+                if self.is_skipped(&instance.class, prop_name) {
+                    continue;
+                }
+
+                if let Variant::Ref(target) = &properties[prop_name] {
+                    if target.is_some() && !relevant.contains(target) {
+                        return Err(InnerError::InvalidRef {
+                            type_name: instance.class.clone(),
+                            prop_name: prop_name.clone(),
+                            instance_full_name: self.full_name_for(referent),
+                            target: *target,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Registers a SharedString to be written into the SSTR chunk, unless an
+    /// identical value has already been registered.
+    fn add_shared_string(&mut self, value: &SharedString) {
+        let hash = value.hash();
+
+        if !self.shared_string_ids.contains_key(&hash) {
+            let id = self.shared_strings.len() as u32;
+            self.shared_strings.push(value.clone());
+            self.shared_string_ids.insert(hash, id);
+        }
+    }
+
     /// Collect information about all the different types of instance and their
     /// properties.
     fn collect_type_info(&mut self, referent: Ref) -> Result<(), InnerError> {
@@ -194,10 +746,38 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
             .get_by_ref(referent)
             .ok_or_else(|| InnerError::InvalidInstanceId { referent })?;
 
+        let properties = self.properties_of(referent, instance);
+        let skip_non_serialized = self.skip_non_serialized;
+
         let type_info = self.get_or_create_type_info(&instance.class);
         type_info.object_refs.push(referent);
 
-        for (prop_name, prop_value) in &instance.properties {
+        for (prop_name, prop_value) in properties {
+            if skip_non_serialized
+                && !rbx_reflection_database::get().property_serializes(&instance.class, prop_name)
+            {
+                continue;
+            }
+
+            if let Variant::Unknown(value) = prop_value {
+                let ser_name = find_serialized_property_descriptor(&instance.class, prop_name)
+                    .map(|descriptor| descriptor.name.as_ref())
+                    .unwrap_or(prop_name.as_str());
+
+                if !type_info.properties.contains_key(ser_name) {
+                    type_info.properties.insert(
+                        ser_name.to_owned(),
+                        PropInfo {
+                            prop_type: Type::String,
+                            default_value: Cow::Owned(prop_value.clone()),
+                            unknown_type: Some(value.type_id),
+                        },
+                    );
+                }
+
+                continue;
+            }
+
             let ser_name;
             let ser_rbx_type;
 
@@ -207,10 +787,14 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                     ser_rbx_type = match &descriptor.data_type {
                         DataType::Value(ty) => *ty,
                         DataType::Enum(_) => VariantType::EnumValue,
-                        _ => {
+                        unknown_type => {
                             // rbx_binary is not new enough to handle this kind
                             // of property, whatever it is.
-                            panic!("Unsupported property type");
+                            return Err(InnerError::UnsupportedPropType {
+                                type_name: instance.class.clone(),
+                                prop_name: prop_name.clone(),
+                                prop_type: format!("{:?}", unknown_type),
+                            });
                         }
                     };
                 }
@@ -224,9 +808,8 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                 let default_value = type_info
                     .class_descriptor
                     .and_then(|class| {
-                        class
-                            .default_properties
-                            .get(prop_name.as_str())
+                        rbx_reflection_database::get()
+                            .find_default_property(class, prop_name.as_str())
                             .map(Cow::Borrowed)
                     })
                     .or_else(|| Self::fallback_default_value(ser_rbx_type).map(Cow::Owned))
@@ -236,7 +819,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                         InnerError::UnsupportedPropType {
                             type_name: instance.class.clone(),
                             prop_name: prop_name.clone(),
-                            prop_type: format!("{:?}", ser_rbx_type),
+                            prop_type: ser_rbx_type.name().to_owned(),
                         }
                     })?;
 
@@ -247,7 +830,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                     InnerError::UnsupportedPropType {
                         type_name: instance.class.clone(),
                         prop_name: prop_name.clone(),
-                        prop_type: format!("{:?}", ser_rbx_type),
+                        prop_type: ser_rbx_type.name().to_owned(),
                     }
                 })?;
 
@@ -256,6 +839,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                     PropInfo {
                         prop_type: ser_type,
                         default_value,
+                        unknown_type: None,
                     },
                 );
             }
@@ -295,6 +879,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                 PropInfo {
                     prop_type: Type::String,
                     default_value: Cow::Owned(Variant::String(String::new())),
+                    unknown_type: None,
                 },
             );
 
@@ -346,8 +931,52 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
 
     /// Write out any metadata about this file, stored in a chunk named META.
     fn serialize_metadata(&mut self) -> Result<(), InnerError> {
-        log::trace!("Writing metadata (currently no-op)");
-        // TODO: There is no concept of metadata in a dom yet.
+        let metadata = self.dom.metadata();
+        if metadata.is_empty() {
+            return Ok(());
+        }
+
+        log::trace!("Writing {} metadata entries", metadata.len());
+
+        let mut chunk = ChunkBuilder::new(b"META", self.compression);
+
+        chunk.write_u32::<LittleEndian>(metadata.len() as u32)?;
+
+        for (key, value) in metadata {
+            chunk.write_string(key)?;
+            chunk.write_string(value)?;
+        }
+
+        chunk.dump(&mut self.output)?;
+
+        Ok(())
+    }
+
+    /// Write out every unique SharedString value used by the instances we're
+    /// serializing, stored in a chunk named SSTR. PROP chunks refer to these
+    /// values by their index in this chunk.
+    fn serialize_shared_strings(&mut self) -> Result<(), InnerError> {
+        if self.shared_strings.is_empty() {
+            return Ok(());
+        }
+
+        log::trace!("Writing {} shared strings", self.shared_strings.len());
+
+        let mut chunk = ChunkBuilder::new(b"SSTR", self.compression);
+
+        chunk.write_u32::<LittleEndian>(0)?; // SSTR version 0
+        chunk.write_u32::<LittleEndian>(self.shared_strings.len() as u32)?;
+
+        for value in &self.shared_strings {
+            // Roblox uses a 16 byte MD5 hash here, but doesn't check it when
+            // loading files. We write the first 16 bytes of our own hash
+            // instead of pulling in another hash function.
+            chunk.write_all(&value.hash().as_bytes()[..16])?;
+            chunk.write_binary_string(value.data())?;
+        }
+
+        chunk.dump(&mut self.output)?;
+
         Ok(())
     }
 
@@ -363,7 +992,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                 type_info.object_refs.len()
             );
 
-            let mut chunk = ChunkBuilder::new(b"INST", ChunkCompression::Compressed);
+            let mut chunk = ChunkBuilder::new(b"INST", self.compression);
 
             chunk.write_u32::<LittleEndian>(type_info.type_id)?;
             chunk.write_string(type_name)?;
@@ -410,6 +1039,9 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
     fn serialize_properties(&mut self) -> Result<(), InnerError> {
         log::trace!("Writing properties");
 
+        let total_instances = self.relevant_instances.len();
+        let mut instances_written = 0;
+
         for (type_name, type_info) in &self.type_infos {
             for (prop_name, prop_info) in &type_info.properties {
                 log::trace!(
@@ -419,11 +1051,39 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                     prop_info.prop_type
                 );
 
-                let mut chunk = ChunkBuilder::new(b"PROP", ChunkCompression::Compressed);
+                // Ref and SharedString values are encoded as indices that
+                // depend on the rest of the file, so they can't be reused.
+                // Unknown values don't have a real prop_type to compare
+                // against, so they're always written again too, as are
+                // values from a `map_property` callback.
+                let cacheable = prop_info.prop_type != Type::Ref
+                    && prop_info.prop_type != Type::SharedString
+                    && prop_info.unknown_type.is_none()
+                    && self.mapped_properties.is_none();
+                let cache_key = (type_name.clone(), prop_name.clone());
+
+                if let Some((cache, dirty)) = &self.cache {
+                    if let Some(cached) = cache.props.get(&cache_key) {
+                        let reusable = cacheable
+                            && cached.type_id == type_info.type_id
+                            && cached.prop_type == prop_info.prop_type
+                            && cached.compression == self.compression
+                            && cached.object_refs == type_info.object_refs
+                            && !type_info.object_refs.iter().any(|id| dirty.contains(id));
+
+                        if reusable {
+                            log::trace!("Reusing cached chunk for {}.{}", type_name, prop_name);
+                            self.output.write_all(&cached.contents)?;
+                            continue;
+                        }
+                    }
+                }
+
+                let mut chunk = ChunkBuilder::new(b"PROP", self.compression);
 
                 chunk.write_u32::<LittleEndian>(type_info.type_id)?;
                 chunk.write_string(&prop_name)?;
-                chunk.write_u8(prop_info.prop_type as u8)?;
+                chunk.write_u8(prop_info.unknown_type.unwrap_or(prop_info.prop_type as u8))?;
 
                 let dom = &self.dom;
                 let values = type_info
@@ -441,8 +1101,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                         if prop_name == "Name" {
                             Cow::Owned(Variant::String(instance.name.clone()))
                         } else {
-                            instance
-                                .properties
+                            self.properties_of(*id, instance)
                                 .get(prop_name)
                                 .map(Cow::Borrowed)
                                 .unwrap_or(Cow::Borrowed(prop_info.default_value.borrow()))
@@ -458,12 +1117,50 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                             type_name: type_name.clone(),
                             prop_name: prop_name.clone(),
                             valid_type_names,
-                            actual_type_name: format!("{:?}", bad_value.ty()),
+                            actual_type_name: bad_value.type_name().to_owned(),
                             instance_full_name: self.full_name_for(type_info.object_refs[i]),
                         })
                     };
 
                 match prop_info.prop_type {
+                    _ if prop_info.unknown_type.is_some() => {
+                        for (i, referent) in type_info.object_refs.iter().enumerate() {
+                            let instance = dom.get_by_ref(*referent).unwrap();
+
+                            match self.properties_of(*referent, instance).get(prop_name) {
+                                Some(Variant::Unknown(value))
+                                    if Some(value.type_id) == prop_info.unknown_type =>
+                                {
+                                    // Values we couldn't split evenly only
+                                    // make sense as part of the column they
+                                    // came from, so the column must be made
+                                    // up of the same instances in the same
+                                    // order.
+                                    if value.column_index != i
+                                        || value.column_len != type_info.object_refs.len()
+                                    {
+                                        return Err(InnerError::UnknownValuesChanged {
+                                            type_name: type_name.clone(),
+                                            prop_name: prop_name.clone(),
+                                            instance_full_name: self.full_name_for(*referent),
+                                        });
+                                    }
+
+                                    chunk.write_all(value.data.as_ref())?;
+                                }
+                                Some(other) => {
+                                    return type_mismatch(i, other, "Unknown");
+                                }
+                                None => {
+                                    return Err(InnerError::MissingUnknownValue {
+                                        type_name: type_name.clone(),
+                                        prop_name: prop_name.clone(),
+                                        instance_full_name: self.full_name_for(*referent),
+                                    });
+                                }
+                            }
+                        }
+                    }
                     Type::String => {
                         for (i, rbx_value) in values {
                             match rbx_value.as_ref() {
@@ -476,11 +1173,26 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                                 Variant::BinaryString(value) => {
                                     chunk.write_binary_string(value.as_ref())?;
                                 }
+                                Variant::Attributes(value) => {
+                                    let mut buffer = Vec::new();
+                                    value.to_writer(&mut buffer).map_err(|source| {
+                                        InnerError::InvalidAttributes {
+                                            instance_full_name: self
+                                                .full_name_for(type_info.object_refs[i]),
+                                            source,
+                                        }
+                                    })?;
+
+                                    chunk.write_binary_string(&buffer)?;
+                                }
+                                Variant::Tags(value) => {
+                                    chunk.write_binary_string(&value.encode())?;
+                                }
                                 _ => {
                                     return type_mismatch(
                                         i,
                                         &rbx_value,
-                                        "String, Content, or BinaryString",
+                                        "String, Content, BinaryString, Attributes, or Tags",
                                     );
                                 }
                             }
@@ -498,16 +1210,329 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
                             }
                         }
                     }
+                    Type::BrickColor => {
+                        let mut numbers = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::BrickColor(value) => {
+                                    numbers.push(u32::from(value.to_number()));
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "BrickColor");
+                                }
+                            }
+                        }
+
+                        chunk.write_interleaved_u32_array(numbers.into_iter())?;
+                    }
+                    Type::CFrame => {
+                        let mut cframes = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::CFrame(value) => cframes.push(*value),
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "CFrame");
+                                }
+                            }
+                        }
+
+                        write_cframe_array(&mut chunk, &cframes)?;
+                    }
+                    Type::OptionalCFrame => {
+                        let mut cframes = Vec::with_capacity(type_info.object_refs.len());
+                        let mut present = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::OptionalCFrame(value) => {
+                                    // Missing values still need an entry in the
+                                    // CFrame column, which Roblox ignores.
+                                    cframes.push(value.unwrap_or_else(|| {
+                                        CFrame::new(
+                                            Vector3::new(0.0, 0.0, 0.0),
+                                            Matrix3::identity(),
+                                        )
+                                    }));
+                                    present.push(value.is_some());
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "OptionalCFrame");
+                                }
+                            }
+                        }
+
+                        chunk.write_u8(Type::CFrame as u8)?;
+                        write_cframe_array(&mut chunk, &cframes)?;
+
+                        chunk.write_u8(Type::Bool as u8)?;
+                        for value in present {
+                            chunk.write_bool(value)?;
+                        }
+                    }
+                    Type::Ref => {
+                        let mut referents = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::Ref(value) => {
+                                    // Refs to instances that aren't part of
+                                    // this file are written as null refs.
+                                    let referent =
+                                        self.id_to_referent.get(value).copied().unwrap_or(-1);
+                                    referents.push(referent);
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "Ref");
+                                }
+                            }
+                        }
+
+                        chunk.write_referents(referents.into_iter())?;
+                    }
+                    Type::SharedString => {
+                        let mut entries = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::SharedString(value) => {
+                                    // This index will be present because we
+                                    // register every SharedString value when
+                                    // adding instances.
+                                    entries.push(self.shared_string_ids[&value.hash()]);
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "SharedString");
+                                }
+                            }
+                        }
+
+                        chunk.write_interleaved_u32_array(entries.into_iter())?;
+                    }
+                    Type::UniqueId => {
+                        let mut ids = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::UniqueId(value) => ids.push(*value),
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "UniqueId");
+                                }
+                            }
+                        }
+
+                        write_unique_id_array(&mut chunk, &ids)?;
+                    }
+                    Type::SecurityCapabilities => {
+                        let mut bitmasks = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::SecurityCapabilities(value) => {
+                                    bitmasks.push(value.bits() as i64)
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "SecurityCapabilities");
+                                }
+                            }
+                        }
+
+                        chunk.write_interleaved_i64_array(bitmasks.into_iter())?;
+                    }
+                    Type::Font => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::Font(value) => {
+                                    chunk.write_string(&value.family)?;
+                                    chunk.write_u16::<LittleEndian>(value.weight.to_u16())?;
+                                    chunk.write_u8(value.style.to_u8())?;
+                                    chunk.write_string(
+                                        value.cached_face_id.as_deref().unwrap_or_default(),
+                                    )?;
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "Font");
+                                }
+                            }
+                        }
+                    }
+                    Type::PhysicalProperties => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::PhysicalProperties(PhysicalProperties::Default) => {
+                                    chunk.write_u8(0)?;
+                                }
+                                Variant::PhysicalProperties(PhysicalProperties::Custom(value)) => {
+                                    chunk.write_u8(1)?;
+                                    chunk.write_f32::<LittleEndian>(value.density)?;
+                                    chunk.write_f32::<LittleEndian>(value.friction)?;
+                                    chunk.write_f32::<LittleEndian>(value.elasticity)?;
+                                    chunk.write_f32::<LittleEndian>(value.friction_weight)?;
+                                    chunk.write_f32::<LittleEndian>(value.elasticity_weight)?;
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "PhysicalProperties");
+                                }
+                            }
+                        }
+                    }
+                    Type::Vector3int16 => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::Vector3int16(value) => {
+                                    chunk.write_i16::<LittleEndian>(value.x)?;
+                                    chunk.write_i16::<LittleEndian>(value.y)?;
+                                    chunk.write_i16::<LittleEndian>(value.z)?;
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "Vector3int16");
+                                }
+                            }
+                        }
+                    }
+                    Type::NumberSequence => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::NumberSequence(value) => {
+                                    chunk.write_u32::<LittleEndian>(value.keypoints.len() as u32)?;
+
+                                    for keypoint in &value.keypoints {
+                                        chunk.write_f32::<LittleEndian>(keypoint.time)?;
+                                        chunk.write_f32::<LittleEndian>(keypoint.value)?;
+                                        chunk.write_f32::<LittleEndian>(keypoint.envelope)?;
+                                    }
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "NumberSequence");
+                                }
+                            }
+                        }
+                    }
+                    Type::Faces => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::Faces(value) => chunk.write_u8(value.bits())?,
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "Faces");
+                                }
+                            }
+                        }
+                    }
+                    Type::Axes => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::Axes(value) => chunk.write_u8(value.bits())?,
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "Axes");
+                                }
+                            }
+                        }
+                    }
+                    Type::NumberRange => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::NumberRange(value) => {
+                                    chunk.write_f32::<LittleEndian>(value.min)?;
+                                    chunk.write_f32::<LittleEndian>(value.max)?;
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "NumberRange");
+                                }
+                            }
+                        }
+                    }
+                    Type::Ray => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::Ray(value) => {
+                                    chunk.write_f32::<LittleEndian>(value.origin.x)?;
+                                    chunk.write_f32::<LittleEndian>(value.origin.y)?;
+                                    chunk.write_f32::<LittleEndian>(value.origin.z)?;
+                                    chunk.write_f32::<LittleEndian>(value.direction.x)?;
+                                    chunk.write_f32::<LittleEndian>(value.direction.y)?;
+                                    chunk.write_f32::<LittleEndian>(value.direction.z)?;
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "Ray");
+                                }
+                            }
+                        }
+                    }
+                    Type::Rect => {
+                        let mut rects = Vec::with_capacity(type_info.object_refs.len());
+
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::Rect(value) => rects.push(*value),
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "Rect");
+                                }
+                            }
+                        }
+
+                        chunk.write_interleaved_f32_array(rects.iter().map(|rect| rect.min.x))?;
+                        chunk.write_interleaved_f32_array(rects.iter().map(|rect| rect.min.y))?;
+                        chunk.write_interleaved_f32_array(rects.iter().map(|rect| rect.max.x))?;
+                        chunk.write_interleaved_f32_array(rects.iter().map(|rect| rect.max.y))?;
+                    }
+                    Type::ColorSequence => {
+                        for (i, rbx_value) in values {
+                            match rbx_value.as_ref() {
+                                Variant::ColorSequence(value) => {
+                                    chunk.write_u32::<LittleEndian>(value.keypoints.len() as u32)?;
+
+                                    for keypoint in &value.keypoints {
+                                        chunk.write_f32::<LittleEndian>(keypoint.time)?;
+                                        chunk.write_f32::<LittleEndian>(keypoint.color.r)?;
+                                        chunk.write_f32::<LittleEndian>(keypoint.color.g)?;
+                                        chunk.write_f32::<LittleEndian>(keypoint.color.b)?;
+
+                                        // ColorSequence keypoints have an
+                                        // unused envelope, which is always zero.
+                                        chunk.write_f32::<LittleEndian>(0.0)?;
+                                    }
+                                }
+                                _ => {
+                                    return type_mismatch(i, &rbx_value, "ColorSequence");
+                                }
+                            }
+                        }
+                    }
                     _ => {
                         return Err(InnerError::UnsupportedPropType {
                             type_name: type_name.clone(),
                             prop_name: prop_name.clone(),
-                            prop_type: format!("binary type {:?}", prop_info.prop_type),
+                            prop_type: prop_info.default_value.type_name().to_owned(),
                         });
                     }
                 }
 
-                chunk.dump(&mut self.output)?;
+                match &mut self.cache {
+                    Some((cache, _)) if cacheable => {
+                        let mut contents = Vec::new();
+                        chunk.dump(&mut contents)?;
+                        self.output.write_all(&contents)?;
+
+                        cache.props.insert(
+                            cache_key,
+                            CachedPropChunk {
+                                type_id: type_info.type_id,
+                                prop_type: prop_info.prop_type,
+                                compression: self.compression,
+                                object_refs: type_info.object_refs.clone(),
+                                contents,
+                            },
+                        );
+                    }
+                    _ => chunk.dump(&mut self.output)?,
+                }
+            }
+
+            instances_written += type_info.object_refs.len();
+
+            if let Some(on_progress) = self.on_progress {
+                on_progress(instances_written, total_instances);
             }
         }
 
@@ -519,7 +1544,7 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
     fn serialize_parents(&mut self) -> Result<(), InnerError> {
         log::trace!("Writing parent relationships");
 
-        let mut chunk = ChunkBuilder::new(b"PRNT", ChunkCompression::Compressed);
+        let mut chunk = ChunkBuilder::new(b"PRNT", self.compression);
 
         chunk.write_u8(0)?; // PRNT version 0
         chunk.write_u32::<LittleEndian>(self.relevant_instances.len() as u32)?;
@@ -553,6 +1578,21 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
         Ok(())
     }
 
+    /// Write out the chunks that weren't understood when the DOM was read from
+    /// a binary file. Their original positions weren't kept, so they're all
+    /// written right before the END chunk, where Roblox puts `SIGN` chunks.
+    fn serialize_unknown_chunks(&mut self) -> Result<(), InnerError> {
+        for unknown_chunk in self.dom.unknown_chunks() {
+            log::trace!("Writing unknown chunk {:?}", unknown_chunk.name);
+
+            let mut chunk = ChunkBuilder::new(&unknown_chunk.name, self.compression);
+            chunk.write_all(&unknown_chunk.data)?;
+            chunk.dump(&mut self.output)?;
+        }
+
+        Ok(())
+    }
+
     /// Write the fixed, uncompressed end chunk used to verify that the file
     /// hasn't been truncated mistakenly. This chunk is named END\0, with a zero
     /// byte at the end.
@@ -592,7 +1632,105 @@ impl<'a, W: Write> BinarySerializer<'a, W> {
             VariantType::String => Variant::String(String::new()),
             VariantType::BinaryString => Variant::BinaryString(BinaryString::new()),
             VariantType::Bool => Variant::Bool(false),
+            VariantType::BrickColor => Variant::BrickColor(BrickColor::MediumStoneGrey),
+            VariantType::Content => Variant::Content(Content::new()),
+            VariantType::CFrame => Variant::CFrame(CFrame::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                Matrix3::identity(),
+            )),
+            VariantType::OptionalCFrame => Variant::OptionalCFrame(None),
+            VariantType::Attributes => Variant::Attributes(Attributes::new()),
+            VariantType::Tags => Variant::Tags(Tags::new()),
+            VariantType::SharedString => Variant::SharedString(SharedString::new(Vec::new())),
+            VariantType::Font => Variant::Font(Font::default()),
+            VariantType::PhysicalProperties => {
+                Variant::PhysicalProperties(PhysicalProperties::Default)
+            }
+            VariantType::Faces => Variant::Faces(Faces::empty()),
+            VariantType::Axes => Variant::Axes(Axes::empty()),
+            VariantType::Ref => Variant::Ref(Ref::none()),
+            VariantType::UniqueId => Variant::UniqueId(UniqueId::new(0, 0, 0)),
+            VariantType::SecurityCapabilities => {
+                Variant::SecurityCapabilities(SecurityCapabilities::default())
+            }
+            VariantType::NumberRange => Variant::NumberRange(NumberRange::new(0.0, 0.0)),
+            VariantType::Ray => Variant::Ray(Ray::new(
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 0.0),
+            )),
+            VariantType::Rect => {
+                Variant::Rect(Rect::new(Vector2::new(0.0, 0.0), Vector2::new(0.0, 0.0)))
+            }
+            VariantType::NumberSequence => Variant::NumberSequence(NumberSequence {
+                keypoints: vec![
+                    NumberSequenceKeypoint::new(0.0, 0.0, 0.0),
+                    NumberSequenceKeypoint::new(1.0, 0.0, 0.0),
+                ],
+            }),
+            VariantType::ColorSequence => Variant::ColorSequence(ColorSequence {
+                keypoints: vec![
+                    ColorSequenceKeypoint::new(0.0, Color3::new(1.0, 1.0, 1.0)),
+                    ColorSequenceKeypoint::new(1.0, Color3::new(1.0, 1.0, 1.0)),
+                ],
+            }),
+            VariantType::Vector3int16 => Variant::Vector3int16(Vector3int16::new(0, 0, 0)),
             _ => return None,
         })
     }
 }
+
+/// Writes CFrame values in the column format used by the binary format: a
+/// rotation for each value, followed by interleaved arrays of each position
+/// component.
+fn write_cframe_array<W: Write>(output: &mut W, values: &[CFrame]) -> io::Result<()> {
+    for value in values {
+        let rotation = &value.orientation;
+
+        // Axis-aligned rotations can be written as a single byte, which is
+        // what Roblox Studio does.
+        if let Some(id) = special_rotation_id(rotation) {
+            output.write_u8(id)?;
+            continue;
+        }
+
+        // A rotation ID of zero means that the full rotation matrix follows.
+        output.write_u8(0)?;
+
+        for row in &[rotation.x, rotation.y, rotation.z] {
+            output.write_f32::<LittleEndian>(row.x)?;
+            output.write_f32::<LittleEndian>(row.y)?;
+            output.write_f32::<LittleEndian>(row.z)?;
+        }
+    }
+
+    output.write_interleaved_f32_array(values.iter().map(|value| value.position.x))?;
+    output.write_interleaved_f32_array(values.iter().map(|value| value.position.y))?;
+    output.write_interleaved_f32_array(values.iter().map(|value| value.position.z))?;
+
+    Ok(())
+}
+
+/// Writes UniqueId values in the format used by the binary format. Each value
+/// is 16 big-endian bytes: the random number rotated left by one bit, then the
+/// time, then the index. The bytes are interleaved across all values, like
+/// other column types.
+fn write_unique_id_array<W: Write>(output: &mut W, values: &[UniqueId]) -> io::Result<()> {
+    let encoded: Vec<[u8; 16]> = values
+        .iter()
+        .map(|value| {
+            let mut bytes = [0; 16];
+            bytes[0..8].copy_from_slice(&value.random.rotate_left(1).to_be_bytes());
+            bytes[8..12].copy_from_slice(&value.time.to_be_bytes());
+            bytes[12..16].copy_from_slice(&value.index.to_be_bytes());
+            bytes
+        })
+        .collect();
+
+    for i in 0..16 {
+        for bytes in &encoded {
+            output.write_u8(bytes[i])?;
+        }
+    }
+
+    Ok(())
+}
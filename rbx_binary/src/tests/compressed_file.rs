@@ -0,0 +1,67 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{encode, from_reader_auto, to_writer_compressed, FileCompression};
+
+fn model() -> WeakDom {
+    WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello, world!"),
+        InstanceBuilder::new("Folder").with_name("Empty"),
+    ]))
+}
+
+fn summarize(dom: &WeakDom) -> Vec<(String, Option<Variant>)> {
+    dom.descendants_of_root()
+        .map(|referent| {
+            let instance = dom.get_by_ref(referent).unwrap();
+            (
+                instance.name.clone(),
+                instance.properties.get("Value").cloned(),
+            )
+        })
+        .collect()
+}
+
+fn round_trip(compression: FileCompression, magic: &[u8]) {
+    let tree = model();
+
+    let mut buffer = Vec::new();
+    to_writer_compressed(&mut buffer, &tree, tree.root().children(), compression)
+        .expect("failed to encode model");
+    assert!(buffer.starts_with(magic));
+
+    let decoded = from_reader_auto(buffer.as_slice()).expect("failed to decode model");
+    assert_eq!(decoded.root().children().len(), 2);
+    assert_eq!(summarize(&decoded), summarize(&tree));
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn gzip_round_trip() {
+    round_trip(FileCompression::Gzip, &[0x1f, 0x8b]);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn zstd_round_trip() {
+    round_trip(FileCompression::Zstd, &[0x28, 0xb5, 0x2f, 0xfd]);
+}
+
+/// Ensures that files that weren't compressed as a whole are still read.
+#[test]
+fn uncompressed_file() {
+    let tree = model();
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = from_reader_auto(buffer.as_slice()).expect("failed to decode model");
+    assert_eq!(summarize(&decoded), summarize(&tree));
+}
+
+/// Ensures that files too short to contain a magic number fail to decode
+/// instead of panicking.
+#[test]
+fn truncated_file() {
+    assert!(from_reader_auto(&[0x1f][..]).is_err());
+    assert!(from_reader_auto(&[][..]).is_err());
+}
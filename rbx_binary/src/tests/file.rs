@@ -0,0 +1,44 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{encode, from_file, from_reader_default};
+
+/// Ensures that a model read from a memory-mapped file matches the model that
+/// was written.
+#[test]
+fn from_file_round_trip() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello, world!"),
+        InstanceBuilder::new("Folder").with_name("Empty"),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("model.rbxm");
+    std::fs::write(&path, &buffer).unwrap();
+
+    let from_file = from_file(&path).expect("failed to decode model from file");
+    let from_buffer = from_reader_default(buffer.as_slice()).unwrap();
+
+    let summarize = |dom: &WeakDom| -> Vec<(String, Option<Variant>)> {
+        dom.descendants_of_root()
+            .map(|referent| {
+                let instance = dom.get_by_ref(referent).unwrap();
+                (
+                    instance.name.clone(),
+                    instance.properties.get("Value").cloned(),
+                )
+            })
+            .collect()
+    };
+
+    assert_eq!(from_file.root().children().len(), 2);
+    assert_eq!(summarize(&from_file), summarize(&from_buffer));
+}
+
+#[test]
+fn from_file_missing() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(from_file(dir.path().join("missing.rbxm")).is_err());
+}
@@ -1,3 +1,8 @@
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+mod compressed_file;
+#[cfg(feature = "mmap")]
+mod file;
 mod models;
 mod serializer;
+mod stream;
 mod util;
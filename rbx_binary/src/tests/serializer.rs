@@ -1,9 +1,27 @@
+use std::{
+    collections::HashSet,
+    convert::TryFrom,
+    sync::{Arc, Mutex},
+};
+
 use rbx_dom_weak::{
-    types::{Ref, UDim},
+    types::{
+        Attributes, Axes, BinaryString, BrickColor, CFrame, Color3, ColorSequence,
+        ColorSequenceKeypoint, Content, CustomPhysicalProperties, Faces, Font, FontStyle,
+        FontWeight, Matrix3, NumberRange, NumberSequence, NumberSequenceKeypoint,
+        PhysicalProperties, Ray, Rect, Ref, SecurityCapabilities, SharedString, Tags, UDim,
+        UniqueId, UnknownValue, Variant, VariantType, Vector2, Vector3, Vector3int16,
+    },
     InstanceBuilder, WeakDom,
 };
 
-use crate::{encode, text_deserializer::DecodedModel};
+use crate::{
+    core::RbxWriteExt,
+    deserializer::decode,
+    encode, from_reader_as,
+    text_deserializer::{DecodedModel, ModelStats},
+    Compression, Deserializer, ModelKind, Serializer, SerializerCache,
+};
 
 /// A basic test to make sure we can serialize the simplest instance: a Folder.
 #[test]
@@ -37,6 +55,34 @@ fn partially_present() {
     insta::assert_yaml_snapshot!(decoded);
 }
 
+/// Ensures that removing a property from an instance makes it fall back to the
+/// default value, just like it was never set.
+#[test]
+fn removed_property() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+        InstanceBuilder::new("StringValue").with_property("Value", "Goodbye"),
+    ]));
+
+    let removed_ref = tree.root().children()[1];
+    let removed = tree.get_by_ref_mut(removed_ref).unwrap();
+    assert!(removed.remove_property("Value").is_some());
+    assert!(!removed.has_property("Value"));
+
+    let buffer = encode_full(&tree);
+    let decoded = decode(buffer.as_slice()).unwrap();
+    let decoded_ref = decoded.root().children()[1];
+
+    assert_eq!(
+        decoded
+            .get_by_ref(decoded_ref)
+            .unwrap()
+            .properties
+            .get("Value"),
+        Some(&Variant::String(String::new()))
+    );
+}
+
 /// Ensures that unknown properties get serialized on instances.
 #[test]
 fn unknown_property() {
@@ -64,6 +110,11 @@ fn unimplemented_type_known_property() {
     let result = encode(&tree, &[tree.root_ref()], &mut buffer);
 
     assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "failed to serialize property 'Padding' (UDim) on instance of class 'UIListLayout': \
+         this property type is not supported"
+    );
 }
 
 /// Ensures that serializing a tree with an unimplemented property type AND an
@@ -83,6 +134,11 @@ fn unimplemented_type_unknown_property() {
     let result = encode(&tree, &[tree.root_ref()], &mut buffer);
 
     assert!(result.is_err());
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "failed to serialize property 'WILL_NEVER_EXIST' (UDim) on instance of class 'Folder': \
+         this property type is not supported"
+    );
 }
 
 /// Ensures that the serializer returns an error instead of panicking if we give
@@ -96,3 +152,1789 @@ fn unknown_id() {
 
     assert!(result.is_err());
 }
+
+/// Ensures that Font values survive a round trip through the serializer and
+/// deserializer, including their optional CachedFaceId.
+#[test]
+fn font_round_trip() {
+    let bold = Font {
+        family: "rbxasset://fonts/families/Arial.json".to_owned(),
+        weight: FontWeight::Bold,
+        style: FontStyle::Italic,
+        cached_face_id: Some("rbxasset://fonts/arialbd.ttf".to_owned()),
+    };
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("TextLabel").with_property("FontFace", bold.clone()),
+        InstanceBuilder::new("TextLabel").with_property("FontFace", Font::default()),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let first = decoded.get_by_ref(children[0]).unwrap();
+    assert_eq!(first.properties.get("FontFace"), Some(&Variant::Font(bold)));
+
+    let second = decoded.get_by_ref(children[1]).unwrap();
+    assert_eq!(
+        second.properties.get("FontFace"),
+        Some(&Variant::Font(Font::default()))
+    );
+}
+
+/// Ensures that SharedString values are written once into the SSTR chunk and
+/// that instances that shared a value still share it after decoding.
+#[test]
+fn shared_string_round_trip() {
+    let data: Vec<u8> = (0..=255).collect();
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Folder")
+            .with_property("MeshData", SharedString::new(data.clone())),
+        InstanceBuilder::new("Folder")
+            .with_property("MeshData", SharedString::new(data.clone())),
+        InstanceBuilder::new("Folder")
+            .with_property("MeshData", SharedString::new(b"different".to_vec())),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    // Drop the original values so that the decoded values can't share their
+    // allocation with them.
+    drop(tree);
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let get_data = |referent: Ref| match decoded
+        .get_by_ref(referent)
+        .unwrap()
+        .properties
+        .get("MeshData")
+    {
+        Some(Variant::SharedString(value)) => value.clone(),
+        other => panic!("expected SharedString, got {:?}", other),
+    };
+
+    let first = get_data(children[0]);
+    let second = get_data(children[1]);
+    let third = get_data(children[2]);
+
+    assert_eq!(first.data(), data.as_slice());
+    assert_eq!(third.data(), b"different");
+    assert!(std::ptr::eq(first.data(), second.data()));
+    assert!(!std::ptr::eq(first.data(), third.data()));
+}
+
+/// Ensures that attributes are written into the AttributesSerialize property
+/// and decoded back into structured values.
+#[test]
+fn attributes_round_trip() {
+    let mut attributes = Attributes::new();
+    attributes.insert("Greeting", "Hello");
+    attributes.insert("Health", 100.0f64);
+    attributes.insert("Enabled", true);
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Folder").with_property("AttributesSerialize", attributes.clone()),
+        InstanceBuilder::new("Folder"),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let first = decoded.get_by_ref(children[0]).unwrap();
+    assert_eq!(
+        first.properties.get("AttributesSerialize"),
+        Some(&Variant::Attributes(attributes))
+    );
+
+    let second = decoded.get_by_ref(children[1]).unwrap();
+    assert_eq!(
+        second.properties.get("AttributesSerialize"),
+        Some(&Variant::Attributes(Attributes::new()))
+    );
+}
+
+/// Ensures that an empty AttributesSerialize blob, which Roblox writes for
+/// instances with no attributes, decodes as an empty set of attributes.
+#[test]
+fn attributes_empty_blob() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_property("AttributesSerialize", BinaryString::new()),
+    );
+
+    let mut buffer = Vec::new();
+    encode(&tree, &[tree.root_ref()], &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let folder = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+
+    assert_eq!(
+        folder.properties.get("AttributesSerialize"),
+        Some(&Variant::Attributes(Attributes::new()))
+    );
+}
+
+/// Ensures that tags are written as a null-delimited buffer and decoded back
+/// into a list, including tags that were set as a raw BinaryString. Buffers
+/// that can't be decoded without changing them are kept as a BinaryString.
+#[test]
+fn tags_round_trip() {
+    let tags = Tags::try_from(vec!["Enemy".to_owned(), "Boss".to_owned()]).unwrap();
+    let not_utf8 = BinaryString::from(&b"Bad\0\xFF"[..]);
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+            InstanceBuilder::new("Folder").with_property("Tags", tags.clone()),
+            InstanceBuilder::new("Folder").with_property("Tags", Tags::new()),
+            InstanceBuilder::new("Folder")
+                .with_property("Tags", BinaryString::from(&b"Old\0Style"[..])),
+            InstanceBuilder::new("Folder").with_property("Tags", not_utf8.clone()),
+            InstanceBuilder::new("Folder"),
+        ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    assert!(buffer
+        .windows(b"Enemy\0Boss".len())
+        .any(|window| window == b"Enemy\0Boss"));
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+    let tags_of = |index: usize| {
+        decoded
+            .get_by_ref(children[index])
+            .unwrap()
+            .properties
+            .get("Tags")
+            .cloned()
+    };
+
+    assert_eq!(tags_of(0), Some(Variant::Tags(tags)));
+    assert_eq!(tags_of(1), Some(Variant::Tags(Tags::new())));
+    assert_eq!(
+        tags_of(2),
+        Some(Variant::Tags(
+            Tags::try_from(vec!["Old".to_owned(), "Style".to_owned()]).unwrap()
+        ))
+    );
+    assert_eq!(tags_of(3), Some(Variant::BinaryString(not_utf8)));
+    assert_eq!(tags_of(4), Some(Variant::Tags(Tags::new())));
+}
+
+/// Attributes that hold types Roblox doesn't support should fail to serialize.
+#[test]
+fn attributes_unsupported_type() {
+    let mut attributes = Attributes::new();
+    attributes.insert("Count", 5i32);
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_property("AttributesSerialize", attributes),
+    );
+
+    let mut buffer = Vec::new();
+    let result = encode(&tree, &[tree.root_ref()], &mut buffer);
+    assert!(result.is_err());
+}
+
+/// Ensures that CFrame and OptionalCFrame values survive a round trip, and that
+/// missing OptionalCFrame values decode as `None`.
+#[test]
+fn cframe_round_trip() {
+    let cframe = CFrame::new(
+        Vector3::new(1.0, -2.5, 1000.0),
+        Matrix3 {
+            x: Vector3::new(0.0, -1.0, 0.0),
+            y: Vector3::new(0.6, 0.0, 0.8),
+            z: Vector3::new(-0.8, 0.0, 0.6),
+        },
+    );
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Folder")
+            .with_property("Pivot", Some(cframe))
+            .with_property("Origin", cframe),
+        InstanceBuilder::new("Folder")
+            .with_property("Pivot", Variant::OptionalCFrame(None))
+            .with_property("Origin", cframe),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let first = decoded.get_by_ref(children[0]).unwrap();
+    assert_eq!(
+        first.properties.get("Pivot"),
+        Some(&Variant::OptionalCFrame(Some(cframe)))
+    );
+    assert_eq!(
+        first.properties.get("Origin"),
+        Some(&Variant::CFrame(cframe))
+    );
+
+    let second = decoded.get_by_ref(children[1]).unwrap();
+    assert_eq!(
+        second.properties.get("Pivot"),
+        Some(&Variant::OptionalCFrame(None))
+    );
+}
+
+/// Ensures that axis-aligned CFrames are written using a one byte rotation ID
+/// instead of a full rotation matrix, and that they survive a round trip.
+#[test]
+fn cframe_special_rotations() {
+    let encode_origin = |rotation: Matrix3| -> Vec<u8> {
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Folder")
+                    .with_property("Origin", CFrame::new(Vector3::new(1.0, 2.0, 3.0), rotation)),
+            ),
+        );
+
+        let mut buffer = Vec::new();
+        Serializer::new()
+            .compression(Compression::None)
+            .serialize(&mut buffer, &tree, tree.root().children())
+            .expect("failed to encode model");
+
+        let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+        let child = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+        assert_eq!(
+            child.properties.get("Origin"),
+            Some(&Variant::CFrame(CFrame::new(
+                Vector3::new(1.0, 2.0, 3.0),
+                rotation
+            )))
+        );
+
+        buffer
+    };
+
+    let special = [
+        Matrix3::identity(),
+        // 90 degrees around the Y axis
+        Matrix3 {
+            x: Vector3::new(0.0, 0.0, 1.0),
+            y: Vector3::new(0.0, 1.0, 0.0),
+            z: Vector3::new(-1.0, 0.0, 0.0),
+        },
+        // 180 degrees around the X axis
+        Matrix3 {
+            x: Vector3::new(1.0, 0.0, 0.0),
+            y: Vector3::new(0.0, -1.0, 0.0),
+            z: Vector3::new(0.0, 0.0, -1.0),
+        },
+        // 90 degrees around the Z axis, then 90 degrees around the X axis
+        Matrix3 {
+            x: Vector3::new(0.0, -1.0, 0.0),
+            y: Vector3::new(0.0, 0.0, -1.0),
+            z: Vector3::new(1.0, 0.0, 0.0),
+        },
+    ];
+
+    let full = encode_origin(Matrix3 {
+        x: Vector3::new(0.0, -1.0, 0.0),
+        y: Vector3::new(0.6, 0.0, 0.8),
+        z: Vector3::new(-0.8, 0.0, 0.6),
+    });
+
+    for &rotation in &special {
+        let compact = encode_origin(rotation);
+
+        // The nine floats of the rotation matrix are left out.
+        assert_eq!(compact.len() + 9 * 4, full.len());
+    }
+}
+
+/// Ensures that both default and custom PhysicalProperties values survive a
+/// round trip, since only custom values have a payload.
+#[test]
+fn physical_properties_round_trip() {
+    let custom = PhysicalProperties::Custom(CustomPhysicalProperties {
+        density: 0.7,
+        friction: 0.3,
+        elasticity: 0.5,
+        friction_weight: 1.0,
+        elasticity_weight: 2.0,
+    });
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Part").with_property("CustomPhysicalProperties", custom),
+        InstanceBuilder::new("Part")
+            .with_property("CustomPhysicalProperties", PhysicalProperties::Default),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let first = decoded.get_by_ref(children[0]).unwrap();
+    assert_eq!(
+        first.properties.get("CustomPhysicalProperties"),
+        Some(&Variant::PhysicalProperties(custom))
+    );
+
+    let second = decoded.get_by_ref(children[1]).unwrap();
+    assert_eq!(
+        second.properties.get("CustomPhysicalProperties"),
+        Some(&Variant::PhysicalProperties(PhysicalProperties::Default))
+    );
+}
+
+/// Ensures that children keep their order through a round trip, since that
+/// order is visible in Studio.
+#[test]
+fn child_order_round_trip() {
+    let names = ["Zebra", "Apple", "Mango", "Banana", "Cherry"];
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("Folder").with_children(
+                names
+                    .iter()
+                    .map(|name| InstanceBuilder::new("Folder").with_name(*name)),
+            ),
+        ),
+    );
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let parent = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+
+    let decoded_names: Vec<&str> = parent
+        .children()
+        .iter()
+        .map(|referent| decoded.get_by_ref(*referent).unwrap().name.as_str())
+        .collect();
+
+    assert_eq!(decoded_names, names);
+}
+
+/// Ensures that BrickColor values survive a round trip.
+#[test]
+fn brick_color_round_trip() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("SpawnLocation").with_property("TeamColor", BrickColor::BrightRed),
+        InstanceBuilder::new("SpawnLocation").with_property("TeamColor", BrickColor::Black),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let first = decoded.get_by_ref(children[0]).unwrap();
+    assert_eq!(
+        first.properties.get("TeamColor"),
+        Some(&Variant::BrickColor(BrickColor::BrightRed))
+    );
+
+    let second = decoded.get_by_ref(children[1]).unwrap();
+    assert_eq!(
+        second.properties.get("TeamColor"),
+        Some(&Variant::BrickColor(BrickColor::Black))
+    );
+}
+
+/// Ensures that models written with and without compression decode to the
+/// same instances.
+#[test]
+fn uncompressed_round_trip() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children((0..20).map(|i| {
+            InstanceBuilder::new("StringValue")
+                .with_name(format!("Value {}", i))
+                .with_property("Value", "A value that appears many times")
+        })),
+    );
+
+    let mut compressed = Vec::new();
+    Serializer::new()
+        .compression(Compression::Lz4)
+        .serialize(&mut compressed, &tree, tree.root().children())
+        .expect("failed to encode compressed model");
+
+    let mut uncompressed = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut uncompressed, &tree, tree.root().children())
+        .expect("failed to encode uncompressed model");
+
+    assert!(uncompressed.len() > compressed.len());
+
+    let compressed = decode(compressed.as_slice()).expect("failed to decode compressed model");
+    let uncompressed =
+        decode(uncompressed.as_slice()).expect("failed to decode uncompressed model");
+
+    let describe = |dom: &WeakDom| -> Vec<(String, Option<Variant>)> {
+        dom.root()
+            .children()
+            .iter()
+            .map(|referent| {
+                let instance = dom.get_by_ref(*referent).unwrap();
+                (
+                    instance.name.clone(),
+                    instance.properties.get("Value").cloned(),
+                )
+            })
+            .collect()
+    };
+
+    assert_eq!(describe(&compressed).len(), 20);
+    assert_eq!(describe(&compressed), describe(&uncompressed));
+}
+
+/// Ensures that corrupt length prefixes make decoding fail with an error instead
+/// of panicking or trying to allocate huge buffers.
+#[test]
+fn corrupt_lengths() {
+    let sequence = NumberSequence {
+        keypoints: vec![
+            NumberSequenceKeypoint::new(0.0, 0.0, 0.0),
+            NumberSequenceKeypoint::new(1.0, 1.0, 0.0),
+        ],
+    };
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Folder").with_property("Sequence", sequence)),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .expect("failed to encode model");
+
+    let find_chunk = |name: &[u8]| -> usize {
+        let position = buffer
+            .windows(name.len())
+            .position(|window| window == name)
+            .expect("chunk was missing");
+
+        // Skip over the chunk's name, lengths, and reserved bytes.
+        position + 16
+    };
+
+    let corrupt = |offset: usize| -> Vec<u8> {
+        let mut corrupted = buffer.clone();
+        corrupted[offset..offset + 4].copy_from_slice(&std::u32::MAX.to_le_bytes());
+        corrupted
+    };
+
+    let inst = find_chunk(b"INST");
+    let prnt = find_chunk(b"PRNT");
+
+    // The INST chunk starts with the type ID, followed by the type name and
+    // the object format, which are followed by the number of instances.
+    let bad_type_name = corrupt(inst + 4);
+    let bad_instance_count = corrupt(inst + 8 + "Folder".len() + 1);
+
+    // The PRNT chunk starts with a version byte followed by its length.
+    let bad_parent_count = corrupt(prnt + 1);
+
+    // The property's name is followed by its type and then the keypoint count
+    // of the only value.
+    let sequence_name = buffer
+        .windows(b"Sequence".len())
+        .position(|window| window == b"Sequence")
+        .expect("property was missing");
+    let bad_keypoint_count = corrupt(sequence_name + b"Sequence".len() + 1);
+
+    // The file header starts with the magic number, signature, and version,
+    // which are followed by the number of types and instances.
+    let bad_header_types = corrupt(16);
+    let bad_header_instances = corrupt(20);
+
+    for corrupted in &[
+        bad_type_name,
+        bad_instance_count,
+        bad_parent_count,
+        bad_keypoint_count,
+        bad_header_types,
+        bad_header_instances,
+    ] {
+        assert!(decode(corrupted.as_slice()).is_err());
+    }
+
+    decode(buffer.as_slice()).expect("failed to decode uncorrupted model");
+}
+
+/// Ensures that each entry of the PRNT chunk decides the parent of an instance,
+/// and that entries that don't describe a valid tree are rejected rather than
+/// producing a tree with missing or misplaced instances.
+#[test]
+fn parent_entries() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("A")
+                    .with_child(InstanceBuilder::new("Folder").with_name("C")),
+            )
+            .with_child(InstanceBuilder::new("Folder").with_name("B")),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .expect("failed to encode model");
+
+    // Instances are assigned referents breadth-first, so A is 0, B is 1, and C
+    // is 2. A parent of -1 means the top of the file.
+    let with_parents = |entries: &[(i32, i32)]| -> Vec<u8> {
+        let start = buffer
+            .windows(4)
+            .position(|window| window == b"PRNT")
+            .expect("PRNT chunk was missing");
+        let mut old_len = [0; 4];
+        old_len.copy_from_slice(&buffer[start + 8..start + 12]);
+        let end = start + 16 + u32::from_le_bytes(old_len) as usize;
+
+        let mut chunk = vec![0];
+        chunk.extend_from_slice(&(entries.len() as u32).to_le_bytes());
+        chunk
+            .write_referents(entries.iter().map(|(subject, _)| *subject))
+            .unwrap();
+        chunk
+            .write_referents(entries.iter().map(|(_, parent)| *parent))
+            .unwrap();
+
+        let mut output = buffer[..start].to_vec();
+        output.extend_from_slice(b"PRNT");
+        output.extend_from_slice(&0u32.to_le_bytes());
+        output.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        output.extend_from_slice(&0u32.to_le_bytes());
+        output.extend_from_slice(&chunk);
+        output.extend_from_slice(&buffer[end..]);
+        output
+    };
+
+    let describe = |decoded: &WeakDom| -> Vec<(String, String)> {
+        let mut parents: Vec<_> = decoded
+            .descendants_of_root()
+            .map(|referent| {
+                let instance = decoded.get_by_ref(referent).unwrap();
+                let parent = decoded.get_by_ref(instance.parent()).unwrap();
+                (instance.name.clone(), parent.name.clone())
+            })
+            .collect();
+        parents.sort();
+        parents
+    };
+
+    let original = decode(with_parents(&[(0, -1), (1, -1), (2, 0)]).as_slice()).unwrap();
+    assert_eq!(describe(&original), describe(&tree));
+
+    let moved = decode(with_parents(&[(2, 1), (1, -1), (0, -1)]).as_slice()).unwrap();
+    assert_eq!(
+        describe(&moved),
+        vec![
+            ("A".to_owned(), "DataModel".to_owned()),
+            ("B".to_owned(), "DataModel".to_owned()),
+            ("C".to_owned(), "B".to_owned()),
+        ]
+    );
+
+    let invalid = [
+        // A parent that doesn't exist.
+        &[(0, -1), (1, -1), (2, 7)][..],
+        // An instance that doesn't exist.
+        &[(0, -1), (1, -1), (2, 0), (7, 0)],
+        // An instance with two parents.
+        &[(0, -1), (1, -1), (2, 0), (2, 1)],
+        // An instance that's never given a parent.
+        &[(0, -1), (1, -1)],
+        // Instances that are each other's parent.
+        &[(0, -1), (1, 2), (2, 1)],
+    ];
+
+    for entries in &invalid {
+        assert!(
+            decode(with_parents(entries).as_slice()).is_err(),
+            "PRNT entries {:?} should not decode",
+            entries
+        );
+    }
+}
+
+/// Ensures that Content values stay distinct from String values through a
+/// round trip, whether they're empty or not, and whether or not the property
+/// is known.
+#[test]
+fn content_round_trip() {
+    let url = Content::from("rbxassetid://12345");
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("ImageLabel")
+            .with_property("Image", url.clone())
+            .with_property("WILL_NEVER_EXIST", url.clone()),
+        InstanceBuilder::new("ImageLabel").with_property("Image", Content::new()),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let first = decoded.get_by_ref(children[0]).unwrap();
+    assert_eq!(first.properties.get("Image"), Some(&Variant::Content(url)));
+
+    let second = decoded.get_by_ref(children[1]).unwrap();
+    assert_eq!(
+        second.properties.get("Image"),
+        Some(&Variant::Content(Content::new()))
+    );
+}
+
+/// Ensures that model statistics count instances per class and values per
+/// property type.
+#[test]
+fn model_stats() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+        InstanceBuilder::new("StringValue").with_property("Value", "world"),
+        InstanceBuilder::new("BoolValue").with_property("Value", true),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, &[tree.root_ref()], &mut buffer).expect("failed to encode model");
+
+    let stats = ModelStats::from_reader(buffer.as_slice());
+
+    assert_eq!(stats.num_instances, 4);
+    assert_eq!(stats.instances_by_class["Folder"], 1);
+    assert_eq!(stats.instances_by_class["StringValue"], 2);
+    assert_eq!(stats.instances_by_class["BoolValue"], 1);
+
+    // Every instance has a Name, plus one Value property per value instance.
+    let strings = &stats.properties_by_type["String"];
+    assert_eq!(strings.num_values, 6);
+    let string_bytes: usize = ["Folder", "StringValue", "StringValue", "BoolValue"]
+        .iter()
+        .chain(["Hello", "world"].iter())
+        .map(|value| 4 + value.len())
+        .sum();
+    assert_eq!(strings.num_bytes, string_bytes);
+
+    let bools = &stats.properties_by_type["Bool"];
+    assert_eq!(bools.num_values, 1);
+    assert_eq!(bools.num_bytes, 1);
+}
+
+/// Ensures that Vector3int16 values round-trip, including negative components
+/// which would be mangled if they were encoded as unsigned.
+#[test]
+fn vector3int16_round_trip() {
+    let values = [
+        Vector3int16::new(1, 2, 3),
+        Vector3int16::new(-1, -300, std::i16::MIN),
+        Vector3int16::new(std::i16::MAX, 0, -32000),
+    ];
+
+    let tree =
+        WeakDom::new(
+            InstanceBuilder::new("Folder").with_children(values.iter().map(|&value| {
+                InstanceBuilder::new("Folder").with_property("WILL_NEVER_EXIST", value)
+            })),
+        );
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+    assert_eq!(children.len(), values.len());
+
+    for (referent, value) in children.iter().zip(values.iter()) {
+        let instance = decoded.get_by_ref(*referent).unwrap();
+        assert_eq!(
+            instance.properties.get("WILL_NEVER_EXIST"),
+            Some(&Variant::Vector3int16(*value))
+        );
+    }
+}
+
+/// Ensures that NumberSequence and ColorSequence values with several keypoints
+/// round-trip, including the envelope of NumberSequence keypoints.
+#[test]
+fn sequence_round_trip() {
+    let size = NumberSequence {
+        keypoints: vec![
+            NumberSequenceKeypoint::new(0.0, 1.0, 0.5),
+            NumberSequenceKeypoint::new(0.25, -2.0, 0.0),
+            NumberSequenceKeypoint::new(1.0, 10.0, 3.0),
+        ],
+    };
+
+    let color = ColorSequence {
+        keypoints: vec![
+            ColorSequenceKeypoint::new(0.0, Color3::new(1.0, 0.0, 0.0)),
+            ColorSequenceKeypoint::new(0.5, Color3::new(0.0, 1.0, 0.0)),
+            ColorSequenceKeypoint::new(1.0, Color3::new(0.0, 0.0, 1.0)),
+        ],
+    };
+
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("ParticleEmitter")
+            .with_property("Size", size.clone())
+            .with_property("Color", color.clone()),
+        InstanceBuilder::new("ParticleEmitter"),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+
+    let custom = decoded.get_by_ref(children[0]).unwrap();
+    assert_eq!(
+        custom.properties.get("Size"),
+        Some(&Variant::NumberSequence(size))
+    );
+    assert_eq!(
+        custom.properties.get("Color"),
+        Some(&Variant::ColorSequence(color))
+    );
+
+    let default = decoded.get_by_ref(children[1]).unwrap();
+    assert_eq!(
+        default.properties.get("Size").map(Variant::ty),
+        Some(VariantType::NumberSequence)
+    );
+    assert_eq!(
+        default.properties.get("Color").map(Variant::ty),
+        Some(VariantType::ColorSequence)
+    );
+}
+
+/// Ensures that NumberRange values round-trip, including ranges with equal or
+/// negative bounds.
+#[test]
+fn number_range_round_trip() {
+    let values = [
+        NumberRange::new(1.0, 5.0),
+        NumberRange::new(2.5, 2.5),
+        NumberRange::new(-10.0, 3.0),
+    ];
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children(values.iter().map(|&value| {
+            InstanceBuilder::new("ParticleEmitter").with_property("Lifetime", value)
+        })),
+    );
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+    assert_eq!(children.len(), values.len());
+
+    for (referent, value) in children.iter().zip(values.iter()) {
+        let instance = decoded.get_by_ref(*referent).unwrap();
+        assert_eq!(
+            instance.properties.get("Lifetime"),
+            Some(&Variant::NumberRange(*value))
+        );
+    }
+}
+
+/// Ensures that Ray values round-trip, including rays with zero or negative
+/// components.
+#[test]
+fn ray_round_trip() {
+    let values = [
+        Ray::new(Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0)),
+        Ray::new(Vector3::new(1.0, 2.0, 3.0), Vector3::new(0.0, -1.0, 0.0)),
+        Ray::new(
+            Vector3::new(-4.5, 100.0, 0.25),
+            Vector3::new(10.0, 20.0, -30.0),
+        ),
+    ];
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children(
+            values
+                .iter()
+                .map(|&value| InstanceBuilder::new("RayValue").with_property("Value", value)),
+        ),
+    );
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+    assert_eq!(children.len(), values.len());
+
+    for (referent, value) in children.iter().zip(values.iter()) {
+        let instance = decoded.get_by_ref(*referent).unwrap();
+        assert_eq!(
+            instance.properties.get("Value"),
+            Some(&Variant::Ray(*value))
+        );
+    }
+}
+
+/// Ensures that Rect values round-trip, including empty rects and rects with
+/// negative coordinates.
+#[test]
+fn rect_round_trip() {
+    let values = [
+        Rect::new(Vector2::new(0.0, 0.0), Vector2::new(0.0, 0.0)),
+        Rect::new(Vector2::new(-12.5, -30.0), Vector2::new(-1.0, 9.5)),
+        Rect::new(Vector2::new(4.0, 4.0), Vector2::new(60.0, 28.0)),
+    ];
+
+    let tree =
+        WeakDom::new(InstanceBuilder::new("Folder").with_children(
+            values.iter().map(|&value| {
+                InstanceBuilder::new("ImageLabel").with_property("SliceCenter", value)
+            }),
+        ));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+    assert_eq!(children.len(), values.len());
+
+    for (referent, value) in children.iter().zip(values.iter()) {
+        let instance = decoded.get_by_ref(*referent).unwrap();
+        assert_eq!(
+            instance.properties.get("SliceCenter"),
+            Some(&Variant::Rect(*value))
+        );
+    }
+}
+
+/// Ensures that Axes and Faces values round-trip as single-byte bitmasks.
+#[test]
+fn axes_and_faces_round_trip() {
+    let values = [
+        (Axes::empty(), Faces::empty()),
+        (Axes::Z, Faces::FRONT),
+        (Axes::all(), Faces::all()),
+    ];
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children(values.iter().map(|&(axes, faces)| {
+            InstanceBuilder::new("Folder")
+                .with_property("UnknownAxes", axes)
+                .with_property("UnknownFaces", faces)
+        })),
+    );
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+    assert_eq!(children.len(), values.len());
+
+    for (referent, &(axes, faces)) in children.iter().zip(values.iter()) {
+        let instance = decoded.get_by_ref(*referent).unwrap();
+        assert_eq!(
+            instance.properties.get("UnknownAxes"),
+            Some(&Variant::Axes(axes))
+        );
+        assert_eq!(
+            instance.properties.get("UnknownFaces"),
+            Some(&Variant::Faces(faces))
+        );
+    }
+}
+
+/// Ensures that the format version can be pinned to the supported version, and
+/// that asking for an unsupported version is an error.
+#[test]
+fn format_version() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder"));
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .format_version(0)
+        .serialize(&mut buffer, &tree, &[tree.root_ref()])
+        .expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    assert_eq!(decoded.root().children().len(), 1);
+
+    let mut buffer = Vec::new();
+    let result =
+        Serializer::new()
+            .format_version(1)
+            .serialize(&mut buffer, &tree, &[tree.root_ref()]);
+
+    assert!(result.is_err());
+    assert!(buffer.is_empty());
+}
+
+/// Ensures that Ref properties point to the right instances after a round
+/// trip, and that refs to instances outside the file become null refs.
+#[test]
+fn ref_round_trip() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    let root_ref = tree.root_ref();
+
+    let outside = tree.insert(root_ref, InstanceBuilder::new("Folder"));
+    let model = tree.insert(root_ref, InstanceBuilder::new("Model"));
+    let target = tree.insert(model, InstanceBuilder::new("Part").with_name("Target"));
+
+    for &(name, value) in &[
+        ("Valid", target),
+        ("Null", Ref::none()),
+        ("Outside", outside),
+    ] {
+        tree.insert(
+            model,
+            InstanceBuilder::new("ObjectValue")
+                .with_name(name)
+                .with_property("Value", value),
+        );
+    }
+
+    let mut buffer = Vec::new();
+    encode(&tree, &[model], &mut buffer).expect("failed to encode model");
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let decoded_model = decoded.root().children()[0];
+    let decoded_target = decoded.find_first_child(decoded_model, "Target").unwrap();
+
+    let value_of = |name: &str| {
+        let child = decoded.find_first_child(decoded_model, name).unwrap();
+        decoded
+            .get_by_ref(child)
+            .unwrap()
+            .properties
+            .get("Value")
+            .cloned()
+    };
+
+    assert_eq!(value_of("Valid"), Some(Variant::Ref(decoded_target)));
+    assert_eq!(value_of("Null"), Some(Variant::Ref(Ref::none())));
+    assert_eq!(value_of("Outside"), Some(Variant::Ref(Ref::none())));
+}
+
+/// Ensures that validating refs rejects refs to instances outside of the
+/// serialized set, but accepts null refs and refs within it.
+#[test]
+fn validate_refs() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    let root_ref = tree.root_ref();
+
+    let outside = tree.insert(root_ref, InstanceBuilder::new("Folder"));
+    let model = tree.insert(root_ref, InstanceBuilder::new("Model"));
+    let target = tree.insert(model, InstanceBuilder::new("Part"));
+
+    tree.insert(
+        model,
+        InstanceBuilder::new("ObjectValue").with_property("Value", target),
+    );
+    tree.insert(
+        model,
+        InstanceBuilder::new("ObjectValue").with_property("Value", Ref::none()),
+    );
+
+    let serializer = Serializer::new().validate_refs(true);
+
+    let mut buffer = Vec::new();
+    serializer
+        .serialize(&mut buffer, &tree, &[model])
+        .expect("valid refs should serialize");
+
+    tree.insert(
+        model,
+        InstanceBuilder::new("ObjectValue")
+            .with_name("Dangling")
+            .with_property("Value", outside),
+    );
+
+    let mut buffer = Vec::new();
+    let error = serializer
+        .serialize(&mut buffer, &tree, &[model])
+        .expect_err("ref to an instance outside the model should be an error");
+
+    assert!(error.to_string().contains("ObjectValue.Value"));
+    assert!(error.to_string().contains("Model.Dangling"));
+    assert!(buffer.is_empty());
+
+    // Without validation, the same ref is written as a null ref.
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .serialize(&mut buffer, &tree, &[model])
+        .expect("failed to encode model");
+}
+
+/// Ensures that UniqueId values round trip, and that each component is written
+/// big-endian with the random number rotated like Roblox does.
+#[test]
+fn unique_id_round_trip() {
+    let value = UniqueId::new(0x0048_15fc, 0x02e9_c68d, 0x44b1_88da_ce63_2b47);
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Folder").with_property("UniqueId", value)),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .expect("failed to encode model");
+
+    let expected_bytes = [
+        0x89, 0x63, 0x11, 0xb5, 0x9c, 0xc6, 0x56, 0x8e, // random, rotated left
+        0x02, 0xe9, 0xc6, 0x8d, // time
+        0x00, 0x48, 0x15, 0xfc, // index
+    ];
+    assert!(buffer
+        .windows(expected_bytes.len())
+        .any(|window| window == expected_bytes));
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let child = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+
+    assert_eq!(
+        child.properties.get("UniqueId"),
+        Some(&Variant::UniqueId(value))
+    );
+}
+
+/// Ensures that SecurityCapabilities values round trip, and that they're
+/// written like Int64 values: transformed, then interleaved big-endian.
+#[test]
+fn security_capabilities_round_trip() {
+    let low = SecurityCapabilities::from_bits(0b11);
+    let high = SecurityCapabilities::from_bits(0x8000_0000_0000_0001);
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Folder").with_property("Capabilities", low))
+            .with_child(InstanceBuilder::new("Folder").with_property("Capabilities", high)),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .expect("failed to encode model");
+
+    // 3 transforms to 6, and the high bit of the second value makes its
+    // transformed form all ones except for the bit that 1 shifts into.
+    let expected_bytes = [
+        0x21, // type ID
+        0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x00, 0xff, //
+        0x00, 0xff, 0x00, 0xff, 0x00, 0xff, 0x06, 0xfd,
+    ];
+    assert!(buffer
+        .windows(expected_bytes.len())
+        .any(|window| window == expected_bytes));
+
+    let decoded = decode(buffer.as_slice()).expect("failed to decode model");
+    let children = decoded.root().children();
+    let capabilities: Vec<_> = children
+        .iter()
+        .map(|referent| {
+            decoded
+                .get_by_ref(*referent)
+                .unwrap()
+                .properties
+                .get("Capabilities")
+        })
+        .collect();
+
+    assert_eq!(
+        capabilities,
+        vec![
+            Some(&Variant::SecurityCapabilities(low)),
+            Some(&Variant::SecurityCapabilities(high)),
+        ]
+    );
+}
+
+fn encode_full(tree: &WeakDom) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    encode(tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+    buffer
+}
+
+fn encode_incremental(
+    tree: &WeakDom,
+    cache: &mut SerializerCache,
+    dirty: &HashSet<Ref>,
+) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .serialize_incremental(&mut buffer, tree, tree.root().children(), cache, dirty)
+        .expect("failed to encode model");
+    buffer
+}
+
+/// Ensures that incremental serialization produces the same output as a full
+/// serialization as instances are changed, added, and removed.
+#[test]
+fn incremental_matches_full() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    let root_ref = tree.root_ref();
+
+    let first = tree.insert(
+        root_ref,
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+    );
+    tree.insert(
+        root_ref,
+        InstanceBuilder::new("Folder")
+            .with_property("WILL_NEVER_EXIST", SharedString::new(b"shared".to_vec())),
+    );
+
+    let mut cache = SerializerCache::new();
+    let mut dirty = HashSet::new();
+
+    assert_eq!(
+        encode_incremental(&tree, &mut cache, &dirty),
+        encode_full(&tree)
+    );
+    assert_eq!(
+        encode_incremental(&tree, &mut cache, &dirty),
+        encode_full(&tree)
+    );
+
+    tree.get_by_ref_mut(first)
+        .unwrap()
+        .properties
+        .insert("Value".to_owned(), "world".into());
+    dirty.insert(first);
+    assert_eq!(
+        encode_incremental(&tree, &mut cache, &dirty),
+        encode_full(&tree)
+    );
+    dirty.clear();
+
+    tree.insert(
+        root_ref,
+        InstanceBuilder::new("StringValue").with_property("Value", "new"),
+    );
+    assert_eq!(
+        encode_incremental(&tree, &mut cache, &dirty),
+        encode_full(&tree)
+    );
+
+    // Removing an instance shifts the IDs of every instance after it.
+    tree.destroy(first);
+    assert_eq!(
+        encode_incremental(&tree, &mut cache, &dirty),
+        encode_full(&tree)
+    );
+}
+
+/// Ensures that incremental serialization actually reuses chunks for instances
+/// that aren't marked dirty, by changing one without reporting it.
+#[test]
+fn incremental_reuses_clean_chunks() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    let value = tree.insert(
+        tree.root_ref(),
+        InstanceBuilder::new("StringValue").with_property("Value", "Hello"),
+    );
+
+    let mut cache = SerializerCache::new();
+    let original = encode_incremental(&tree, &mut cache, &HashSet::new());
+
+    tree.get_by_ref_mut(value)
+        .unwrap()
+        .properties
+        .insert("Value".to_owned(), "world".into());
+
+    assert_eq!(
+        encode_incremental(&tree, &mut cache, &HashSet::new()),
+        original
+    );
+
+    let dirty = vec![value].into_iter().collect();
+    assert_eq!(
+        encode_incremental(&tree, &mut cache, &dirty),
+        encode_full(&tree)
+    );
+}
+
+/// Ensures that decompressing chunks on several threads produces the same DOM
+/// as decoding on one, and still reports errors.
+#[test]
+fn threaded_deserialize() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_children((0..50).map(|i| {
+            InstanceBuilder::new("Folder")
+                .with_name(format!("Folder {}", i))
+                .with_child(
+                    InstanceBuilder::new("StringValue").with_property("Value", i.to_string()),
+                )
+                .with_child(InstanceBuilder::new("BoolValue").with_property("Value", i % 2 == 0))
+                .with_child(InstanceBuilder::new("ObjectValue").with_property("Value", Ref::none()))
+        })),
+    );
+    let buffer = encode_full(&tree);
+
+    let single = Deserializer::new(buffer.as_slice())
+        .deserialize()
+        .expect("failed to decode model on one thread");
+
+    for &threads in &[2, 4, 16] {
+        let threaded = Deserializer::new(buffer.as_slice())
+            .threads(threads)
+            .deserialize()
+            .expect("failed to decode model on several threads");
+
+        assert_eq!(encode_full(&threaded), encode_full(&single));
+    }
+
+    let truncated = &buffer[..buffer.len() - 20];
+    assert!(Deserializer::new(truncated)
+        .threads(4)
+        .deserialize()
+        .is_err());
+}
+
+/// Ensures that properties with types rbx_binary can't decode are kept as
+/// opaque values when requested, and written back out byte for byte.
+#[test]
+fn preserve_unknown_types() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("First")
+                    .with_property("Data", BinaryString::from(b"abcd".to_vec())),
+            )
+            .with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("Second")
+                    .with_property("Data", BinaryString::from(b"efgh".to_vec())),
+            ),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .unwrap();
+
+    // Retype the Data property as 0x7F, which no version of the format uses
+    // yet, and as UDim, which rbx_binary doesn't decode yet.
+    let header = b"\x04\x00\x00\x00Data";
+    let type_offset = buffer
+        .windows(header.len())
+        .position(|window| window == header)
+        .expect("PROP chunk for Data was not found")
+        + header.len();
+
+    for &type_value in &[0x7F, 0x06] {
+        buffer[type_offset] = type_value;
+
+        let skipped = decode(buffer.as_slice()).unwrap();
+        let first = skipped.get_by_ref(skipped.root().children()[0]).unwrap();
+        assert_eq!(first.properties.get("Data"), None);
+
+        let preserved = Deserializer::new(buffer.as_slice())
+            .preserve_unknown(true)
+            .deserialize()
+            .unwrap();
+
+        let children = preserved.root().children();
+        let first = preserved.get_by_ref(children[0]).unwrap();
+        assert_eq!(
+            first.properties.get("Data"),
+            Some(&Variant::Unknown(UnknownValue::in_column(
+                type_value,
+                BinaryString::from(b"\x04\x00\x00\x00abcd".to_vec()),
+                0,
+                2
+            )))
+        );
+
+        let mut reencoded = Vec::new();
+        Serializer::new()
+            .compression(Compression::None)
+            .serialize(&mut reencoded, &preserved, children)
+            .unwrap();
+
+        assert_eq!(reencoded, buffer);
+    }
+}
+
+/// Ensures that a column of unknown values that couldn't be split between its
+/// instances is only written back out if its instances haven't changed.
+#[test]
+fn unknown_types_changed_instances() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("First")
+                    .with_property("Data", BinaryString::from(b"abc".to_vec())),
+            )
+            .with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("Second")
+                    .with_property("Data", BinaryString::from(b"efgh".to_vec())),
+            ),
+    );
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .unwrap();
+
+    let header = b"\x04\x00\x00\x00Data";
+    let type_offset = buffer
+        .windows(header.len())
+        .position(|window| window == header)
+        .expect("PROP chunk for Data was not found")
+        + header.len();
+    buffer[type_offset] = 0x7F;
+
+    let decode_preserved = || {
+        Deserializer::new(buffer.as_slice())
+            .preserve_unknown(true)
+            .deserialize()
+            .unwrap()
+    };
+    let serialize = |dom: &WeakDom, refs: &[Ref]| {
+        Serializer::new()
+            .compression(Compression::None)
+            .serialize(Vec::new(), dom, refs)
+    };
+
+    // The values are 7 and 8 bytes long, so the first instance holds both.
+    let dom = decode_preserved();
+    let children = dom.root().children().to_vec();
+    assert!(serialize(&dom, &children).is_ok());
+
+    let reordered = [children[1], children[0]];
+    assert!(serialize(&dom, &reordered).is_err());
+
+    let mut dom = decode_preserved();
+    let children = dom.root().children().to_vec();
+    dom.destroy(children[0]);
+    assert!(serialize(&dom, &children[1..]).is_err());
+}
+
+/// Ensures that the entries of a DOM's metadata are written to a `META` chunk
+/// and read back.
+#[test]
+fn metadata_round_trip() {
+    let mut tree =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+    tree.metadata_mut()
+        .insert("ExplicitAutoJoints".to_owned(), "true".to_owned());
+    tree.metadata_mut()
+        .insert("AnotherKey".to_owned(), "some value".to_owned());
+
+    let buffer = encode_full(&tree);
+    let decoded = decode(buffer.as_slice()).unwrap();
+    assert_eq!(decoded.metadata(), tree.metadata());
+
+    // Entries are written sorted by key, so the output is deterministic.
+    let model = DecodedModel::from_reader(buffer.as_slice());
+    insta::assert_yaml_snapshot!(model);
+}
+
+/// Ensures that progress is reported once per class of instance, ending with
+/// every instance written.
+#[test]
+fn progress_callback() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("Folder"),
+        InstanceBuilder::new("StringValue"),
+        InstanceBuilder::new("StringValue"),
+        InstanceBuilder::new("BoolValue"),
+    ]));
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let serializer = {
+        let reports = Arc::clone(&reports);
+        Serializer::new().on_progress(move |written, total| {
+            reports.lock().unwrap().push((written, total));
+        })
+    };
+
+    let mut buffer = Vec::new();
+    serializer
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .unwrap();
+
+    // Classes are written in alphabetical order: BoolValue, Folder, then
+    // StringValue.
+    assert_eq!(*reports.lock().unwrap(), vec![(1, 4), (2, 4), (4, 4)]);
+
+    // Reporting progress doesn't change the output.
+    assert_eq!(buffer, encode_full(&tree));
+}
+
+/// Ensures that a `map_property` callback can replace and remove property
+/// values as they're written, without changing the DOM.
+#[test]
+fn map_property() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue").with_property("Value", "secret"),
+        InstanceBuilder::new("StringValue").with_property("Value", "also secret"),
+        InstanceBuilder::new("BoolValue").with_property("Value", true),
+        InstanceBuilder::new("IntValue").with_property("Value", 5),
+    ]));
+
+    let serializer = Serializer::new().map_property(|class, name, value| match (class, name) {
+        ("StringValue", "Value") => Some(Variant::String(String::new())),
+        ("IntValue", "Value") => None,
+        _ => Some(value.clone()),
+    });
+
+    let mut buffer = Vec::new();
+    serializer
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .unwrap();
+
+    let decoded = decode(buffer.as_slice()).unwrap();
+    let values: Vec<_> = decoded
+        .root()
+        .children()
+        .iter()
+        .map(|referent| {
+            let instance = decoded.get_by_ref(*referent).unwrap();
+            instance.properties.get("Value").cloned()
+        })
+        .collect();
+
+    assert_eq!(
+        values,
+        vec![
+            Some(Variant::String(String::new())),
+            Some(Variant::String(String::new())),
+            Some(Variant::Bool(true)),
+            // IntValue.Value was left out of every IntValue, so it isn't
+            // written at all.
+            None,
+        ]
+    );
+
+    let original = tree.get_by_ref(tree.root().children()[0]).unwrap();
+    assert_eq!(
+        original.properties.get("Value"),
+        Some(&Variant::String("secret".to_owned()))
+    );
+}
+
+/// Ensures that properties Roblox doesn't persist are only left out when
+/// `skip_non_serialized` is enabled.
+#[test]
+fn skip_non_serialized() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("StringValue")
+                .with_property("Value", "Hello")
+                .with_property("RobloxLocked", true),
+        ),
+    );
+
+    let decode_value = |serializer: Serializer| {
+        let mut buffer = Vec::new();
+        serializer
+            .serialize(&mut buffer, &tree, tree.root().children())
+            .unwrap();
+
+        let decoded = decode(buffer.as_slice()).unwrap();
+        let instance = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+        (
+            instance.properties.get("Value").cloned(),
+            instance.properties.get("RobloxLocked").cloned(),
+        )
+    };
+
+    assert_eq!(
+        decode_value(Serializer::new()),
+        (
+            Some(Variant::String("Hello".to_owned())),
+            Some(Variant::Bool(true))
+        )
+    );
+    assert_eq!(
+        decode_value(Serializer::new().skip_non_serialized(true)),
+        (Some(Variant::String("Hello".to_owned())), None)
+    );
+}
+
+/// Ensures that incremental serialization doesn't reuse chunks written with a
+/// different `map_property` callback, even if the DOM hasn't changed.
+#[test]
+fn map_property_incremental() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("StringValue").with_property("Value", "Hello")),
+    );
+
+    let replace_with = |replacement: &'static str| {
+        Serializer::new().map_property(move |_, name, value| match name {
+            "Value" => Some(Variant::String(replacement.to_owned())),
+            _ => Some(value.clone()),
+        })
+    };
+
+    let serialize = |serializer: &Serializer, cache: Option<&mut SerializerCache>| {
+        let mut buffer = Vec::new();
+        match cache {
+            Some(cache) => serializer
+                .serialize_incremental(
+                    &mut buffer,
+                    &tree,
+                    tree.root().children(),
+                    cache,
+                    &HashSet::new(),
+                )
+                .unwrap(),
+            None => serializer
+                .serialize(&mut buffer, &tree, tree.root().children())
+                .unwrap(),
+        }
+        buffer
+    };
+
+    let mut cache = SerializerCache::new();
+    serialize(&Serializer::new(), Some(&mut cache));
+
+    let first = replace_with("first");
+    assert_eq!(serialize(&first, Some(&mut cache)), serialize(&first, None));
+
+    let second = replace_with("second");
+    assert_eq!(
+        serialize(&second, Some(&mut cache)),
+        serialize(&second, None)
+    );
+
+    assert_eq!(
+        serialize(&Serializer::new(), Some(&mut cache)),
+        serialize(&Serializer::new(), None)
+    );
+}
+
+/// Ensures that `validate_refs` checks the values that a `map_property`
+/// callback returns instead of the values in the DOM.
+#[test]
+fn map_property_validate_refs() {
+    let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+    let outside = tree.insert(tree.root_ref(), InstanceBuilder::new("Folder"));
+    let value = tree.insert(
+        tree.root_ref(),
+        InstanceBuilder::new("ObjectValue").with_property("Value", Ref::none()),
+    );
+    let dangling = tree.insert(
+        tree.root_ref(),
+        InstanceBuilder::new("ObjectValue").with_property("Value", outside),
+    );
+
+    let serialize = |serializer: Serializer, refs: &[Ref]| {
+        serializer
+            .validate_refs(true)
+            .serialize(Vec::new(), &tree, refs)
+    };
+
+    // Mapping a dangling ref away makes it valid.
+    let clear_refs = Serializer::new().map_property(|_, _, value| match value {
+        Variant::Ref(_) => Some(Variant::Ref(Ref::none())),
+        value => Some(value.clone()),
+    });
+    assert!(serialize(Serializer::new(), &[dangling]).is_err());
+    assert!(serialize(clear_refs, &[dangling]).is_ok());
+
+    // Mapping a valid ref so it dangles makes it invalid.
+    let add_refs = Serializer::new().map_property(move |_, _, value| match value {
+        Variant::Ref(_) => Some(Variant::Ref(outside)),
+        value => Some(value.clone()),
+    });
+    assert!(serialize(Serializer::new(), &[value]).is_ok());
+    assert!(serialize(add_refs, &[value]).is_err());
+}
+
+/// Ensures that chunks the deserializer doesn't recognize are kept on the DOM
+/// and written back out unchanged.
+#[test]
+fn unknown_chunks_round_trip() {
+    let tree =
+        WeakDom::new(InstanceBuilder::new("DataModel").with_child(InstanceBuilder::new("Folder")));
+
+    let mut buffer = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut buffer, &tree, tree.root().children())
+        .unwrap();
+
+    // Insert an uncompressed chunk named TEST right before the END chunk,
+    // which is always the last 25 bytes of the file.
+    let data = b"some data rbx_binary doesn't understand";
+    let mut custom_chunk = b"TEST".to_vec();
+    custom_chunk.extend_from_slice(&0u32.to_le_bytes());
+    custom_chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    custom_chunk.extend_from_slice(&0u32.to_le_bytes());
+    custom_chunk.extend_from_slice(data);
+
+    let end_position = buffer.len() - 25;
+    buffer.splice(end_position..end_position, custom_chunk.iter().copied());
+
+    let decoded = decode(buffer.as_slice()).unwrap();
+    assert_eq!(decoded.unknown_chunks().len(), 1);
+    assert_eq!(&decoded.unknown_chunks()[0].name, b"TEST");
+    assert_eq!(decoded.unknown_chunks()[0].data, &data[..]);
+
+    let mut reencoded = Vec::new();
+    Serializer::new()
+        .compression(Compression::None)
+        .serialize(&mut reencoded, &decoded, decoded.root().children())
+        .unwrap();
+
+    assert_eq!(reencoded, buffer);
+}
+
+/// Ensures that infinite and NaN floats keep their exact bit patterns when
+/// written to and read from the binary format.
+#[test]
+fn non_finite_floats() {
+    let values = [std::f32::INFINITY, std::f32::NEG_INFINITY, std::f32::NAN];
+
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_children(values.iter().map(|&value| {
+                InstanceBuilder::new("ParticleEmitter")
+                    .with_property("Lifetime", NumberRange::new(value, value))
+            }))
+            .with_children(values.iter().map(|&value| {
+                InstanceBuilder::new("RayValue").with_property(
+                    "Value",
+                    Ray::new(
+                        Vector3::new(value, 0.0, value),
+                        Vector3::new(1.0, value, 1.0),
+                    ),
+                )
+            })),
+    );
+
+    let decoded = decode(encode_full(&tree).as_slice()).unwrap();
+
+    let bits = |dom: &WeakDom| -> Vec<u32> {
+        let mut bits = Vec::new();
+
+        for referent in dom.root().children() {
+            for value in dom.get_by_ref(*referent).unwrap().properties.values() {
+                match value {
+                    Variant::NumberRange(range) => {
+                        bits.extend(&[range.min.to_bits(), range.max.to_bits()]);
+                    }
+                    Variant::Ray(ray) => {
+                        for vector in &[ray.origin, ray.direction] {
+                            bits.extend(&[
+                                vector.x.to_bits(),
+                                vector.y.to_bits(),
+                                vector.z.to_bits(),
+                            ]);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        bits
+    };
+
+    assert_eq!(bits(&decoded).len(), 24);
+    assert_eq!(bits(&decoded), bits(&tree));
+}
+
+/// Ensures that strict mode rejects classes missing from the reflection
+/// database, while the default accepts them.
+#[test]
+fn strict_classes() {
+    let tree = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Model"))
+            .with_child(InstanceBuilder::new("NotARealClass")),
+    );
+    let buffer = encode_full(&tree);
+
+    let decoded = Deserializer::new(buffer.as_slice()).deserialize().unwrap();
+    assert_eq!(decoded.root().children().len(), 2);
+
+    let err = Deserializer::new(buffer.as_slice())
+        .strict_classes(true)
+        .deserialize()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Class NotARealClass is not in the reflection database"
+    );
+
+    let known =
+        WeakDom::new(InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Model")));
+    let buffer = encode_full(&known);
+    Deserializer::new(buffer.as_slice())
+        .strict_classes(true)
+        .deserialize()
+        .unwrap();
+}
+
+/// Ensures that files are recognized as places when they contain services and
+/// as models otherwise, and that the expected kind is enforced.
+#[test]
+fn model_kind() {
+    let place = WeakDom::new(
+        InstanceBuilder::new("DataModel")
+            .with_child(InstanceBuilder::new("Workspace").with_child(InstanceBuilder::new("Model")))
+            .with_child(InstanceBuilder::new("Lighting")),
+    );
+    let place = encode_full(&place);
+
+    let model = WeakDom::new(
+        InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Model").with_child(InstanceBuilder::new("Folder"))),
+    );
+    let model = encode_full(&model);
+
+    let (decoded, kind) = Deserializer::new(place.as_slice())
+        .deserialize_with_kind()
+        .unwrap();
+    assert_eq!(kind, ModelKind::Place);
+    assert_eq!(decoded.root().children().len(), 2);
+
+    let (_, kind) = Deserializer::new(model.as_slice())
+        .deserialize_with_kind()
+        .unwrap();
+    assert_eq!(kind, ModelKind::Model);
+
+    from_reader_as(ModelKind::Place, place.as_slice()).unwrap();
+    from_reader_as(ModelKind::Model, model.as_slice()).unwrap();
+
+    let err = from_reader_as(ModelKind::Model, place.as_slice()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Expected the file to be a Model, but it was a Place"
+    );
+    assert!(from_reader_as(ModelKind::Place, model.as_slice()).is_err());
+}
@@ -0,0 +1,56 @@
+use rbx_dom_weak::{types::Variant, InstanceBuilder, WeakDom};
+
+use crate::{encode, Deserializer};
+
+/// Ensures that streaming a model yields every instance with its properties,
+/// grouped by class.
+#[test]
+fn stream_instances() {
+    let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(vec![
+        InstanceBuilder::new("StringValue")
+            .with_name("First")
+            .with_property("Value", "Hello"),
+        InstanceBuilder::new("Folder").with_children(vec![InstanceBuilder::new("StringValue")
+            .with_name("Second")
+            .with_property("Value", "World")]),
+    ]));
+
+    let mut buffer = Vec::new();
+    encode(&tree, tree.root().children(), &mut buffer).expect("failed to encode model");
+
+    let instances: Vec<_> = Deserializer::new(buffer.as_slice())
+        .stream()
+        .expect("failed to read header")
+        .collect::<Result<_, _>>()
+        .expect("failed to stream model");
+
+    assert_eq!(instances.len(), 3);
+
+    let mut values: Vec<_> = instances
+        .iter()
+        .filter(|instance| instance.class == "StringValue")
+        .map(|instance| (instance.name.as_str(), &instance.properties["Value"]))
+        .collect();
+    values.sort_by_key(|(name, _)| *name);
+
+    assert_eq!(
+        values,
+        vec![
+            ("First", &Variant::String("Hello".to_owned())),
+            ("Second", &Variant::String("World".to_owned())),
+        ]
+    );
+
+    let folders = instances
+        .iter()
+        .filter(|instance| instance.class == "Folder")
+        .count();
+    assert_eq!(folders, 1);
+}
+
+/// Starting a stream should fail right away if the input isn't a binary model.
+#[test]
+fn stream_bad_header() {
+    let deserializer = Deserializer::new(&b"not a binary model"[..]);
+    assert!(deserializer.stream().is_err());
+}
@@ -4,7 +4,11 @@
 
 #![allow(missing_docs)]
 
-use std::{collections::HashMap, convert::TryInto, io::Read};
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::{TryFrom, TryInto},
+    io::Read,
+};
 
 use byteorder::{LittleEndian, ReadBytesExt};
 use serde::{Deserialize, Serialize};
@@ -62,6 +66,74 @@ impl DecodedModel {
     }
 }
 
+/// A summary of what a binary model contains, useful for figuring out what
+/// makes a file large. Unlike `DecodedModel`, nothing about the file's contents
+/// is kept besides counts and sizes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ModelStats {
+    pub num_instances: usize,
+    pub instances_by_class: BTreeMap<String, usize>,
+    pub properties_by_type: BTreeMap<String, PropertyTypeStats>,
+}
+
+/// Statistics about every property of a given binary type in a model.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PropertyTypeStats {
+    /// The number of values of this type in the file.
+    pub num_values: usize,
+
+    /// The number of bytes that values of this type take up after
+    /// decompression, not counting chunk or property headers.
+    pub num_bytes: usize,
+}
+
+impl ModelStats {
+    pub fn from_reader<R: Read>(mut reader: R) -> Self {
+        FileHeader::decode(&mut reader).expect("invalid file header");
+        let mut stats = ModelStats::default();
+
+        // The number of instance with a given type ID. Used to attribute the
+        // values in a PROP chunk to the right number of instances.
+        let mut count_by_type_id = HashMap::new();
+
+        loop {
+            let chunk = Chunk::decode(&mut reader).expect("invalid chunk");
+            let mut data = chunk.data.as_slice();
+
+            match &chunk.name {
+                b"INST" => {
+                    let type_id = data.read_u32::<LittleEndian>().unwrap();
+                    let type_name = data.read_string().unwrap();
+                    let _object_format = data.read_u8().unwrap();
+                    let num_instances = data.read_u32::<LittleEndian>().unwrap() as usize;
+
+                    count_by_type_id.insert(type_id, num_instances);
+                    stats.num_instances += num_instances;
+                    *stats.instances_by_class.entry(type_name).or_default() += num_instances;
+                }
+                b"PROP" => {
+                    let type_id = data.read_u32::<LittleEndian>().unwrap();
+                    let _prop_name = data.read_string().unwrap();
+                    let prop_type_value = data.read_u8().unwrap();
+
+                    let type_name = match Type::try_from(prop_type_value) {
+                        Ok(prop_type) => format!("{:?}", prop_type),
+                        Err(_) => format!("Unknown({})", prop_type_value),
+                    };
+
+                    let entry = stats.properties_by_type.entry(type_name).or_default();
+                    entry.num_values += count_by_type_id.get(&type_id).copied().unwrap_or(0);
+                    entry.num_bytes += data.len();
+                }
+                b"END\0" => break,
+                _ => {}
+            }
+        }
+
+        stats
+    }
+}
+
 fn decode_meta_chunk<R: Read>(mut reader: R) -> DecodedChunk {
     let num_entries = reader.read_u32::<LittleEndian>().unwrap();
     let mut entries = Vec::with_capacity(num_entries as usize);
@@ -37,6 +37,11 @@ pub enum Type {
     PhysicalProperties = 0x19,
     Color3uint8 = 0x1A,
     Int64 = 0x1B,
+    SharedString = 0x1C,
+    OptionalCFrame = 0x1E,
+    UniqueId = 0x1F,
+    Font = 0x20,
+    SecurityCapabilities = 0x21,
 }
 
 impl Type {
@@ -46,6 +51,8 @@ impl Type {
             VariantType::String => Type::String,
             VariantType::BinaryString => Type::String,
             VariantType::Content => Type::String,
+            VariantType::Attributes => Type::String,
+            VariantType::Tags => Type::String,
 
             VariantType::Bool => Type::Bool,
             VariantType::Int32 => Type::Int32,
@@ -62,6 +69,7 @@ impl Type {
             VariantType::Vector3 => Type::Vector3,
             VariantType::CFrame => Type::CFrame,
             VariantType::EnumValue => Type::Enum,
+            VariantType::EnumItem => Type::Enum,
             VariantType::Ref => Type::Ref,
             VariantType::Vector3int16 => Type::Vector3int16,
             VariantType::NumberSequence => Type::NumberSequence,
@@ -71,6 +79,11 @@ impl Type {
             VariantType::PhysicalProperties => Type::PhysicalProperties,
             VariantType::Color3uint8 => Type::Color3uint8,
             VariantType::Int64 => Type::Int64,
+            VariantType::SharedString => Type::SharedString,
+            VariantType::OptionalCFrame => Type::OptionalCFrame,
+            VariantType::UniqueId => Type::UniqueId,
+            VariantType::Font => Type::Font,
+            VariantType::SecurityCapabilities => Type::SecurityCapabilities,
 
             _ => return None,
         })
@@ -105,6 +118,11 @@ impl Type {
             Type::PhysicalProperties => VariantType::PhysicalProperties,
             Type::Color3uint8 => VariantType::Color3uint8,
             Type::Int64 => VariantType::Int64,
+            Type::SharedString => VariantType::SharedString,
+            Type::OptionalCFrame => VariantType::OptionalCFrame,
+            Type::UniqueId => VariantType::UniqueId,
+            Type::Font => VariantType::Font,
+            Type::SecurityCapabilities => VariantType::SecurityCapabilities,
         })
     }
 }
@@ -141,6 +159,11 @@ impl TryFrom<u8> for Type {
             0x19 => PhysicalProperties,
             0x1A => Color3uint8,
             0x1B => Int64,
+            0x1C => SharedString,
+            0x1E => OptionalCFrame,
+            0x1F => UniqueId,
+            0x20 => Font,
+            0x21 => SecurityCapabilities,
             _ => return Err(InvalidTypeError(value)),
         })
     }
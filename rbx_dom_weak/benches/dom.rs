@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+const INSTANCE_COUNT: usize = 100_000;
+
+fn insert_folders(dom: &mut WeakDom) {
+    let root_ref = dom.root_ref();
+
+    for i in 0..INSTANCE_COUNT {
+        dom.insert(
+            root_ref,
+            InstanceBuilder::new("Folder").with_name(format!("Folder {}", i)),
+        );
+    }
+}
+
+pub fn insert_100k(c: &mut Criterion) {
+    c.bench_function("Insert 100k Folders", |b| {
+        b.iter_batched(
+            || WeakDom::new(InstanceBuilder::new("Folder")),
+            |mut dom| insert_folders(&mut dom),
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+pub fn insert_100k_with_capacity(c: &mut Criterion) {
+    c.bench_function("Insert 100k Folders with capacity", |b| {
+        b.iter_batched(
+            || WeakDom::with_capacity(InstanceBuilder::new("Folder"), INSTANCE_COUNT + 1),
+            |mut dom| insert_folders(&mut dom),
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+criterion_group!(dom, insert_100k, insert_100k_with_capacity);
+criterion_main!(dom);
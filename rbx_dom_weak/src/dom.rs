@@ -1,6 +1,9 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt, mem,
+};
 
-use rbx_types::Ref;
+use rbx_types::{Ref, Variant};
 
 use crate::instance::{Instance, InstanceBuilder};
 
@@ -16,14 +19,35 @@ use crate::instance::{Instance, InstanceBuilder};
 pub struct WeakDom {
     instances: HashMap<Ref, Instance>,
     root_ref: Ref,
+    metadata: BTreeMap<String, String>,
+    unknown_chunks: Vec<UnknownChunk>,
+}
+
+/// A chunk from a binary model or place that rbx_binary didn't understand,
+/// like a `SIGN` chunk holding a signature. The chunk's data is kept as-is so
+/// that it can be written back out when the DOM is serialized again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownChunk {
+    /// The 4-byte name of the chunk.
+    pub name: [u8; 4],
+
+    /// The contents of the chunk, after decompressing it.
+    pub data: Vec<u8>,
 }
 
 impl WeakDom {
     /// Consruct a new `WeakDom` described by the given `InstanceBuilder`.
     pub fn new(builder: InstanceBuilder) -> WeakDom {
+        WeakDom::with_capacity(builder, 0)
+    }
+
+    /// Construct a new `WeakDom` described by the given `InstanceBuilder`,
+    /// with space for at least `capacity` instances before it needs to
+    /// reallocate.
+    pub fn with_capacity(builder: InstanceBuilder, capacity: usize) -> WeakDom {
         let root_ref = builder.referent;
 
-        let mut instances = HashMap::new();
+        let mut instances = HashMap::with_capacity(capacity);
         instances.insert(
             root_ref,
             Instance {
@@ -39,6 +63,8 @@ impl WeakDom {
         let mut dom = WeakDom {
             root_ref,
             instances,
+            metadata: BTreeMap::new(),
+            unknown_chunks: Vec::new(),
         };
 
         for child in builder.children {
@@ -48,6 +74,41 @@ impl WeakDom {
         dom
     }
 
+    /// Construct a new `WeakDom` whose root is an empty `DataModel`, the root
+    /// of every Roblox place. Services can be added to it with
+    /// [`get_or_create_service`](#method.get_or_create_service).
+    pub fn new_data_model() -> WeakDom {
+        WeakDom::new(InstanceBuilder::new("DataModel"))
+    }
+
+    /// Returns the metadata describing the file this DOM came from, like the
+    /// entries of the `META` chunk in binary places.
+    ///
+    /// Entries are kept sorted by key so that serializing them is
+    /// deterministic.
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+
+    /// Returns a mutable reference to the metadata of the `WeakDom`. See
+    /// [`metadata`](#method.metadata).
+    pub fn metadata_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.metadata
+    }
+
+    /// Returns the chunks from the binary file this DOM came from that
+    /// couldn't be interpreted, in the order they appeared in the file.
+    /// Serializing the DOM to the binary format writes them back out.
+    pub fn unknown_chunks(&self) -> &[UnknownChunk] {
+        &self.unknown_chunks
+    }
+
+    /// Returns a mutable reference to the unknown chunks of the `WeakDom`. See
+    /// [`unknown_chunks`](#method.unknown_chunks).
+    pub fn unknown_chunks_mut(&mut self) -> &mut Vec<UnknownChunk> {
+        &mut self.unknown_chunks
+    }
+
     /// Returns the referent of the root instance of the `WeakDom`.
     pub fn root_ref(&self) -> Ref {
         self.root_ref
@@ -59,6 +120,9 @@ impl WeakDom {
     }
 
     /// Returns a _mutable_ reference to the root instance of the `WeakDom`.
+    ///
+    /// Like [`get_by_ref_mut`](#method.get_by_ref_mut), this allows changing
+    /// the root's name, class, and properties, but not its children.
     pub fn root_mut(&mut self) -> &mut Instance {
         self.instances.get_mut(&self.root_ref).unwrap()
     }
@@ -71,10 +135,330 @@ impl WeakDom {
 
     /// Returns a _mutable_ reference to an instance by referent, or `None` if
     /// it is not found.
+    ///
+    /// The instance's name, class, and properties can be changed freely. Its
+    /// parent and children can only be changed through `WeakDom`, which keeps
+    /// the tree consistent.
     pub fn get_by_ref_mut(&mut self, referent: Ref) -> Option<&mut Instance> {
         self.instances.get_mut(&referent)
     }
 
+    /// Returns the number of instances in the DOM, including the root.
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    /// Returns an estimate of the number of bytes of heap memory used by the
+    /// DOM, including every instance, their names, classes, and properties.
+    ///
+    /// This is only an estimate, meant for finding out roughly how much memory
+    /// a large DOM is using. It includes the space reserved by the DOM's maps
+    /// and lists, but not the overhead of the allocator or of hashing. Property
+    /// values are measured with `Variant::heap_size`, which counts shared
+    /// strings once per use.
+    pub fn estimated_heap_size(&self) -> usize {
+        let instances =
+            self.instances.capacity() * (mem::size_of::<Ref>() + mem::size_of::<Instance>());
+
+        let instance_contents: usize = self
+            .instances
+            .values()
+            .map(|instance| {
+                let properties: usize = instance
+                    .properties
+                    .iter()
+                    .map(|(name, value)| name.capacity() + value.heap_size())
+                    .sum();
+
+                instance.name.capacity()
+                    + instance.class.capacity()
+                    + instance.children.capacity() * mem::size_of::<Ref>()
+                    + instance.properties.capacity()
+                        * (mem::size_of::<String>() + mem::size_of::<Variant>())
+                    + properties
+            })
+            .sum();
+
+        instances + instance_contents
+    }
+
+    /// Tells whether the DOM contains nothing but its root instance.
+    ///
+    /// Every `WeakDom` has a root, so this never means that the DOM has no
+    /// instances at all.
+    pub fn is_empty(&self) -> bool {
+        self.instances.len() == 1
+    }
+
+    /// Returns an iterator over the referents of all of the descendants of
+    /// the given instance, not including the instance itself.
+    ///
+    /// Descendants are visited depth-first, in pre-order, and children are
+    /// visited in order. The iterator only holds onto the siblings it has yet
+    /// to visit, so it's suitable for very large trees.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn descendants(&self, referent: Ref) -> impl Iterator<Item = Ref> + '_ {
+        let instance = self
+            .instances
+            .get(&referent)
+            .unwrap_or_else(|| panic!("cannot get descendants of an instance that does not exist"));
+
+        Descendants {
+            dom: self,
+            stack: instance.children.iter().rev().copied().collect(),
+        }
+    }
+
+    /// Returns an iterator over the referents of all of the descendants of the
+    /// root instance of the `WeakDom`.
+    pub fn descendants_of_root(&self) -> impl Iterator<Item = Ref> + '_ {
+        self.descendants(self.root_ref)
+    }
+
+    /// Returns an iterator over the referents of all of the ancestors of the
+    /// given instance, starting with its parent and ending with the root
+    /// instance. The instance itself is not included.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn ancestors(&self, referent: Ref) -> impl Iterator<Item = Ref> + '_ {
+        let instance = self
+            .instances
+            .get(&referent)
+            .unwrap_or_else(|| panic!("cannot get ancestors of an instance that does not exist"));
+
+        Ancestors {
+            dom: self,
+            next: instance.parent,
+        }
+    }
+
+    /// Returns the names of the given instance and all of its ancestors joined
+    /// with `.`, like `Workspace.Model.Part`.
+    ///
+    /// This is equivalent to `Instance:GetFullName()` from Roblox, which leaves
+    /// out the name of the `DataModel` if the instance is inside of one.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn get_full_name(&self, referent: Ref) -> String {
+        let instance = self
+            .instances
+            .get(&referent)
+            .unwrap_or_else(|| panic!("cannot get full name of an instance that does not exist"));
+
+        let mut components = vec![instance.name.as_str()];
+
+        for ancestor_ref in self.ancestors(referent) {
+            let ancestor = &self.instances[&ancestor_ref];
+
+            if ancestor.parent.is_none() && ancestor.class == "DataModel" {
+                break;
+            }
+
+            components.push(ancestor.name.as_str());
+        }
+
+        components.reverse();
+        components.join(".")
+    }
+
+    /// Returns a copy of the referents of the given instance's children, in
+    /// order.
+    ///
+    /// Unlike [`Instance::children`](struct.Instance.html#method.children), the
+    /// returned `Vec` doesn't borrow the DOM, so it can be used to loop over
+    /// the children while mutating them. It's a snapshot: adding, removing, or
+    /// moving children afterwards isn't reflected in it.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn children(&self, referent: Ref) -> Vec<Ref> {
+        self.instances
+            .get(&referent)
+            .unwrap_or_else(|| panic!("cannot find children of an instance that does not exist"))
+            .children
+            .clone()
+    }
+
+    /// Find the first child of the given instance with the given name.
+    ///
+    /// This is equivalent to `Instance:FindFirstChild()` from Roblox. Children
+    /// are stored in insertion order, so "first" means the earliest child with
+    /// a matching name.
+    ///
+    /// ## Panics
+    /// Panics if `parent_ref` does not refer to an instance in the DOM.
+    pub fn find_first_child(&self, parent_ref: Ref, name: &str) -> Option<Ref> {
+        self.find_first_child_by(parent_ref, |child| child.name == name)
+    }
+
+    /// Find the first child of the given instance with the given ClassName.
+    ///
+    /// This is equivalent to `Instance:FindFirstChildOfClass()` from Roblox.
+    /// Subclasses are not considered matches.
+    ///
+    /// ## Panics
+    /// Panics if `parent_ref` does not refer to an instance in the DOM.
+    pub fn find_first_child_of_class(&self, parent_ref: Ref, class: &str) -> Option<Ref> {
+        self.find_first_child_by(parent_ref, |child| child.class == class)
+    }
+
+    /// Follow a path of names separated by dots, like `Workspace.Model.Part`,
+    /// starting from the given instance. Each segment is resolved with
+    /// [`find_first_child`](#method.find_first_child), just like indexing
+    /// instances by name from Lua.
+    ///
+    /// Returns `None` if any segment of the path can't be found. An empty path
+    /// refers to `root` itself. There is no way to escape dots, so instances
+    /// whose names contain a dot can't be reached with this method.
+    ///
+    /// ## Panics
+    /// Panics if `root` does not refer to an instance in the DOM.
+    pub fn get_by_path(&self, root: Ref, path: &str) -> Option<Ref> {
+        if !self.instances.contains_key(&root) {
+            panic!("cannot find children of an instance that does not exist");
+        }
+
+        if path.is_empty() {
+            return Some(root);
+        }
+
+        path.split('.')
+            .try_fold(root, |current, name| self.find_first_child(current, name))
+    }
+
+    /// Build an index from ClassName to the referents of every instance in
+    /// the DOM with that ClassName, including the root.
+    ///
+    /// The index is a snapshot: it borrows the DOM, and it doesn't reflect
+    /// instances that are inserted, removed, or moved after it's built. Build a
+    /// new index after mutating the DOM.
+    ///
+    /// Referents for each class are listed in the same order they're visited
+    /// by [`descendants`](#method.descendants), starting with the root.
+    pub fn build_class_index(&self) -> HashMap<&str, Vec<Ref>> {
+        let mut index: HashMap<&str, Vec<Ref>> = HashMap::new();
+
+        for referent in std::iter::once(self.root_ref).chain(self.descendants_of_root()) {
+            let instance = &self.instances[&referent];
+            index
+                .entry(instance.class.as_str())
+                .or_default()
+                .push(referent);
+        }
+
+        index
+    }
+
+    /// Tells whether this DOM and `other` have the same shape and the same
+    /// contents, ignoring the referents they use.
+    ///
+    /// Instances are paired up by their position in each tree, starting with
+    /// the roots, and compared with
+    /// [`Instance::eq_ignoring_refs`](struct.Instance.html#method.eq_ignoring_refs).
+    /// `Ref` properties must point to instances at the same position in both
+    /// trees, or to instances that are in neither tree. This makes it possible
+    /// to check that a DOM survived a round trip through a file.
+    ///
+    /// Metadata and unknown chunks aren't compared.
+    pub fn structurally_eq(&self, other: &WeakDom) -> bool {
+        let mut pairs = Vec::new();
+        let mut to_visit = vec![(self.root_ref, other.root_ref)];
+
+        while let Some((a_ref, b_ref)) = to_visit.pop() {
+            let a = &self.instances[&a_ref];
+            let b = &other.instances[&b_ref];
+
+            if a.children.len() != b.children.len() || !a.eq_ignoring_refs(b) {
+                return false;
+            }
+
+            pairs.push((a_ref, b_ref));
+            to_visit.extend(a.children.iter().copied().zip(b.children.iter().copied()));
+        }
+
+        let a_to_b: HashMap<Ref, Ref> = pairs.iter().copied().collect();
+
+        pairs.iter().all(|(a_ref, b_ref)| {
+            let b = &other.instances[b_ref];
+
+            self.instances[a_ref]
+                .properties
+                .iter()
+                .all(|(name, value)| match (value, b.properties.get(name)) {
+                    (Variant::Ref(a_target), Some(Variant::Ref(b_target))) => {
+                        match a_to_b.get(a_target) {
+                            Some(expected) => expected == b_target,
+                            None => !other.instances.contains_key(b_target),
+                        }
+                    }
+                    _ => true,
+                })
+        })
+    }
+
+    fn find_first_child_by<F>(&self, parent_ref: Ref, predicate: F) -> Option<Ref>
+    where
+        F: Fn(&Instance) -> bool,
+    {
+        let parent = self
+            .instances
+            .get(&parent_ref)
+            .unwrap_or_else(|| panic!("cannot find children of an instance that does not exist"));
+
+        parent
+            .children
+            .iter()
+            .copied()
+            .find(|child_ref| predicate(&self.instances[child_ref]))
+    }
+
+    /// Rename the given instance to `desired`, appending a number to the name
+    /// if one of its siblings already has it, and return the name that was
+    /// chosen.
+    ///
+    /// Numbers start at 2, so renaming a second child to `Part` names it
+    /// `Part2`. To rename an instance without checking its siblings, set
+    /// [`Instance::name`](struct.Instance.html#structfield.name) through
+    /// [`get_by_ref_mut`](#method.get_by_ref_mut) instead.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in the DOM.
+    pub fn set_name_unique(&mut self, referent: Ref, desired: &str) -> String {
+        let parent_ref = self
+            .instances
+            .get(&referent)
+            .unwrap_or_else(|| panic!("cannot rename an instance that does not exist"))
+            .parent;
+
+        let is_taken = |name: &str| match self.instances.get(&parent_ref) {
+            Some(parent) => parent
+                .children
+                .iter()
+                .any(|child_ref| *child_ref != referent && self.instances[child_ref].name == name),
+            None => false,
+        };
+
+        let mut name = desired.to_owned();
+        let mut suffix = 2;
+        while is_taken(&name) {
+            name = format!("{}{}", desired, suffix);
+            suffix += 1;
+        }
+
+        self.instances.get_mut(&referent).unwrap().name = name.clone();
+        name
+    }
+
+    /// Reserves space for at least `additional` more instances to be inserted
+    /// into the DOM without reallocating.
+    pub fn reserve(&mut self, additional: usize) {
+        self.instances.reserve(additional);
+    }
+
     /// Insert a new instance into the DOM with the given parent.
     ///
     /// ## Panics
@@ -108,6 +492,321 @@ impl WeakDom {
         referent
     }
 
+    /// Insert many new instances into the DOM with the same parent, returning
+    /// their referents in the same order as `builders`.
+    ///
+    /// This is equivalent to calling `insert` for each builder, but reserves
+    /// space for every instance in each builder's subtree up front.
+    ///
+    /// ## Panics
+    /// Panics if `parent_ref` does not refer to an instance in the DOM.
+    pub fn insert_many<I>(&mut self, parent_ref: Ref, builders: I) -> Vec<Ref>
+    where
+        I: IntoIterator<Item = InstanceBuilder>,
+    {
+        let builders: Vec<InstanceBuilder> = builders.into_iter().collect();
+
+        let parent = self
+            .instances
+            .get_mut(&parent_ref)
+            .unwrap_or_else(|| panic!("cannot insert into parent that does not exist"));
+        parent.children.reserve(builders.len());
+
+        let total: usize = builders.iter().map(count_instances).sum();
+        self.instances.reserve(total);
+
+        builders
+            .into_iter()
+            .map(|builder| self.insert(parent_ref, builder))
+            .collect()
+    }
+
+    /// Returns the referent of the service with the given ClassName, like
+    /// `Workspace` or `Lighting`, creating it under the root `DataModel` if it
+    /// doesn't exist yet.
+    ///
+    /// This is equivalent to `DataModel:GetService()` from Roblox. Services are
+    /// singletons, so an existing child of the `DataModel` with the same
+    /// ClassName is always returned instead of creating a second one,
+    /// regardless of its name. Nothing checks that `class` is actually a
+    /// service.
+    ///
+    /// Returns `None` if the root instance of the DOM is not a `DataModel`,
+    /// since only places have services.
+    pub fn get_or_create_service(&mut self, class: &str) -> Option<Ref> {
+        if self.root().class != "DataModel" {
+            return None;
+        }
+
+        let service = match self.find_first_child_of_class(self.root_ref, class) {
+            Some(service) => service,
+            None => self.insert(self.root_ref, InstanceBuilder::new(class)),
+        };
+
+        Some(service)
+    }
+
+    /// Clone the instance with the given referent and all of its descendants
+    /// into `dest`, parenting the copy to `dest_parent`. Returns the referent
+    /// of the new copy.
+    ///
+    /// Every cloned instance is given a fresh referent. `Ref` properties that
+    /// point to an instance inside the cloned subtree are rewritten to point to
+    /// its copy, while `Ref` properties that point outside of the subtree are
+    /// set to `Ref::none()`, since those instances don't exist in `dest`.
+    ///
+    /// ## Panics
+    /// Panics if `referent` does not refer to an instance in this DOM, or if
+    /// `dest_parent` does not refer to an instance in `dest`.
+    pub fn clone_into(&self, referent: Ref, dest: &mut WeakDom, dest_parent: Ref) -> Ref {
+        // Assign a new referent to every instance in the subtree up front so
+        // that Ref properties can be rewritten no matter where in the subtree
+        // they point, including cycles.
+        let mut rewrites = HashMap::new();
+        let mut to_clone = Vec::new();
+
+        let mut to_visit = VecDeque::new();
+        to_visit.push_back(referent);
+
+        while let Some(referent) = to_visit.pop_front() {
+            let instance = self
+                .instances
+                .get(&referent)
+                .unwrap_or_else(|| panic!("cannot clone an instance that does not exist"));
+
+            rewrites.insert(referent, Ref::new());
+            to_clone.push(referent);
+            to_visit.extend(&instance.children);
+        }
+
+        // Parents are always visited before their children, so each parent
+        // will already exist in the destination DOM when we insert a child.
+        for old_referent in to_clone {
+            let instance = &self.instances[&old_referent];
+            let mut copy = Instance {
+                referent: old_referent,
+                children: Vec::new(),
+                parent: instance.parent,
+                name: instance.name.clone(),
+                class: instance.class.clone(),
+                properties: instance.properties.clone(),
+            };
+            rewrite_refs(&mut copy, &rewrites);
+
+            let parent = if old_referent == referent {
+                dest_parent
+            } else {
+                copy.parent
+            };
+
+            dest.insert(
+                parent,
+                InstanceBuilder {
+                    referent: copy.referent,
+                    name: copy.name,
+                    class: copy.class,
+                    properties: copy.properties,
+                    children: Vec::new(),
+                },
+            );
+        }
+
+        rewrites[&referent]
+    }
+
+    /// Create a deep copy of this DOM where every instance is given a fresh
+    /// referent, so that the copy can coexist with the original without any
+    /// referents colliding.
+    ///
+    /// `Ref` properties that point to an instance in this DOM are rewritten to
+    /// point to its copy. `Ref` properties that point to instances that don't
+    /// exist in this DOM are set to `Ref::none()`.
+    pub fn clone_with_fresh_refs(&self) -> WeakDom {
+        let rewrites: HashMap<Ref, Ref> = self
+            .instances
+            .keys()
+            .map(|&referent| (referent, Ref::new()))
+            .collect();
+
+        let instances = self
+            .instances
+            .values()
+            .map(|instance| {
+                let mut copy = Instance {
+                    referent: instance.referent,
+                    children: instance.children.clone(),
+                    parent: instance.parent,
+                    name: instance.name.clone(),
+                    class: instance.class.clone(),
+                    properties: instance.properties.clone(),
+                };
+                rewrite_refs(&mut copy, &rewrites);
+
+                (copy.referent, copy)
+            })
+            .collect();
+
+        WeakDom {
+            instances,
+            root_ref: rewrites[&self.root_ref],
+            metadata: self.metadata.clone(),
+            unknown_chunks: self.unknown_chunks.clone(),
+        }
+    }
+
+    /// Consume the `WeakDom`, returning an iterator over every instance in it
+    /// along with its referent. Instances are moved out rather than cloned.
+    ///
+    /// Each instance is yielded exactly once, in no particular order. The tree
+    /// structure is still described by each instance's own
+    /// [`parent`](struct.Instance.html#method.parent) and
+    /// [`children`](struct.Instance.html#method.children), so callers can
+    /// rebuild it. The root instance is the only one whose parent is
+    /// `Ref::none()`.
+    pub fn into_instances(self) -> impl Iterator<Item = (Ref, Instance)> {
+        self.instances.into_iter()
+    }
+
+    /// Move every child of the root of `other`, along with their descendants,
+    /// into this DOM under `parent`. Returns the new referents of the moved
+    /// children, in the same order that they had in `other`.
+    ///
+    /// Every moved instance is given a fresh referent so that it can't collide
+    /// with any instance already in this DOM. `Ref` properties that point to a
+    /// moved instance are rewritten to point to its new referent, while `Ref`
+    /// properties that point anywhere else, including the root of `other`, are
+    /// set to `Ref::none()`. The root of `other` and its metadata are
+    /// discarded.
+    ///
+    /// ## Panics
+    /// Panics if `parent` does not refer to an instance in this DOM.
+    pub fn merge(&mut self, mut other: WeakDom, parent: Ref) -> Vec<Ref> {
+        if !self.instances.contains_key(&parent) {
+            panic!("cannot merge into a parent that does not exist");
+        }
+
+        let other_root = other.instances.remove(&other.root_ref).unwrap();
+
+        let rewrites: HashMap<Ref, Ref> = other
+            .instances
+            .keys()
+            .map(|&referent| (referent, Ref::new()))
+            .collect();
+
+        self.instances.reserve(other.instances.len());
+
+        for (_, mut instance) in other.instances {
+            let is_top_level = instance.parent == other_root.referent;
+            rewrite_refs(&mut instance, &rewrites);
+
+            if is_top_level {
+                instance.parent = parent;
+            }
+
+            self.instances.insert(instance.referent, instance);
+        }
+
+        let children: Vec<Ref> = other_root
+            .children
+            .iter()
+            .map(|child| rewrites[child])
+            .collect();
+        self.instances
+            .get_mut(&parent)
+            .unwrap()
+            .children
+            .extend_from_slice(&children);
+
+        children
+    }
+
+    /// Move the instance with the given referent to a new parent within this
+    /// DOM. The instance keeps its referent, so any `Ref` properties that point
+    /// to it or its descendants remain valid.
+    ///
+    /// The instance is added after any existing children of its new parent.
+    ///
+    /// ## Panics
+    /// Panics if `referent` or `new_parent_ref` do not refer to instances in
+    /// the DOM, or if `new_parent_ref` is `referent` or one of its descendants.
+    pub fn transfer_within(&mut self, referent: Ref, new_parent_ref: Ref) {
+        let old_parent_ref = self
+            .instances
+            .get(&referent)
+            .unwrap_or_else(|| panic!("cannot move an instance that does not exist"))
+            .parent;
+
+        if !self.instances.contains_key(&new_parent_ref) {
+            panic!("cannot move an instance into a parent that does not exist");
+        }
+
+        if new_parent_ref == referent || self.ancestors(new_parent_ref).any(|id| id == referent) {
+            panic!("cannot move an instance into itself or one of its descendants");
+        }
+
+        if old_parent_ref.is_some() {
+            let old_parent = self.instances.get_mut(&old_parent_ref).unwrap();
+            old_parent.children.retain(|&child| child != referent);
+        }
+
+        let new_parent = self.instances.get_mut(&new_parent_ref).unwrap();
+        new_parent.children.push(referent);
+
+        let instance = self.instances.get_mut(&referent).unwrap();
+        instance.parent = new_parent_ref;
+    }
+
+    /// Detach the instance with the given referent and all of its descendants
+    /// from this DOM, returning them as a new `WeakDom` rooted at that
+    /// instance.
+    ///
+    /// Instances keep their referents, so `Ref` properties that point within
+    /// the removed subtree remain valid. `Ref` properties that point to
+    /// instances still in this DOM are set to `Ref::none()`. The new DOM has no
+    /// metadata or unknown chunks.
+    ///
+    /// Returns `None` if `referent` does not refer to an instance in the DOM,
+    /// or if it refers to the root instance.
+    pub fn remove(&mut self, referent: Ref) -> Option<WeakDom> {
+        if referent == self.root_ref {
+            return None;
+        }
+
+        let parent_ref = self.instances.get(&referent)?.parent;
+        let parent = self.instances.get_mut(&parent_ref).unwrap();
+        parent.children.retain(|&child| child != referent);
+
+        let mut instances = HashMap::new();
+        let mut to_remove = VecDeque::new();
+        to_remove.push_back(referent);
+
+        while let Some(referent) = to_remove.pop_front() {
+            let instance = self.instances.remove(&referent).unwrap();
+            to_remove.extend(instance.children.iter().copied());
+            instances.insert(referent, instance);
+        }
+
+        let removed_refs: HashSet<Ref> = instances.keys().copied().collect();
+        for instance in instances.values_mut() {
+            for value in instance.properties.values_mut() {
+                if let Variant::Ref(target) = value {
+                    if target.is_some() && !removed_refs.contains(target) {
+                        *target = Ref::none();
+                    }
+                }
+            }
+        }
+
+        instances.get_mut(&referent).unwrap().parent = Ref::none();
+
+        Some(WeakDom {
+            instances,
+            root_ref: referent,
+            metadata: BTreeMap::new(),
+            unknown_chunks: Vec::new(),
+        })
+    }
+
     /// Destroy the instance with the given referent.
     ///
     /// ## Panics
@@ -133,4 +832,1188 @@ impl WeakDom {
             to_remove.extend(instance.children);
         }
     }
+
+    /// Swap the properties of the two instances with the given referents.
+    /// Names, classes, and positions in the tree are left alone.
+    ///
+    /// ## Panics
+    /// Panics if either referent does not refer to an instance in the DOM.
+    pub fn swap_properties(&mut self, a: Ref, b: Ref) {
+        if !self.instances.contains_key(&a) || !self.instances.contains_key(&b) {
+            panic!("cannot swap properties of an instance that does not exist");
+        }
+
+        if a == b {
+            return;
+        }
+
+        let a_properties = std::mem::take(&mut self.instances.get_mut(&a).unwrap().properties);
+        let b_instance = self.instances.get_mut(&b).unwrap();
+        let b_properties = std::mem::replace(&mut b_instance.properties, a_properties);
+        self.instances.get_mut(&a).unwrap().properties = b_properties;
+    }
+
+    /// Remove every instance for which `predicate` returns `false`, along with
+    /// all of its descendants. The root instance is never removed.
+    ///
+    /// Removing an instance always removes its descendants, regardless of what
+    /// `predicate` would return for them. `predicate` is only called for
+    /// instances whose ancestors have all been kept, in pre-order.
+    ///
+    /// `Ref` properties on remaining instances that point to removed instances
+    /// are set to `Ref::none()`.
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&Instance) -> bool,
+    {
+        let mut to_destroy = Vec::new();
+        let mut to_visit: Vec<Ref> = self.root().children.iter().rev().copied().collect();
+
+        while let Some(referent) = to_visit.pop() {
+            let instance = &self.instances[&referent];
+
+            if predicate(instance) {
+                to_visit.extend(instance.children.iter().rev());
+            } else {
+                to_destroy.push(referent);
+            }
+        }
+
+        if to_destroy.is_empty() {
+            return;
+        }
+
+        let mut removed_refs = HashSet::new();
+        for referent in to_destroy {
+            removed_refs.insert(referent);
+            removed_refs.extend(self.descendants(referent));
+            self.destroy(referent);
+        }
+
+        for instance in self.instances.values_mut() {
+            for value in instance.properties.values_mut() {
+                if let Variant::Ref(target) = value {
+                    if removed_refs.contains(target) {
+                        *target = Ref::none();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Check that the `WeakDom` is internally consistent, returning every
+    /// problem that was found.
+    ///
+    /// The methods on `WeakDom` keep it consistent on their own, so this is
+    /// mostly useful in tests, or as a sanity check before serializing a DOM
+    /// that went through lots of changes. Problems are returned in no
+    /// particular order.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+
+        let root = match self.instances.get(&self.root_ref) {
+            Some(root) => root,
+            None => {
+                return Err(vec![ValidationError::MissingRoot {
+                    root: self.root_ref,
+                }])
+            }
+        };
+
+        if root.parent.is_some() {
+            errors.push(ValidationError::RootHasParent {
+                parent: root.parent,
+            });
+        }
+
+        let mut seen_children = HashSet::new();
+        let mut duplicate_children = HashSet::new();
+
+        for (&key, instance) in &self.instances {
+            if instance.referent != key {
+                errors.push(ValidationError::MismatchedReferent {
+                    key,
+                    referent: instance.referent,
+                });
+            }
+
+            if key != self.root_ref
+                && instance.parent.is_some()
+                && !self.instances.contains_key(&instance.parent)
+            {
+                errors.push(ValidationError::MissingParent {
+                    referent: key,
+                    parent: instance.parent,
+                });
+            }
+
+            for &child_ref in &instance.children {
+                if !seen_children.insert(child_ref) && duplicate_children.insert(child_ref) {
+                    errors.push(ValidationError::DuplicateChild { child: child_ref });
+                }
+
+                match self.instances.get(&child_ref) {
+                    Some(child) => {
+                        if child.parent != key {
+                            errors.push(ValidationError::ParentMismatch {
+                                child: child_ref,
+                                expected_parent: key,
+                                actual_parent: child.parent,
+                            });
+                        }
+                    }
+                    None => errors.push(ValidationError::MissingChild {
+                        parent: key,
+                        child: child_ref,
+                    }),
+                }
+            }
+        }
+
+        // Walk up the parent pointers from every instance, remembering which
+        // instances are known to lead to the root (or to a dead end, which is
+        // reported above) so that each instance is only walked once.
+        let mut finished = HashSet::new();
+        for &start in self.instances.keys() {
+            let mut path = Vec::new();
+            let mut on_path = HashSet::new();
+            let mut current = start;
+
+            while current.is_some() && !finished.contains(&current) {
+                if !on_path.insert(current) {
+                    let cycle_start = path.iter().position(|&r| r == current).unwrap();
+                    errors.push(ValidationError::Cycle {
+                        referents: path[cycle_start..].to_vec(),
+                    });
+                    break;
+                }
+
+                path.push(current);
+
+                current = match self.instances.get(&current) {
+                    Some(instance) if current != self.root_ref => instance.parent,
+                    _ => Ref::none(),
+                };
+            }
+
+            finished.extend(path);
+        }
+
+        let mut reachable = HashSet::new();
+        let mut to_visit = vec![self.root_ref];
+        while let Some(referent) = to_visit.pop() {
+            if !reachable.insert(referent) {
+                continue;
+            }
+
+            if let Some(instance) = self.instances.get(&referent) {
+                to_visit.extend(instance.children.iter().copied());
+            }
+        }
+
+        for &referent in self.instances.keys() {
+            if !reachable.contains(&referent) {
+                errors.push(ValidationError::Orphaned { referent });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// A problem found by [`WeakDom::validate`](struct.WeakDom.html#method.validate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The DOM's root referent doesn't refer to an instance in the DOM.
+    MissingRoot { root: Ref },
+
+    /// The root instance has a parent.
+    RootHasParent { parent: Ref },
+
+    /// An instance is stored under a different referent than its own.
+    MismatchedReferent { key: Ref, referent: Ref },
+
+    /// An instance lists a child that doesn't exist in the DOM.
+    MissingChild { parent: Ref, child: Ref },
+
+    /// An instance has a parent that doesn't exist in the DOM.
+    MissingParent { referent: Ref, parent: Ref },
+
+    /// A child's parent doesn't match the instance that lists it as a child.
+    ParentMismatch {
+        child: Ref,
+        expected_parent: Ref,
+        actual_parent: Ref,
+    },
+
+    /// An instance is listed as a child more than once, either by the same
+    /// parent or by several.
+    DuplicateChild { child: Ref },
+
+    /// Following parents from one of these instances leads back to it.
+    Cycle { referents: Vec<Ref> },
+
+    /// An instance can't be reached by following children from the root.
+    Orphaned { referent: Ref },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::MissingRoot { root } => {
+                write!(formatter, "Root instance {} does not exist", root)
+            }
+            ValidationError::RootHasParent { parent } => {
+                write!(formatter, "Root instance has parent {}", parent)
+            }
+            ValidationError::MismatchedReferent { key, referent } => write!(
+                formatter,
+                "Instance with referent {} is stored under referent {}",
+                referent, key
+            ),
+            ValidationError::MissingChild { parent, child } => write!(
+                formatter,
+                "Instance {} has child {}, which does not exist",
+                parent, child
+            ),
+            ValidationError::MissingParent { referent, parent } => write!(
+                formatter,
+                "Instance {} has parent {}, which does not exist",
+                referent, parent
+            ),
+            ValidationError::ParentMismatch {
+                child,
+                expected_parent,
+                actual_parent,
+            } => write!(
+                formatter,
+                "Instance {} is a child of {}, but its parent is {}",
+                child, expected_parent, actual_parent
+            ),
+            ValidationError::DuplicateChild { child } => {
+                write!(
+                    formatter,
+                    "Instance {} is listed as a child more than once",
+                    child
+                )
+            }
+            ValidationError::Cycle { referents } => {
+                write!(formatter, "Instances form a cycle of parents:")?;
+
+                for referent in referents {
+                    write!(formatter, " {}", referent)?;
+                }
+
+                Ok(())
+            }
+            ValidationError::Orphaned { referent } => write!(
+                formatter,
+                "Instance {} is not a descendant of the root instance",
+                referent
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Iterator returned by `WeakDom::ancestors`.
+struct Ancestors<'a> {
+    dom: &'a WeakDom,
+    next: Ref,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = Ref;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_none() {
+            return None;
+        }
+
+        let referent = self.next;
+        self.next = self.dom.instances[&referent].parent;
+
+        Some(referent)
+    }
+}
+
+/// Iterator returned by `WeakDom::descendants`.
+struct Descendants<'a> {
+    dom: &'a WeakDom,
+    stack: Vec<Ref>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = Ref;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let referent = self.stack.pop()?;
+
+        // Children are pushed in reverse so that the first child is the next
+        // value to be popped off of the stack.
+        let instance = &self.dom.instances[&referent];
+        self.stack.extend(instance.children.iter().rev());
+
+        Some(referent)
+    }
+}
+
+/// Counts the number of instances in the subtree described by `builder`,
+/// including `builder` itself.
+fn count_instances(builder: &InstanceBuilder) -> usize {
+    1 + builder.children.iter().map(count_instances).sum::<usize>()
+}
+
+/// Rewrites the referent, parent, children, and `Ref` properties of `instance`
+/// using `rewrites`. Referents that aren't in `rewrites` are replaced with
+/// `Ref::none()`.
+fn rewrite_refs(instance: &mut Instance, rewrites: &HashMap<Ref, Ref>) {
+    let rewrite = |referent: &Ref| rewrites.get(referent).copied().unwrap_or_else(Ref::none);
+
+    instance.referent = rewrite(&instance.referent);
+    instance.parent = rewrite(&instance.parent);
+
+    for child in &mut instance.children {
+        *child = rewrite(child);
+    }
+
+    for value in instance.properties.values_mut() {
+        if let Variant::Ref(target) = value {
+            *target = rewrite(target);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::Attributes;
+
+    #[test]
+    fn ancestors_and_full_name() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel").with_name("Game"));
+        let workspace = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Workspace").with_name("Workspace"),
+        );
+        let model = dom.insert(workspace, InstanceBuilder::new("Model").with_name("Model"));
+        let part = dom.insert(model, InstanceBuilder::new("Part").with_name("Part"));
+
+        let ancestors: Vec<_> = dom.ancestors(part).collect();
+        assert_eq!(ancestors, vec![model, workspace, dom.root_ref()]);
+        assert_eq!(dom.ancestors(dom.root_ref()).count(), 0);
+
+        assert_eq!(dom.get_full_name(part), "Workspace.Model.Part");
+        assert_eq!(dom.get_full_name(dom.root_ref()), "Game");
+
+        let folders = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Root")
+                .with_child(InstanceBuilder::new("Folder").with_name("Child")),
+        );
+        let child = folders.root().children()[0];
+        assert_eq!(folders.get_full_name(child), "Root.Child");
+    }
+
+    #[test]
+    fn transfer_within() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let first = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+        let second = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+        let value = dom.insert(
+            first,
+            InstanceBuilder::new("ObjectValue").with_property("Value", second),
+        );
+
+        dom.transfer_within(value, second);
+
+        assert!(dom.get_by_ref(first).unwrap().children().is_empty());
+        assert_eq!(dom.get_by_ref(second).unwrap().children(), &[value]);
+
+        let value_instance = dom.get_by_ref(value).unwrap();
+        assert_eq!(value_instance.parent(), second);
+        assert_eq!(
+            value_instance.properties.get("Value"),
+            Some(&Variant::Ref(second))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot move an instance into itself or one of its descendants")]
+    fn transfer_within_descendant() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let parent = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+        let child = dom.insert(parent, InstanceBuilder::new("Folder"));
+
+        dom.transfer_within(parent, child);
+    }
+
+    #[test]
+    fn insert_many() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+
+        let builders: Vec<_> = (0..4)
+            .map(|i| {
+                InstanceBuilder::new("Folder")
+                    .with_name(format!("Child {}", i))
+                    .with_child(InstanceBuilder::new("StringValue"))
+            })
+            .collect();
+        let expected: Vec<Ref> = builders.iter().map(|builder| builder.referent).collect();
+
+        let inserted = dom.insert_many(dom.root_ref(), builders);
+        assert_eq!(inserted, expected);
+        assert_eq!(dom.root().children(), expected.as_slice());
+
+        for (i, referent) in inserted.into_iter().enumerate() {
+            let instance = dom.get_by_ref(referent).unwrap();
+            assert_eq!(instance.name, format!("Child {}", i));
+            assert_eq!(instance.parent(), dom.root_ref());
+            assert_eq!(instance.children().len(), 1);
+        }
+    }
+
+    #[test]
+    fn find_first_child() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+
+        let first = dom.insert(root_ref, InstanceBuilder::new("Part").with_name("Thing"));
+        let second = dom.insert(root_ref, InstanceBuilder::new("Model").with_name("Thing"));
+        let third = dom.insert(root_ref, InstanceBuilder::new("Model").with_name("Other"));
+        let nested = dom.insert(third, InstanceBuilder::new("Folder").with_name("Nested"));
+
+        assert_eq!(dom.find_first_child(root_ref, "Thing"), Some(first));
+        assert_eq!(dom.find_first_child(root_ref, "Other"), Some(third));
+        assert_eq!(dom.find_first_child(root_ref, "Nested"), None);
+        assert_eq!(dom.find_first_child(third, "Nested"), Some(nested));
+
+        assert_eq!(
+            dom.find_first_child_of_class(root_ref, "Model"),
+            Some(second)
+        );
+        assert_eq!(dom.find_first_child_of_class(root_ref, "Folder"), None);
+    }
+
+    #[test]
+    fn get_by_path() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let root_ref = dom.root_ref();
+
+        let workspace = dom.insert(root_ref, InstanceBuilder::new("Workspace"));
+        let first = dom.insert(workspace, InstanceBuilder::new("Model").with_name("Thing"));
+        let _second = dom.insert(workspace, InstanceBuilder::new("Model").with_name("Thing"));
+        let part = dom.insert(first, InstanceBuilder::new("Part"));
+
+        assert_eq!(dom.get_by_path(root_ref, ""), Some(root_ref));
+        assert_eq!(dom.get_by_path(root_ref, "Workspace"), Some(workspace));
+        assert_eq!(dom.get_by_path(root_ref, "Workspace.Thing"), Some(first));
+        assert_eq!(
+            dom.get_by_path(root_ref, "Workspace.Thing.Part"),
+            Some(part)
+        );
+        assert_eq!(dom.get_by_path(workspace, "Thing.Part"), Some(part));
+
+        assert_eq!(dom.get_by_path(root_ref, "Workspace.Missing.Part"), None);
+        assert_eq!(
+            dom.get_by_path(root_ref, "Workspace.Thing.Part.Extra"),
+            None
+        );
+        assert_eq!(dom.get_by_path(root_ref, "Workspace."), None);
+    }
+
+    #[test]
+    fn remove() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let outside = dom.insert(dom.root_ref(), InstanceBuilder::new("Part"));
+        let model = dom.insert(dom.root_ref(), InstanceBuilder::new("Model"));
+        let inner = dom.insert(model, InstanceBuilder::new("Part"));
+        let value = dom.insert(
+            model,
+            InstanceBuilder::new("ObjectValue")
+                .with_property("Inside", inner)
+                .with_property("Outside", outside),
+        );
+        dom.get_by_ref_mut(model)
+            .unwrap()
+            .properties
+            .insert("PrimaryPart".to_owned(), Variant::Ref(inner));
+
+        let removed = dom.remove(model).unwrap();
+
+        assert_eq!(dom.root().children(), &[outside]);
+        assert!(dom.get_by_ref(model).is_none());
+        assert!(dom.get_by_ref(inner).is_none());
+        assert!(dom.get_by_ref(value).is_none());
+
+        assert_eq!(removed.root_ref(), model);
+        assert!(removed.root().parent().is_none());
+        assert_eq!(removed.root().children(), &[inner, value]);
+        assert_eq!(
+            removed.root().properties.get("PrimaryPart"),
+            Some(&Variant::Ref(inner))
+        );
+
+        let value_instance = removed.get_by_ref(value).unwrap();
+        assert_eq!(
+            value_instance.properties.get("Inside"),
+            Some(&Variant::Ref(inner))
+        );
+        assert_eq!(
+            value_instance.properties.get("Outside"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+    }
+
+    #[test]
+    fn remove_root_or_unknown() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+
+        assert!(dom.remove(dom.root_ref()).is_none());
+        assert!(dom.remove(Ref::new()).is_none());
+        assert!(dom.get_by_ref(dom.root_ref()).is_some());
+    }
+
+    #[test]
+    fn get_by_ref_mut() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let child = dom.insert(dom.root_ref(), InstanceBuilder::new("StringValue"));
+
+        let instance = dom.get_by_ref_mut(child).unwrap();
+        instance.name = "Renamed".to_owned();
+        instance.properties.insert("Value".to_owned(), "Hi".into());
+
+        let instance = dom.get_by_ref(child).unwrap();
+        assert_eq!(instance.name, "Renamed");
+        assert_eq!(
+            instance.properties.get("Value"),
+            Some(&Variant::String("Hi".to_owned()))
+        );
+        assert_eq!(instance.parent(), dom.root_ref());
+
+        assert!(dom.get_by_ref_mut(Ref::none()).is_none());
+        assert!(dom.get_by_ref_mut(Ref::new()).is_none());
+    }
+
+    #[test]
+    fn root_mut() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let workspace = dom.insert(dom.root_ref(), InstanceBuilder::new("Workspace"));
+
+        let root = dom.root_mut();
+        root.name = "Place".to_owned();
+        root.properties
+            .insert("Attributes".to_owned(), Attributes::new().into());
+
+        let root = dom.root();
+        assert_eq!(root.name, "Place");
+        assert_eq!(
+            root.properties.get("Attributes"),
+            Some(&Variant::Attributes(Attributes::new()))
+        );
+        assert_eq!(root.children(), &[workspace]);
+        assert_eq!(dom.get_by_ref(workspace).unwrap().parent(), dom.root_ref());
+    }
+
+    #[test]
+    fn clone_into_rewrites_refs() {
+        let source = {
+            let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+
+            let outside = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+
+            let model = InstanceBuilder::new("Model");
+            let model_ref = model.referent;
+            let inner = InstanceBuilder::new("ObjectValue").with_name("Inner");
+            let inner_ref = inner.referent;
+
+            // Inner points back at its parent and Model points at its child,
+            // forming a cycle within the subtree. Outer points outside of it.
+            let model = model
+                .with_property("PrimaryPart", inner_ref)
+                .with_child(inner.with_property("Value", model_ref))
+                .with_child(
+                    InstanceBuilder::new("ObjectValue")
+                        .with_name("Outer")
+                        .with_property("Value", outside),
+                );
+
+            dom.insert(dom.root_ref(), model);
+            dom
+        };
+
+        let model_ref = source.root().children()[1];
+
+        let mut dest = WeakDom::new(InstanceBuilder::new("Folder"));
+        let dest_root = dest.root_ref();
+        let new_model_ref = source.clone_into(model_ref, &mut dest, dest_root);
+
+        assert_ne!(new_model_ref, model_ref);
+        assert_eq!(dest.root().children(), &[new_model_ref]);
+
+        let new_model = dest.get_by_ref(new_model_ref).unwrap();
+        assert_eq!(new_model.class, "Model");
+        assert_eq!(new_model.parent(), dest_root);
+        assert_eq!(new_model.children().len(), 2);
+
+        let new_inner_ref = new_model.children()[0];
+        let new_inner = dest.get_by_ref(new_inner_ref).unwrap();
+        assert_eq!(new_inner.name, "Inner");
+        assert_eq!(
+            new_model.properties.get("PrimaryPart"),
+            Some(&Variant::Ref(new_inner_ref))
+        );
+        assert_eq!(
+            new_inner.properties.get("Value"),
+            Some(&Variant::Ref(new_model_ref))
+        );
+
+        let new_outer = dest.get_by_ref(new_model.children()[1]).unwrap();
+        assert_eq!(new_outer.name, "Outer");
+        assert_eq!(
+            new_outer.properties.get("Value"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+
+        // The source DOM should be left untouched.
+        assert!(source.get_by_ref(model_ref).is_some());
+        assert!(source.get_by_ref(new_model_ref).is_none());
+    }
+
+    #[test]
+    fn clone_with_fresh_refs() {
+        let source = {
+            let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+
+            let part = InstanceBuilder::new("Part").with_property("Anchored", true);
+            let part_ref = part.referent;
+
+            let model = InstanceBuilder::new("Model")
+                .with_property("PrimaryPart", part_ref)
+                .with_child(part)
+                .with_child(
+                    InstanceBuilder::new("ObjectValue")
+                        .with_name("Dangling")
+                        .with_property("Value", Ref::new()),
+                );
+
+            dom.insert(dom.root_ref(), model);
+            dom
+        };
+
+        let copy = source.clone_with_fresh_refs();
+
+        assert_ne!(copy.root_ref(), source.root_ref());
+        assert_eq!(copy.root().class, "DataModel");
+        assert_eq!(copy.root().parent(), Ref::none());
+        assert_eq!(copy.descendants_of_root().count(), 3);
+
+        for (old_ref, new_ref) in source.descendants_of_root().zip(copy.descendants_of_root()) {
+            assert_ne!(old_ref, new_ref);
+            assert!(source.get_by_ref(new_ref).is_none());
+
+            let old = source.get_by_ref(old_ref).unwrap();
+            let new = copy.get_by_ref(new_ref).unwrap();
+            assert_eq!(old.name, new.name);
+            assert_eq!(old.class, new.class);
+            assert_eq!(old.children().len(), new.children().len());
+        }
+
+        let model = copy.get_by_ref(copy.root().children()[0]).unwrap();
+        let part_ref = model.children()[0];
+        assert_eq!(model.parent(), copy.root_ref());
+        assert_eq!(
+            model.properties.get("PrimaryPart"),
+            Some(&Variant::Ref(part_ref))
+        );
+
+        let part = copy.get_by_ref(part_ref).unwrap();
+        assert_eq!(part.parent(), model.referent());
+        assert_eq!(part.properties.get("Anchored"), Some(&Variant::Bool(true)));
+
+        let dangling = copy.get_by_ref(model.children()[1]).unwrap();
+        assert_eq!(
+            dangling.properties.get("Value"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+    }
+
+    #[test]
+    fn descendants_pre_order() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Root")
+                .with_child(
+                    InstanceBuilder::new("Folder")
+                        .with_name("A")
+                        .with_child(InstanceBuilder::new("Folder").with_name("A1"))
+                        .with_child(InstanceBuilder::new("Folder").with_name("A2")),
+                )
+                .with_child(
+                    InstanceBuilder::new("Folder")
+                        .with_name("B")
+                        .with_child(InstanceBuilder::new("Folder").with_name("B1")),
+                ),
+        );
+
+        let names: Vec<_> = dom
+            .descendants_of_root()
+            .map(|referent| dom.get_by_ref(referent).unwrap().name.as_str())
+            .collect();
+        assert_eq!(names, ["A", "A1", "A2", "B", "B1"]);
+
+        let a_ref = dom.root().children()[0];
+        let names: Vec<_> = dom
+            .descendants(a_ref)
+            .map(|referent| dom.get_by_ref(referent).unwrap().name.as_str())
+            .collect();
+        assert_eq!(names, ["A1", "A2"]);
+    }
+
+    #[test]
+    fn swap_properties() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let a = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("StringValue").with_property("Value", "a"),
+        );
+        let b = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("IntValue").with_property("Value", 2i32),
+        );
+
+        dom.swap_properties(a, b);
+
+        let a_instance = dom.get_by_ref(a).unwrap();
+        assert_eq!(a_instance.class, "StringValue");
+        assert_eq!(a_instance.properties.get("Value"), Some(&Variant::Int32(2)));
+
+        let b_instance = dom.get_by_ref(b).unwrap();
+        assert_eq!(b_instance.class, "IntValue");
+        assert_eq!(
+            b_instance.properties.get("Value"),
+            Some(&Variant::String("a".to_owned()))
+        );
+
+        // Swapping an instance with itself does nothing.
+        dom.swap_properties(a, a);
+        assert_eq!(
+            dom.get_by_ref(a).unwrap().properties.get("Value"),
+            Some(&Variant::Int32(2))
+        );
+    }
+
+    #[test]
+    fn retain() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Script"));
+        let root_ref = dom.root_ref();
+
+        let kept = dom.insert(root_ref, InstanceBuilder::new("Part"));
+        let script = dom.insert(kept, InstanceBuilder::new("Script"));
+        let model = dom.insert(root_ref, InstanceBuilder::new("Script"));
+        let nested = dom.insert(model, InstanceBuilder::new("Part"));
+        let value = dom.insert(
+            root_ref,
+            InstanceBuilder::new("ObjectValue")
+                .with_property("Removed", nested)
+                .with_property("Kept", kept),
+        );
+
+        let mut visited = Vec::new();
+        dom.retain(|instance| {
+            visited.push(instance.referent());
+            instance.class != "Script"
+        });
+
+        // The root is never passed to the predicate, and descendants of
+        // removed instances are skipped.
+        assert_eq!(visited, [kept, script, model, value]);
+
+        assert_eq!(dom.root().children(), &[kept, value]);
+        assert!(dom.get_by_ref(kept).unwrap().children().is_empty());
+        assert!(dom.get_by_ref(script).is_none());
+        assert!(dom.get_by_ref(model).is_none());
+        assert!(dom.get_by_ref(nested).is_none());
+
+        let value_instance = dom.get_by_ref(value).unwrap();
+        assert_eq!(
+            value_instance.properties.get("Removed"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+        assert_eq!(
+            value_instance.properties.get("Kept"),
+            Some(&Variant::Ref(kept))
+        );
+    }
+
+    #[test]
+    fn validate_consistent() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let workspace = dom.insert(dom.root_ref(), InstanceBuilder::new("Workspace"));
+        let model = dom.insert(
+            workspace,
+            InstanceBuilder::new("Model").with_child(InstanceBuilder::new("Part")),
+        );
+        let folder = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+
+        dom.transfer_within(model, folder);
+        dom.destroy(workspace);
+
+        assert_eq!(dom.validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_all_problems() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let first = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+        let second = dom.insert(first, InstanceBuilder::new("Folder"));
+        let third = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+        let missing = Ref::new();
+
+        // Make `first` and `second` each other's parent, detaching them from
+        // the root.
+        let root_ref = dom.root_ref();
+        dom.instances
+            .get_mut(&root_ref)
+            .unwrap()
+            .children
+            .retain(|&child| child != first);
+        dom.instances.get_mut(&first).unwrap().parent = second;
+        dom.instances.get_mut(&second).unwrap().children.push(first);
+
+        // List `third` twice, along with a child that doesn't exist.
+        let root = dom.instances.get_mut(&root_ref).unwrap();
+        root.children.push(third);
+        root.children.push(missing);
+
+        let errors = dom.validate().unwrap_err();
+        assert_eq!(errors.len(), 5, "{:#?}", errors);
+
+        assert!(errors.contains(&ValidationError::DuplicateChild { child: third }));
+        assert!(errors.contains(&ValidationError::MissingChild {
+            parent: root_ref,
+            child: missing,
+        }));
+        assert!(errors.contains(&ValidationError::Orphaned { referent: first }));
+        assert!(errors.contains(&ValidationError::Orphaned { referent: second }));
+
+        let cycle = errors
+            .iter()
+            .find_map(|error| match error {
+                ValidationError::Cycle { referents } => Some(referents),
+                _ => None,
+            })
+            .expect("cycle was not reported");
+        assert_eq!(cycle.len(), 2);
+        assert!(cycle.contains(&first) && cycle.contains(&second));
+    }
+
+    #[test]
+    fn validate_parent_mismatch() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let first = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+        let second = dom.insert(dom.root_ref(), InstanceBuilder::new("Folder"));
+
+        dom.instances.get_mut(&second).unwrap().parent = first;
+
+        assert_eq!(
+            dom.validate(),
+            Err(vec![ValidationError::ParentMismatch {
+                child: second,
+                expected_parent: dom.root_ref(),
+                actual_parent: first,
+            }])
+        );
+    }
+
+    #[test]
+    fn build_class_index() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let root_ref = dom.root_ref();
+        let first = dom.insert(root_ref, InstanceBuilder::new("Script"));
+        let folder = dom.insert(root_ref, InstanceBuilder::new("Folder"));
+        let second = dom.insert(folder, InstanceBuilder::new("Script"));
+        let third = dom.insert(root_ref, InstanceBuilder::new("Script"));
+
+        let index = dom.build_class_index();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index["DataModel"], vec![root_ref]);
+        assert_eq!(index["Folder"], vec![folder]);
+        assert_eq!(index["Script"], vec![first, second, third]);
+        assert!(!index.contains_key("Part"));
+    }
+
+    #[test]
+    fn into_instances() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let root_ref = dom.root_ref();
+        let folder = dom.insert(root_ref, InstanceBuilder::new("Folder"));
+        let part = dom.insert(folder, InstanceBuilder::new("Part"));
+
+        let instances: HashMap<Ref, Instance> = dom.into_instances().collect();
+        assert_eq!(instances.len(), 3);
+
+        for (referent, instance) in &instances {
+            assert_eq!(*referent, instance.referent());
+        }
+
+        assert_eq!(instances[&root_ref].parent(), Ref::none());
+        assert_eq!(instances[&root_ref].children(), &[folder]);
+        assert_eq!(instances[&folder].parent(), root_ref);
+        assert_eq!(instances[&folder].children(), &[part]);
+        assert_eq!(instances[&part].parent(), folder);
+        assert_eq!(instances[&part].class, "Part");
+    }
+
+    #[test]
+    fn children() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+        let first = dom.insert(root_ref, InstanceBuilder::new("Part"));
+        let second = dom.insert(root_ref, InstanceBuilder::new("Part"));
+
+        let children = dom.children(root_ref);
+        assert_eq!(children, vec![first, second]);
+
+        for child_ref in dom.children(root_ref) {
+            dom.get_by_ref_mut(child_ref).unwrap().name = "Renamed".to_owned();
+            dom.insert(root_ref, InstanceBuilder::new("Folder"));
+        }
+
+        assert_eq!(dom.get_by_ref(first).unwrap().name, "Renamed");
+        assert_eq!(dom.get_by_ref(second).unwrap().name, "Renamed");
+        assert_eq!(dom.root().children().len(), 4);
+        assert_eq!(children.len(), 2);
+        assert!(dom.children(first).is_empty());
+    }
+
+    #[test]
+    fn structurally_eq() {
+        fn model(extra_ref: bool) -> WeakDom {
+            let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+            let root_ref = dom.root_ref();
+            let target = dom.insert(root_ref, InstanceBuilder::new("Part"));
+            let pointer = dom.insert(
+                root_ref,
+                InstanceBuilder::new("ObjectValue").with_property("Value", target),
+            );
+
+            if extra_ref {
+                dom.get_by_ref_mut(pointer)
+                    .unwrap()
+                    .properties
+                    .insert("Value".to_owned(), Variant::Ref(pointer));
+            }
+
+            dom
+        }
+
+        // Each DOM uses different referents, but they're still equal.
+        let dom = model(false);
+        assert!(dom.structurally_eq(&model(false)));
+        assert!(dom.structurally_eq(&dom));
+
+        // The ref points to a different position in the tree.
+        assert!(!dom.structurally_eq(&model(true)));
+
+        // A child is missing.
+        let mut missing = model(false);
+        let child = missing.root().children()[0];
+        missing.destroy(child);
+        assert!(!dom.structurally_eq(&missing));
+
+        // A property changed.
+        let mut changed = model(false);
+        let child = changed.root().children()[0];
+        changed.get_by_ref_mut(child).unwrap().name = "Other".to_owned();
+        assert!(!dom.structurally_eq(&changed));
+    }
+
+    #[test]
+    fn estimated_heap_size() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let empty = dom.estimated_heap_size();
+        assert!(empty > 0);
+
+        let root_ref = dom.root_ref();
+        dom.insert(
+            root_ref,
+            InstanceBuilder::new("StringValue").with_property("Value", "x".repeat(10_000)),
+        );
+
+        // The new instance's value alone takes up 10 KB.
+        assert!(dom.estimated_heap_size() >= empty + 10_000);
+    }
+
+    #[test]
+    fn len() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        assert_eq!(dom.len(), 1);
+        assert!(dom.is_empty());
+
+        let root_ref = dom.root_ref();
+        let model = dom.insert(
+            root_ref,
+            InstanceBuilder::new("Model").with_children(vec![
+                InstanceBuilder::new("Part"),
+                InstanceBuilder::new("Part"),
+            ]),
+        );
+        assert_eq!(dom.len(), 4);
+        assert!(!dom.is_empty());
+
+        dom.destroy(model);
+        assert_eq!(dom.len(), 1);
+        assert!(dom.is_empty());
+    }
+
+    #[test]
+    fn set_name_unique() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+        let first = dom.insert(root_ref, InstanceBuilder::new("Part"));
+        let second = dom.insert(root_ref, InstanceBuilder::new("Part"));
+        let third = dom.insert(root_ref, InstanceBuilder::new("Part"));
+
+        assert_eq!(dom.set_name_unique(first, "Door"), "Door");
+        assert_eq!(dom.set_name_unique(second, "Door"), "Door2");
+        assert_eq!(dom.set_name_unique(third, "Door"), "Door3");
+
+        // An instance doesn't collide with its own name.
+        assert_eq!(dom.set_name_unique(second, "Door2"), "Door2");
+
+        assert_eq!(dom.get_by_ref(first).unwrap().name, "Door");
+        assert_eq!(dom.get_by_ref(second).unwrap().name, "Door2");
+        assert_eq!(dom.get_by_ref(third).unwrap().name, "Door3");
+
+        // The root has no siblings, so it always gets the name it asks for.
+        assert_eq!(dom.set_name_unique(root_ref, "Door"), "Door");
+    }
+
+    #[test]
+    fn merge() {
+        fn model(name: &str) -> WeakDom {
+            let mut dom = WeakDom::new(InstanceBuilder::new("Folder").with_name("Model Root"));
+            let root_ref = dom.root_ref();
+
+            let value = InstanceBuilder::new("ObjectValue").with_name(name);
+            let value_ref = value.referent;
+            let target = InstanceBuilder::new("ObjectValue").with_name("Target");
+            let target_ref = target.referent;
+
+            dom.insert(
+                root_ref,
+                value
+                    .with_property("Value", value_ref)
+                    .with_child(target.with_property("Value", value_ref)),
+            );
+            dom.get_by_ref_mut(value_ref)
+                .unwrap()
+                .properties
+                .insert("Other".to_owned(), Variant::Ref(target_ref));
+
+            // Points to the root of the model, which isn't merged.
+            dom.insert(
+                root_ref,
+                InstanceBuilder::new("ObjectValue")
+                    .with_name("Outside")
+                    .with_property("Value", root_ref),
+            );
+
+            dom
+        }
+
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+
+        let first = model("First");
+        let first_value = first.root().children()[0];
+
+        let merged_first = dom.merge(first, root_ref);
+        let merged_second = dom.merge(model("Second"), root_ref);
+        assert_eq!(merged_first.len(), 2);
+        assert_eq!(merged_second.len(), 2);
+        assert_eq!(dom.root().children()[..2], merged_first[..]);
+        assert_eq!(dom.root().children()[2..], merged_second[..]);
+        assert_ne!(merged_first[0], first_value);
+
+        for merged in &[merged_first, merged_second] {
+            let value_ref = merged[0];
+            let value = dom.get_by_ref(value_ref).unwrap();
+            let target_ref = value.children()[0];
+            let target = dom.get_by_ref(target_ref).unwrap();
+
+            assert_eq!(value.parent(), root_ref);
+            assert_eq!(target.parent(), value_ref);
+            assert_eq!(value.properties["Value"], Variant::Ref(value_ref));
+            assert_eq!(value.properties["Other"], Variant::Ref(target_ref));
+            assert_eq!(target.properties["Value"], Variant::Ref(value_ref));
+
+            let outside = dom.get_by_ref(merged[1]).unwrap();
+            assert_eq!(outside.properties["Value"], Variant::Ref(Ref::none()));
+        }
+
+        assert_eq!(dom.descendants_of_root().count(), 6);
+        assert_eq!(dom.validate(), Ok(()));
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut dom = WeakDom::with_capacity(InstanceBuilder::new("Folder"), 100);
+        assert!(dom.instances.capacity() >= 100);
+
+        let root_ref = dom.root_ref();
+        dom.reserve(200);
+        assert!(dom.instances.capacity() >= 201);
+
+        let child = dom.insert(root_ref, InstanceBuilder::new("Part"));
+        assert_eq!(dom.root().children(), &[child]);
+    }
+
+    #[test]
+    fn get_or_create_service() {
+        let mut dom = WeakDom::new_data_model();
+        assert_eq!(dom.root().class, "DataModel");
+        assert!(dom.root().children().is_empty());
+
+        let workspace = dom.get_or_create_service("Workspace").unwrap();
+        assert_eq!(dom.get_or_create_service("Workspace"), Some(workspace));
+
+        let lighting = dom.get_or_create_service("Lighting").unwrap();
+        assert_ne!(lighting, workspace);
+        assert_eq!(dom.root().children(), &[workspace, lighting]);
+
+        let workspace = dom.get_by_ref(workspace).unwrap();
+        assert_eq!(workspace.name, "Workspace");
+        assert_eq!(workspace.parent(), dom.root_ref());
+    }
+
+    #[test]
+    fn get_or_create_service_existing() {
+        // Services that were already in the DOM are found by ClassName, even
+        // if they've been renamed.
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("DataModel")
+                .with_child(InstanceBuilder::new("Folder").with_name("Workspace"))
+                .with_child(InstanceBuilder::new("Workspace").with_name("World")),
+        );
+        let existing = dom.root().children()[1];
+
+        assert_eq!(dom.get_or_create_service("Workspace"), Some(existing));
+        assert_eq!(dom.root().children().len(), 2);
+    }
+
+    #[test]
+    fn get_or_create_service_not_data_model() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        assert_eq!(dom.get_or_create_service("Workspace"), None);
+        assert!(dom.root().children().is_empty());
+    }
 }
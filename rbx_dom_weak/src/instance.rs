@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use rbx_types::{Ref, Variant};
+use rbx_types::{FromVariant, Ref, Variant};
 
 /// Represents an instance that can be turned into a new `WeakDom`, or inserted
 /// into an existing one.
@@ -44,6 +44,9 @@ impl InstanceBuilder {
     }
 
     /// Add multiple properties to the `InstanceBuilder` at once.
+    ///
+    /// Properties are inserted in iteration order, so later entries with the
+    /// same name will replace earlier ones, just like `with_property`.
     pub fn with_properties<K, V, I>(mut self, props: I) -> Self
     where
         K: Into<String>,
@@ -58,6 +61,9 @@ impl InstanceBuilder {
     }
 
     /// Add a new child to the `InstanceBuilder`.
+    ///
+    /// The child is added after any children that were added before it, so
+    /// this can be freely mixed with `with_children`.
     pub fn with_child(mut self, child: InstanceBuilder) -> Self {
         self.children.push(child);
         self
@@ -93,6 +99,8 @@ impl Instance {
         self.referent
     }
 
+    /// The referents of this instance's children, in the order that they were
+    /// added.
     #[inline]
     pub fn children(&self) -> &[Ref] {
         &self.children
@@ -102,4 +110,350 @@ impl Instance {
     pub fn parent(&self) -> Ref {
         self.parent
     }
+
+    /// Tells whether this instance has a value for the given property.
+    #[inline]
+    pub fn has_property(&self, name: &str) -> bool {
+        self.properties.contains_key(name)
+    }
+
+    /// Returns the value of the given property if this instance has it and it
+    /// holds a value of type `T`, like a `String` or `Vector3`.
+    ///
+    /// `None` is returned if the property is missing or has a different type.
+    /// Values are not converted, so reading a `Float32` property as `f64`
+    /// returns `None`.
+    pub fn get_property_typed<T: FromVariant>(&self, name: &str) -> Option<T> {
+        self.properties.get(name).and_then(T::from_variant)
+    }
+
+    /// Tells whether this instance has the same class, name, and properties as
+    /// `other`, without comparing the referents inside of `Ref` properties.
+    ///
+    /// Two `Ref` values are considered equal as long as both are null or both
+    /// point to an instance, since referents are never preserved when a DOM is
+    /// copied or round tripped through a file. Children aren't compared. To
+    /// also check that refs point to the same places in both trees, use
+    /// [`WeakDom::structurally_eq`](struct.WeakDom.html#method.structurally_eq).
+    pub fn eq_ignoring_refs(&self, other: &Instance) -> bool {
+        self.class == other.class
+            && self.name == other.name
+            && self.properties.len() == other.properties.len()
+            && self.properties.iter().all(|(name, value)| {
+                match (value, other.properties.get(name)) {
+                    (Variant::Ref(a), Some(Variant::Ref(b))) => a.is_some() == b.is_some(),
+                    (a, Some(b)) => a == b,
+                    (_, None) => false,
+                }
+            })
+    }
+
+    /// Remove the given property from this instance, returning its value if it
+    /// had one.
+    ///
+    /// Serializers write the default value for properties that aren't set, so
+    /// this is how to make a property fall back to its default when the model
+    /// is loaded.
+    #[inline]
+    pub fn remove_property(&mut self, name: &str) -> Option<Variant> {
+        self.properties.remove(name)
+    }
+
+    /// Create an `InstanceBuilder` with the same name, class, and properties as
+    /// this instance, but with no children and a fresh referent.
+    ///
+    /// This is useful for using an existing instance as a template. Properties
+    /// are copied as-is, so `Ref` properties still point to the same instances
+    /// as before.
+    pub fn to_builder(&self) -> InstanceBuilder {
+        InstanceBuilder::new(self.class.clone())
+            .with_name(self.name.clone())
+            .with_properties(self.properties.clone())
+    }
+
+    /// Compute the changes that would turn this instance's properties into
+    /// `other`'s properties, sorted by property name.
+    ///
+    /// Only `properties` are compared; `name` and `class` are not.
+    pub fn diff_properties(&self, other: &Instance) -> Vec<PropertyChange> {
+        let mut changes = Vec::new();
+
+        for (name, old) in &self.properties {
+            match other.properties.get(name) {
+                Some(new) if new != old => changes.push(PropertyChange::Modified {
+                    name: name.clone(),
+                    old: old.clone(),
+                    new: new.clone(),
+                }),
+                Some(_) => {}
+                None => changes.push(PropertyChange::Removed {
+                    name: name.clone(),
+                    value: old.clone(),
+                }),
+            }
+        }
+
+        for (name, value) in &other.properties {
+            if !self.properties.contains_key(name) {
+                changes.push(PropertyChange::Added {
+                    name: name.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        changes.sort_by(|a, b| a.name().cmp(b.name()));
+        changes
+    }
+
+    /// Apply changes produced by [`diff_properties`](#method.diff_properties)
+    /// to this instance's properties.
+    ///
+    /// Added and modified properties are set to their new value and removed
+    /// properties are removed, regardless of the property's current value.
+    pub fn apply_property_changes(&mut self, changes: &[PropertyChange]) {
+        for change in changes {
+            match change {
+                PropertyChange::Added { name, value } => {
+                    self.properties.insert(name.clone(), value.clone());
+                }
+                PropertyChange::Modified { name, new, .. } => {
+                    self.properties.insert(name.clone(), new.clone());
+                }
+                PropertyChange::Removed { name, .. } => {
+                    self.properties.remove(name);
+                }
+            }
+        }
+    }
+}
+
+/// A change to a single property, produced by
+/// [`Instance::diff_properties`](struct.Instance.html#method.diff_properties).
+#[derive(Debug, Clone, PartialEq)]
+pub enum PropertyChange {
+    /// The property was not present before, and now has `value`.
+    Added { name: String, value: Variant },
+
+    /// The property had `value`, and is no longer present.
+    Removed { name: String, value: Variant },
+
+    /// The property changed from `old` to `new`.
+    Modified {
+        name: String,
+        old: Variant,
+        new: Variant,
+    },
+}
+
+impl PropertyChange {
+    /// The name of the property this change applies to.
+    pub fn name(&self) -> &str {
+        match self {
+            PropertyChange::Added { name, .. }
+            | PropertyChange::Removed { name, .. }
+            | PropertyChange::Modified { name, .. } => name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::{Ref, Vector3};
+
+    use crate::WeakDom;
+
+    #[test]
+    fn with_properties() {
+        let mut props = HashMap::new();
+        props.insert("Value".to_owned(), Variant::Int32(5));
+        props.insert("Other".to_owned(), Variant::Bool(true));
+
+        let builder = InstanceBuilder::new("IntValue")
+            .with_properties(props)
+            .with_property("Value", 7i32);
+
+        assert_eq!(builder.properties.len(), 2);
+        assert_eq!(builder.properties.get("Value"), Some(&Variant::Int32(7)));
+        assert_eq!(builder.properties.get("Other"), Some(&Variant::Bool(true)));
+    }
+
+    #[test]
+    fn with_properties_override() {
+        let builder = InstanceBuilder::new("IntValue")
+            .with_property("Value", 1i32)
+            .with_properties(vec![("Value", 2i32), ("Value", 3i32)]);
+
+        assert_eq!(builder.properties.get("Value"), Some(&Variant::Int32(3)));
+    }
+
+    #[test]
+    fn with_child() {
+        let builder = InstanceBuilder::new("Folder")
+            .with_child(InstanceBuilder::new("Part").with_name("First"))
+            .with_children(vec![
+                InstanceBuilder::new("Part").with_name("Second"),
+                InstanceBuilder::new("Part").with_name("Third"),
+            ])
+            .with_child(InstanceBuilder::new("Part").with_name("Fourth"));
+
+        let names: Vec<&str> = builder
+            .children
+            .iter()
+            .map(|child| child.name.as_str())
+            .collect();
+
+        assert_eq!(names, ["First", "Second", "Third", "Fourth"]);
+    }
+
+    #[test]
+    fn eq_ignoring_refs() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = dom.root_ref();
+        let target = dom.insert(root_ref, InstanceBuilder::new("Part"));
+
+        let object_value = |value: Ref| {
+            InstanceBuilder::new("ObjectValue")
+                .with_property("Value", value)
+                .with_property("Archivable", true)
+        };
+
+        let first = dom.insert(root_ref, object_value(target));
+        let second = dom.insert(root_ref, object_value(root_ref));
+        let null = dom.insert(root_ref, object_value(Ref::none()));
+        let renamed = dom.insert(root_ref, object_value(target).with_name("Renamed"));
+        let extra = dom.insert(root_ref, object_value(target).with_property("Extra", false));
+
+        let get = |referent| dom.get_by_ref(referent).unwrap();
+
+        assert!(get(first).eq_ignoring_refs(get(second)));
+        assert!(get(first).eq_ignoring_refs(get(first)));
+        assert!(!get(first).eq_ignoring_refs(get(null)));
+        assert!(!get(first).eq_ignoring_refs(get(renamed)));
+        assert!(!get(first).eq_ignoring_refs(get(extra)));
+        assert!(!get(extra).eq_ignoring_refs(get(first)));
+        assert!(!get(first).eq_ignoring_refs(get(target)));
+    }
+
+    #[test]
+    fn remove_property() {
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("StringValue")
+                .with_property("Value", "Hello")
+                .with_property("Archivable", true),
+        );
+        let instance = dom.root_mut();
+
+        assert!(instance.has_property("Value"));
+        assert_eq!(
+            instance.remove_property("Value"),
+            Some(Variant::String("Hello".to_owned()))
+        );
+        assert!(!instance.has_property("Value"));
+        assert_eq!(instance.remove_property("Value"), None);
+        assert!(instance.has_property("Archivable"));
+    }
+
+    #[test]
+    fn diff_and_apply_properties() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("Folder"));
+        let before = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Part")
+                .with_property("Anchored", true)
+                .with_property("Transparency", 0.5f32)
+                .with_property("Locked", false),
+        );
+        let after = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("Part")
+                .with_property("Anchored", true)
+                .with_property("Transparency", 1.0f32)
+                .with_property("CastShadow", false),
+        );
+
+        let changes = dom
+            .get_by_ref(before)
+            .unwrap()
+            .diff_properties(dom.get_by_ref(after).unwrap());
+
+        assert_eq!(
+            changes,
+            vec![
+                PropertyChange::Added {
+                    name: "CastShadow".to_owned(),
+                    value: Variant::Bool(false),
+                },
+                PropertyChange::Removed {
+                    name: "Locked".to_owned(),
+                    value: Variant::Bool(false),
+                },
+                PropertyChange::Modified {
+                    name: "Transparency".to_owned(),
+                    old: Variant::Float32(0.5),
+                    new: Variant::Float32(1.0),
+                },
+            ]
+        );
+
+        dom.get_by_ref_mut(before)
+            .unwrap()
+            .apply_property_changes(&changes);
+
+        let before = dom.get_by_ref(before).unwrap();
+        let after = dom.get_by_ref(after).unwrap();
+        assert_eq!(before.properties, after.properties);
+        assert!(before.diff_properties(after).is_empty());
+    }
+
+    #[test]
+    fn to_builder() {
+        let mut dom = WeakDom::new(InstanceBuilder::new("DataModel"));
+        let original_ref = dom.insert(
+            dom.root_ref(),
+            InstanceBuilder::new("IntValue")
+                .with_name("Template")
+                .with_property("Value", 5)
+                .with_child(InstanceBuilder::new("Folder")),
+        );
+
+        let builder = dom.get_by_ref(original_ref).unwrap().to_builder();
+        let copy_ref = dom.insert(dom.root_ref(), builder);
+        assert_ne!(copy_ref, original_ref);
+
+        let original = dom.get_by_ref(original_ref).unwrap();
+        let copy = dom.get_by_ref(copy_ref).unwrap();
+        assert_eq!(copy.name, "Template");
+        assert_eq!(copy.class, "IntValue");
+        assert_eq!(copy.properties, original.properties);
+        assert!(copy.children().is_empty());
+    }
+
+    #[test]
+    fn get_property_typed() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Part")
+                .with_property("Name", "Brick")
+                .with_property("Transparency", 0.5f32)
+                .with_property("Size", Vector3::new(4.0, 1.0, 2.0)),
+        );
+        let part = dom.root();
+
+        assert_eq!(
+            part.get_property_typed::<String>("Name"),
+            Some("Brick".to_owned())
+        );
+        assert_eq!(part.get_property_typed::<f32>("Transparency"), Some(0.5));
+        assert_eq!(
+            part.get_property_typed::<Vector3>("Size"),
+            Some(Vector3::new(4.0, 1.0, 2.0))
+        );
+
+        // Values aren't converted between types.
+        assert_eq!(part.get_property_typed::<f64>("Transparency"), None);
+        assert_eq!(part.get_property_typed::<bool>("Name"), None);
+        assert_eq!(part.get_property_typed::<bool>("Anchored"), None);
+    }
 }
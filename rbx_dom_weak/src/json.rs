@@ -0,0 +1,454 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+use rbx_types::{
+    CFrame, Color3, ColorSequence, NumberSequence, PhysicalProperties, Ref, Variant, Vector2,
+    Vector3,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{InstanceBuilder, WeakDom};
+
+/// Serializes the given `WeakDom` to a human-readable JSON document.
+///
+/// Unlike `DomViewer`, this representation is lossless: referents are kept
+/// as-is and every property keeps its type, so the DOM can be read back with
+/// [`from_json`](fn.from_json.html).
+///
+/// JSON can't represent NaN or infinite numbers, so this returns an error if
+/// any property contains one, including inside of types like `Vector3`.
+pub fn to_json(dom: &WeakDom) -> Result<String, JsonError> {
+    let document = JsonDom {
+        root: JsonInstance::from_dom(dom, dom.root_ref())?,
+        metadata: dom.metadata().clone(),
+    };
+
+    Ok(serde_json::to_string_pretty(&document).map_err(JsonErrorKind::Json)?)
+}
+
+/// Deserializes a `WeakDom` from a JSON document created by
+/// [`to_json`](fn.to_json.html).
+pub fn from_json(source: &str) -> Result<WeakDom, JsonError> {
+    let document: JsonDom = serde_json::from_str(source).map_err(JsonErrorKind::Json)?;
+
+    let mut seen = HashSet::new();
+    let builder = document.root.into_builder(&mut seen)?;
+
+    let mut dom = WeakDom::new(builder);
+    *dom.metadata_mut() = document.metadata;
+
+    Ok(dom)
+}
+
+/// An error that can occur when writing a `WeakDom` to JSON or reading one
+/// from it.
+#[derive(Debug)]
+pub struct JsonError {
+    kind: JsonErrorKind,
+}
+
+#[derive(Debug)]
+enum JsonErrorKind {
+    Json(serde_json::Error),
+    InvalidReferent,
+    DuplicateReferent(Ref),
+    NonFiniteFloat {
+        class_name: String,
+        property_name: String,
+    },
+}
+
+impl From<JsonErrorKind> for JsonError {
+    fn from(kind: JsonErrorKind) -> Self {
+        JsonError { kind }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            JsonErrorKind::Json(err) => write!(formatter, "{}", err),
+            JsonErrorKind::InvalidReferent => {
+                write!(formatter, "Instances must have a non-null referent")
+            }
+            JsonErrorKind::DuplicateReferent(referent) => {
+                write!(
+                    formatter,
+                    "Referent {} is used by more than one instance",
+                    referent
+                )
+            }
+            JsonErrorKind::NonFiniteFloat {
+                class_name,
+                property_name,
+            } => write!(
+                formatter,
+                "Property {}.{} contains a NaN or infinite number, which JSON can't represent",
+                class_name, property_name
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            JsonErrorKind::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JsonDom {
+    root: JsonInstance,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct JsonInstance {
+    referent: Ref,
+    name: String,
+    class: String,
+    properties: BTreeMap<String, Variant>,
+    children: Vec<JsonInstance>,
+}
+
+impl JsonInstance {
+    fn from_dom(dom: &WeakDom, referent: Ref) -> Result<Self, JsonError> {
+        let instance = dom.get_by_ref(referent).unwrap();
+
+        let mut properties = BTreeMap::new();
+        for (key, value) in &instance.properties {
+            if !is_finite(value) {
+                return Err(JsonErrorKind::NonFiniteFloat {
+                    class_name: instance.class.clone(),
+                    property_name: key.clone(),
+                }
+                .into());
+            }
+
+            properties.insert(key.clone(), value.clone());
+        }
+
+        let children = instance
+            .children()
+            .iter()
+            .map(|&child| JsonInstance::from_dom(dom, child))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(JsonInstance {
+            referent,
+            name: instance.name.clone(),
+            class: instance.class.clone(),
+            properties,
+            children,
+        })
+    }
+
+    fn into_builder(self, seen: &mut HashSet<Ref>) -> Result<InstanceBuilder, JsonError> {
+        if self.referent.is_none() {
+            return Err(JsonErrorKind::InvalidReferent.into());
+        }
+
+        if !seen.insert(self.referent) {
+            return Err(JsonErrorKind::DuplicateReferent(self.referent).into());
+        }
+
+        let children = self
+            .children
+            .into_iter()
+            .map(|child| child.into_builder(seen))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut builder = InstanceBuilder::new(self.class)
+            .with_name(self.name)
+            .with_properties(self.properties)
+            .with_children(children);
+        builder.referent = self.referent;
+
+        Ok(builder)
+    }
+}
+
+/// Returns whether every number inside of `value` is finite, which is what
+/// JSON requires. serde_json would otherwise write NaN and infinite numbers as
+/// `null`, which can't be read back.
+fn is_finite(value: &Variant) -> bool {
+    fn vector2(value: &Vector2) -> bool {
+        value.x.is_finite() && value.y.is_finite()
+    }
+
+    fn vector3(value: &Vector3) -> bool {
+        value.x.is_finite() && value.y.is_finite() && value.z.is_finite()
+    }
+
+    fn color3(value: &Color3) -> bool {
+        value.r.is_finite() && value.g.is_finite() && value.b.is_finite()
+    }
+
+    fn cframe(value: &CFrame) -> bool {
+        let orientation = &value.orientation;
+
+        vector3(&value.position)
+            && vector3(&orientation.x)
+            && vector3(&orientation.y)
+            && vector3(&orientation.z)
+    }
+
+    fn number_sequence(value: &NumberSequence) -> bool {
+        value.keypoints.iter().all(|keypoint| {
+            keypoint.time.is_finite() && keypoint.value.is_finite() && keypoint.envelope.is_finite()
+        })
+    }
+
+    fn color_sequence(value: &ColorSequence) -> bool {
+        value
+            .keypoints
+            .iter()
+            .all(|keypoint| keypoint.time.is_finite() && color3(&keypoint.color))
+    }
+
+    match value {
+        Variant::Float32(value) => value.is_finite(),
+        Variant::Float64(value) => value.is_finite(),
+        Variant::Vector2(value) => vector2(value),
+        Variant::Vector3(value) => vector3(value),
+        Variant::Color3(value) => color3(value),
+        Variant::CFrame(value) => cframe(value),
+        Variant::OptionalCFrame(value) => value.as_ref().map_or(true, cframe),
+        Variant::UDim(value) => value.scale.is_finite(),
+        Variant::UDim2(value) => value.x.scale.is_finite() && value.y.scale.is_finite(),
+        Variant::Ray(value) => vector3(&value.origin) && vector3(&value.direction),
+        Variant::Rect(value) => vector2(&value.min) && vector2(&value.max),
+        Variant::Region3(value) => vector3(&value.min) && vector3(&value.max),
+        Variant::NumberRange(value) => value.min.is_finite() && value.max.is_finite(),
+        Variant::NumberSequence(value) => number_sequence(value),
+        Variant::ColorSequence(value) => color_sequence(value),
+        Variant::PhysicalProperties(PhysicalProperties::Custom(value)) => {
+            value.density.is_finite()
+                && value.friction.is_finite()
+                && value.elasticity.is_finite()
+                && value.friction_weight.is_finite()
+                && value.elasticity_weight.is_finite()
+        }
+        Variant::Attributes(value) => value.iter().all(|(_, value)| is_finite(value)),
+
+        // Every other type holds only integers, strings, and other values
+        // that JSON can always represent.
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let value = InstanceBuilder::new("ObjectValue").with_name("Value");
+        let value_ref = value.referent;
+        let value = value
+            .with_property("Value", value_ref)
+            .with_property("Enabled", true)
+            .with_property("Count", 5i32);
+
+        let mut dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_name("Root")
+                .with_child(value)
+                .with_child(InstanceBuilder::new("Folder").with_name("Second")),
+        );
+        dom.metadata_mut()
+            .insert("ExplicitAutoJoints".to_owned(), "true".to_owned());
+
+        let encoded = to_json(&dom).unwrap();
+        let decoded = from_json(&encoded).unwrap();
+
+        assert_eq!(decoded.root_ref(), dom.root_ref());
+        assert_eq!(decoded.root().name, "Root");
+        assert_eq!(decoded.root().children(), dom.root().children());
+        assert_eq!(decoded.metadata(), dom.metadata());
+
+        let decoded_value = decoded.get_by_ref(value_ref).unwrap();
+        assert_eq!(decoded_value.class, "ObjectValue");
+        assert_eq!(
+            decoded_value.properties,
+            dom.get_by_ref(value_ref).unwrap().properties
+        );
+        assert_eq!(
+            decoded_value.properties.get("Value"),
+            Some(&Variant::Ref(value_ref))
+        );
+
+        // Encoding the decoded DOM should produce exactly the same document.
+        assert_eq!(to_json(&decoded).unwrap(), encoded);
+    }
+
+    #[test]
+    fn round_trip_every_type() {
+        use std::convert::TryFrom;
+
+        use rbx_types::*;
+
+        let vector3 = Vector3::new(1.0, -2.5, 3.0);
+        let cframe = CFrame::new(vector3, Matrix3::identity());
+
+        let mut attributes = Attributes::new();
+        attributes.insert("Health", 100.0f64);
+        attributes.insert("Position", vector3);
+
+        let values: Vec<Variant> = vec![
+            Axes::all().into(),
+            BinaryString::from(vec![0, 1, 2, 255]).into(),
+            true.into(),
+            BrickColor::BrightRed.into(),
+            cframe.into(),
+            Color3::new(1.0, 0.5, 0.25).into(),
+            Color3uint8::new(255, 128, 0).into(),
+            ColorSequence {
+                keypoints: vec![
+                    ColorSequenceKeypoint::new(0.0, Color3::new(1.0, 0.0, 0.0)),
+                    ColorSequenceKeypoint::new(1.0, Color3::new(0.0, 0.0, 1.0)),
+                ],
+            }
+            .into(),
+            Content::from("rbxassetid://12345").into(),
+            EnumValue::from_u32(256).into(),
+            EnumItem::new("Material", 256).into(),
+            Faces::all().into(),
+            0.1f32.into(),
+            0.1f64.into(),
+            std::i32::MIN.into(),
+            std::i64::MAX.into(),
+            NumberRange::new(-1.0, 1.0).into(),
+            NumberSequence {
+                keypoints: vec![
+                    NumberSequenceKeypoint::new(0.0, 1.0, 0.0),
+                    NumberSequenceKeypoint::new(1.0, 0.0, 0.5),
+                ],
+            }
+            .into(),
+            PhysicalProperties::Default.into(),
+            PhysicalProperties::Custom(CustomPhysicalProperties {
+                density: 0.7,
+                friction: 0.3,
+                elasticity: 0.5,
+                friction_weight: 1.0,
+                elasticity_weight: 1.0,
+            })
+            .into(),
+            Ray::new(vector3, Vector3::new(0.0, -1.0, 0.0)).into(),
+            Rect::new(Vector2::new(0.0, 0.0), Vector2::new(10.0, 20.0)).into(),
+            Ref::none().into(),
+            Region3::new(Vector3::new(0.0, 0.0, 0.0), vector3).into(),
+            Region3int16::new(Vector3int16::new(-1, -2, -3), Vector3int16::new(1, 2, 3)).into(),
+            SharedString::new(b"shared".to_vec()).into(),
+            "Hello, world!".into(),
+            UDim::new(0.5, -10).into(),
+            UDim2::new(UDim::new(1.0, 0), UDim::new(0.0, 32)).into(),
+            Vector2::new(1.5, -2.5).into(),
+            Vector2int16::new(-5, 5).into(),
+            vector3.into(),
+            Vector3int16::new(1, 2, 3).into(),
+            Variant::OptionalCFrame(Some(cframe)),
+            Variant::OptionalCFrame(None),
+            Tags::try_from(vec!["Enemy".to_owned(), "Boss".to_owned()])
+                .unwrap()
+                .into(),
+            Font::new(
+                "rbxasset://fonts/families/Arial.json",
+                FontWeight::Bold,
+                FontStyle::Italic,
+            )
+            .into(),
+            UniqueId::new(0x0048_15fc, 0x02e9_c68d, 0x44b1_88da_ce63_2b47).into(),
+            SecurityCapabilities::from_bits(0b1011).into(),
+            attributes.into(),
+            UnknownValue::new(0x42, BinaryString::from(vec![1, 2, 3])).into(),
+        ];
+
+        let mut builder = InstanceBuilder::new("Folder");
+        for (i, value) in values.iter().enumerate() {
+            builder = builder.with_property(format!("{} {:?}", i, value.ty()), value.clone());
+        }
+
+        let dom = WeakDom::new(builder);
+        let encoded = to_json(&dom).unwrap();
+        let decoded = from_json(&encoded).unwrap();
+
+        assert_eq!(decoded.root().properties, dom.root().properties);
+    }
+
+    #[test]
+    fn non_finite_floats() {
+        let encode = |value: Variant| {
+            let dom = WeakDom::new(InstanceBuilder::new("Part").with_property("Value", value));
+            to_json(&dom)
+        };
+
+        let error = encode(Variant::Float32(std::f32::NAN)).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Property Part.Value contains a NaN or infinite number, which JSON can't represent"
+        );
+
+        assert!(encode(Variant::Float64(std::f64::INFINITY)).is_err());
+        assert!(encode(Vector3::new(0.0, std::f32::NEG_INFINITY, 0.0).into()).is_err());
+        assert!(encode(
+            NumberSequence {
+                keypoints: vec![rbx_types::NumberSequenceKeypoint::new(
+                    0.0,
+                    std::f32::NAN,
+                    0.0
+                )],
+            }
+            .into()
+        )
+        .is_err());
+
+        let mut attributes = rbx_types::Attributes::new();
+        attributes.insert("Speed", std::f64::NAN);
+        assert!(encode(attributes.into()).is_err());
+
+        assert!(encode(Variant::Float32(std::f32::MAX)).is_ok());
+    }
+
+    #[test]
+    fn duplicate_referent() {
+        let dom =
+            WeakDom::new(InstanceBuilder::new("Folder").with_child(InstanceBuilder::new("Folder")));
+
+        let root_ref = dom.root_ref().to_string();
+        let child_ref = dom.root().children()[0].to_string();
+        let encoded = to_json(&dom).unwrap().replace(&child_ref, &root_ref);
+
+        assert!(from_json(&encoded).is_err());
+    }
+
+    #[test]
+    fn seeded_refs_are_reproducible() {
+        let build = || {
+            Ref::with_seed(1234, || {
+                let target = InstanceBuilder::new("Folder").with_name("Target");
+                let target_ref = target.referent;
+
+                WeakDom::new(
+                    InstanceBuilder::new("Folder")
+                        .with_child(target)
+                        .with_child(
+                            InstanceBuilder::new("ObjectValue").with_property("Value", target_ref),
+                        ),
+                )
+            })
+        };
+
+        assert_eq!(to_json(&build()).unwrap(), to_json(&build()).unwrap());
+    }
+}
@@ -48,12 +48,14 @@
 
 mod dom;
 mod instance;
+mod json;
 mod viewer;
 
 pub use rbx_types as types;
 
 pub use crate::{
-    dom::WeakDom,
-    instance::{Instance, InstanceBuilder},
+    dom::{UnknownChunk, ValidationError, WeakDom},
+    instance::{Instance, InstanceBuilder, PropertyChange},
+    json::{from_json, to_json, JsonError},
     viewer::{DomViewer, ViewedInstance},
 };
@@ -15,6 +15,8 @@ use serde::{Deserialize, Serialize};
 pub struct DomViewer {
     referent_map: HashMap<Ref, String>,
     next_referent: usize,
+    show_real_referents: bool,
+    max_depth: Option<usize>,
 }
 
 impl DomViewer {
@@ -23,15 +25,38 @@ impl DomViewer {
         Self {
             referent_map: HashMap::new(),
             next_referent: 0,
+            show_real_referents: false,
+            max_depth: None,
         }
     }
 
+    /// Sets whether this `DomViewer` should show the real referents of
+    /// instances instead of redacting them to stable placeholder IDs.
+    ///
+    /// This is useful when debugging, but makes output nondeterministic, so it
+    /// should not be used in snapshot tests. Defaults to `false`.
+    pub fn show_real_referents(mut self, show: bool) -> Self {
+        self.show_real_referents = show;
+        self
+    }
+
+    /// Sets how many levels of descendants this `DomViewer` should show below
+    /// the instances being viewed. The children of instances at `max_depth`
+    /// are replaced with a note saying how many were left out, so
+    /// `max_depth(0)` shows only the viewed instances themselves.
+    ///
+    /// By default, every descendant is shown.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
     /// View the given `WeakDom`, creating a `ViewedInstance` object that can be
     /// used in a snapshot test.
     pub fn view(&mut self, dom: &WeakDom) -> ViewedInstance {
         let root_referent = dom.root_ref();
         self.populate_referent_map(dom, root_referent);
-        self.view_instance(dom, root_referent)
+        self.view_instance(dom, root_referent, 0)
     }
 
     /// View the children of the root instance of the given `WeakDom`, returning
@@ -46,14 +71,19 @@ impl DomViewer {
 
         children
             .iter()
-            .map(|&referent| self.view_instance(dom, referent))
+            .map(|&referent| self.view_instance(dom, referent, 0))
             .collect()
     }
 
     fn populate_referent_map(&mut self, dom: &WeakDom, referent: Ref) {
-        self.referent_map
-            .insert(referent, format!("referent-{}", self.next_referent));
-        self.next_referent += 1;
+        let referent_str = if self.show_real_referents {
+            referent.to_string()
+        } else {
+            let redacted = format!("referent-{}", self.next_referent);
+            self.next_referent += 1;
+            redacted
+        };
+        self.referent_map.insert(referent, referent_str);
 
         let instance = dom.get_by_ref(referent).unwrap();
         for referent in instance.children() {
@@ -61,15 +91,27 @@ impl DomViewer {
         }
     }
 
-    fn view_instance(&self, dom: &WeakDom, referent: Ref) -> ViewedInstance {
+    fn view_instance(&self, dom: &WeakDom, referent: Ref, depth: usize) -> ViewedInstance {
         let instance = dom.get_by_ref(referent).unwrap();
 
-        let children = instance
-            .children()
-            .iter()
-            .copied()
-            .map(|referent| self.view_instance(dom, referent))
-            .collect();
+        let truncated = match self.max_depth {
+            Some(max_depth) => depth >= max_depth,
+            None => false,
+        };
+
+        let (children, elided_children) = if truncated && !instance.children().is_empty() {
+            let note = format!("... ({} children elided)", instance.children().len());
+            (Vec::new(), Some(note))
+        } else {
+            let children = instance
+                .children()
+                .iter()
+                .copied()
+                .map(|referent| self.view_instance(dom, referent, depth + 1))
+                .collect();
+
+            (children, None)
+        };
 
         let properties = instance
             .properties
@@ -77,6 +119,9 @@ impl DomViewer {
             .map(|(key, value)| {
                 let key = key.clone();
                 let new_value = match value {
+                    Variant::Ref(ref_referent) if self.show_real_referents => {
+                        ViewedValue::Ref(ref_referent.to_string())
+                    }
                     Variant::Ref(ref_referent) => {
                         let referent_str = self
                             .referent_map
@@ -99,6 +144,7 @@ impl DomViewer {
             class: instance.class.clone(),
             properties,
             children,
+            elided_children,
         }
     }
 }
@@ -112,6 +158,9 @@ pub struct ViewedInstance {
     class: String,
     properties: BTreeMap<String, ViewedValue>,
     children: Vec<ViewedInstance>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    elided_children: Option<String>,
 }
 
 /// Wrapper around Variant with refs replaced to be redacted, stable versions of
@@ -160,4 +209,54 @@ mod test {
 
         insta::assert_yaml_snapshot!(DomViewer::new().view(&dom));
     }
+
+    #[test]
+    fn show_real_referents() {
+        let child = InstanceBuilder::new("ObjectValue").with_name("Child");
+        let child_ref = child.referent;
+        let child = child.with_property("Value", child_ref);
+
+        let dom = WeakDom::new(InstanceBuilder::new("Folder").with_child(child));
+        let root_ref = dom.root_ref();
+
+        let viewed = DomViewer::new().show_real_referents(true).view(&dom);
+        assert_eq!(viewed.referent, root_ref.to_string());
+
+        let viewed_child = &viewed.children[0];
+        assert_eq!(viewed_child.referent, child_ref.to_string());
+
+        match &viewed_child.properties["Value"] {
+            ViewedValue::Ref(value) => assert_eq!(value, &child_ref.to_string()),
+            other => panic!("expected a Ref value, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn max_depth() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder").with_name("Root").with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("Child")
+                    .with_children((0..3).map(|i| {
+                        InstanceBuilder::new("Folder")
+                            .with_name(format!("Grandchild {}", i))
+                            .with_child(InstanceBuilder::new("Folder"))
+                    })),
+            ),
+        );
+
+        insta::assert_yaml_snapshot!(DomViewer::new().max_depth(1).view(&dom));
+
+        let viewed = DomViewer::new().max_depth(0).view(&dom);
+        assert!(viewed.children.is_empty());
+        assert_eq!(
+            viewed.elided_children.as_deref(),
+            Some("... (1 children elided)")
+        );
+
+        // Instances without children don't get a note, even at the limit.
+        let viewed = DomViewer::new().max_depth(3).view(&dom);
+        let great_grandchild = &viewed.children[0].children[0].children[0];
+        assert!(great_grandchild.elided_children.is_none());
+    }
 }
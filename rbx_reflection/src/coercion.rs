@@ -0,0 +1,205 @@
+use std::convert::TryFrom;
+
+use rbx_types::{BrickColor, Content, EnumValue, Variant, VariantType};
+
+/// Attempts to convert `value` into a `Variant` of type `target`.
+///
+/// Values that already have the target type are returned as-is. Otherwise,
+/// these conversions are supported:
+///
+/// - Between any of `Int32`, `Int64`, `Float32`, and `Float64`. Floats only
+///   convert to integers if they have no fractional part and fit in the target
+///   type. Converting to `Float32` may lose precision.
+/// - Between `Color3` and `Color3uint8`. Converting to `Color3uint8` clamps
+///   each channel and rounds it to the nearest integer, with halves rounded
+///   to even.
+/// - From `BrickColor` to `Color3` or `Color3uint8`.
+/// - From `Int32` to `BrickColor` or `EnumValue`, and from `EnumValue` to
+///   `Int32`.
+/// - From `EnumItem` to `EnumValue`, which drops the name of the enum.
+/// - Between `String` and `Content`.
+/// - From `CFrame` to `OptionalCFrame`.
+///
+/// Returns `None` if there is no sensible conversion.
+pub fn coerce_variant(value: Variant, target: VariantType) -> Option<Variant> {
+    if value.ty() == target {
+        return Some(value);
+    }
+
+    Some(match (value, target) {
+        (Variant::Int32(value), VariantType::Int64) => Variant::Int64(value.into()),
+        (Variant::Int32(value), VariantType::Float32) => Variant::Float32(value as f32),
+        (Variant::Int32(value), VariantType::Float64) => Variant::Float64(value.into()),
+        (Variant::Int64(value), VariantType::Int32) => Variant::Int32(i32::try_from(value).ok()?),
+        (Variant::Int64(value), VariantType::Float32) => Variant::Float32(value as f32),
+        (Variant::Int64(value), VariantType::Float64) => Variant::Float64(value as f64),
+        (Variant::Float32(value), VariantType::Float64) => Variant::Float64(value.into()),
+        (Variant::Float32(value), VariantType::Int32) => {
+            Variant::Int32(float_to_int(value.into(), 32)? as i32)
+        }
+        (Variant::Float32(value), VariantType::Int64) => {
+            Variant::Int64(float_to_int(value.into(), 64)? as i64)
+        }
+        (Variant::Float64(value), VariantType::Float32) => Variant::Float32(value as f32),
+        (Variant::Float64(value), VariantType::Int32) => {
+            Variant::Int32(float_to_int(value, 32)? as i32)
+        }
+        (Variant::Float64(value), VariantType::Int64) => {
+            Variant::Int64(float_to_int(value, 64)? as i64)
+        }
+
+        (Variant::Color3uint8(value), VariantType::Color3) => Variant::Color3(value.into()),
+        (Variant::Color3(value), VariantType::Color3uint8) => Variant::Color3uint8(value.into()),
+        (Variant::BrickColor(value), VariantType::Color3) => Variant::Color3(value.to_color3()),
+        (Variant::BrickColor(value), VariantType::Color3uint8) => {
+            Variant::Color3uint8(value.to_color3uint8())
+        }
+
+        (Variant::Int32(value), VariantType::BrickColor) => Variant::BrickColor(
+            u16::try_from(value)
+                .ok()
+                .and_then(BrickColor::from_number)?,
+        ),
+        (Variant::Int32(value), VariantType::EnumValue) => {
+            Variant::EnumValue(EnumValue::from_u32(u32::try_from(value).ok()?))
+        }
+        (Variant::EnumItem(item), VariantType::EnumValue) => Variant::EnumValue(item.into()),
+        (Variant::EnumValue(value), VariantType::Int32) => {
+            Variant::Int32(i32::try_from(value.to_u32()).ok()?)
+        }
+
+        (Variant::String(value), VariantType::Content) => Variant::Content(Content::from(value)),
+        (Variant::Content(value), VariantType::String) => Variant::String(value.into_string()),
+
+        (Variant::CFrame(value), VariantType::OptionalCFrame) => {
+            Variant::OptionalCFrame(Some(value))
+        }
+
+        _ => return None,
+    })
+}
+
+/// Checks that `value` is a whole number that fits in a signed integer with
+/// the given number of bits.
+///
+/// The bounds are powers of two, which floats can represent exactly. Comparing
+/// against the integer type's `MAX` instead would round it up to the same
+/// power of two, letting values one past the end through.
+fn float_to_int(value: f64, bits: i32) -> Option<f64> {
+    let bound = 2f64.powi(bits - 1);
+
+    if value.fract() == 0.0 && value >= -bound && value < bound {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_types::{Color3, Color3uint8, EnumItem};
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            coerce_variant(Variant::Int32(5), VariantType::Float32),
+            Some(Variant::Float32(5.0))
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float64(5.0), VariantType::Int32),
+            Some(Variant::Int32(5))
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float64(5.5), VariantType::Int32),
+            None
+        );
+        assert_eq!(
+            coerce_variant(Variant::Int64(std::i64::MAX), VariantType::Int32),
+            None
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float32(std::f32::NAN), VariantType::Int64),
+            None
+        );
+    }
+
+    #[test]
+    fn float_bounds() {
+        let max_i64 = 2f64.powi(63);
+
+        assert_eq!(
+            coerce_variant(Variant::Float64(max_i64), VariantType::Int64),
+            None
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float64(-max_i64), VariantType::Int64),
+            Some(Variant::Int64(std::i64::MIN))
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float32(max_i64 as f32), VariantType::Int64),
+            None
+        );
+
+        let max_i32 = 2f64.powi(31);
+
+        assert_eq!(
+            coerce_variant(Variant::Float64(max_i32), VariantType::Int32),
+            None
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float64(max_i32 - 1.0), VariantType::Int32),
+            Some(Variant::Int32(std::i32::MAX))
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float64(-max_i32), VariantType::Int32),
+            Some(Variant::Int32(std::i32::MIN))
+        );
+        assert_eq!(
+            coerce_variant(Variant::Float64(-max_i32 - 1.0), VariantType::Int32),
+            None
+        );
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(
+            coerce_variant(
+                Variant::Color3uint8(Color3uint8::new(255, 0, 51)),
+                VariantType::Color3
+            ),
+            Some(Variant::Color3(Color3::new(1.0, 0.0, 0.2)))
+        );
+        assert_eq!(
+            coerce_variant(
+                Variant::Color3(Color3::new(1.5, -1.0, 0.2)),
+                VariantType::Color3uint8
+            ),
+            Some(Variant::Color3uint8(Color3uint8::new(255, 0, 51)))
+        );
+        assert_eq!(
+            coerce_variant(Variant::Int32(21), VariantType::BrickColor),
+            Some(Variant::BrickColor(BrickColor::BrightRed))
+        );
+        assert_eq!(
+            coerce_variant(
+                Variant::EnumItem(EnumItem::new("Material", 256)),
+                VariantType::EnumValue
+            ),
+            Some(Variant::EnumValue(EnumValue::from_u32(256)))
+        );
+    }
+
+    #[test]
+    fn unsupported() {
+        assert_eq!(
+            coerce_variant(Variant::Bool(true), VariantType::String),
+            None
+        );
+        assert_eq!(
+            coerce_variant(Variant::Int32(-1), VariantType::EnumValue),
+            None
+        );
+    }
+}
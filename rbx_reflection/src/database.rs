@@ -6,7 +6,7 @@ use std::{
 use rbx_types::{Variant, VariantType};
 use serde::{Deserialize, Serialize};
 
-use crate::{ClassTag, PropertyTag};
+use crate::{coerce_variant, ClassTag, PropertyTag};
 
 /// Contains information extracted from Roblox to describe all known Instances
 /// and enums.
@@ -20,6 +20,10 @@ pub struct ReflectionDatabase<'a> {
     /// All of the the known classes in the database.
     #[serde(serialize_with = "crate::serde_util::ordered_map")]
     pub classes: HashMap<Cow<'a, str>, ClassDescriptor<'a>>,
+
+    /// All of the known enums in the database.
+    #[serde(default, serialize_with = "crate::serde_util::ordered_map")]
+    pub enums: HashMap<Cow<'a, str>, EnumDescriptor<'a>>,
 }
 
 impl<'a> ReflectionDatabase<'a> {
@@ -28,6 +32,192 @@ impl<'a> ReflectionDatabase<'a> {
         Self {
             version: [0, 0, 0, 0],
             classes: HashMap::new(),
+            enums: HashMap::new(),
+        }
+    }
+
+    /// Finds the default value of the given property on the given class, if
+    /// one is known. Superclasses of `class` are searched if `class` doesn't
+    /// have a default value for the property itself.
+    ///
+    /// Returns `None` if no default value is known, which is the case for
+    /// properties that Roblox doesn't serialize and for classes that can't be
+    /// created.
+    pub fn find_default_property(
+        &'a self,
+        class: &'a ClassDescriptor<'a>,
+        property_name: &str,
+    ) -> Option<&'a Variant> {
+        let mut current_class = Some(class);
+
+        while let Some(class) = current_class {
+            if let Some(value) = class.default_properties.get(property_name) {
+                return Some(value);
+            }
+
+            current_class = class
+                .superclass
+                .as_ref()
+                .and_then(|name| self.classes.get(name.as_ref()));
+        }
+
+        None
+    }
+
+    /// Finds the descriptor for the given property on the given class or one
+    /// of its superclasses.
+    pub fn find_property(
+        &'a self,
+        class: &'a ClassDescriptor<'a>,
+        property_name: &str,
+    ) -> Option<&'a PropertyDescriptor<'a>> {
+        let mut current_class = Some(class);
+
+        while let Some(class) = current_class {
+            if let Some(property) = class.properties.get(property_name) {
+                return Some(property);
+            }
+
+            current_class = class
+                .superclass
+                .as_ref()
+                .and_then(|name| self.classes.get(name.as_ref()));
+        }
+
+        None
+    }
+
+    /// Finds the class with the given name, ignoring differences in ASCII case
+    /// if no class has exactly that name. This is useful for correcting class
+    /// names in hand-written files, like `stringvalue`.
+    ///
+    /// If several classes differ only by case, the one whose name sorts first
+    /// is returned.
+    pub fn find_class_ignore_case(&self, class_name: &str) -> Option<&ClassDescriptor<'a>> {
+        self.classes.get(class_name).or_else(|| {
+            self.classes
+                .values()
+                .filter(|class| class.name.eq_ignore_ascii_case(class_name))
+                .min_by(|a, b| a.name.cmp(&b.name))
+        })
+    }
+
+    /// Finds the descriptor for the given property on the given class or one
+    /// of its superclasses, like [`find_property`](#method.find_property), but
+    /// ignoring differences in ASCII case if no property has exactly that
+    /// name.
+    ///
+    /// Case-insensitive matches on `class` are preferred over ones on its
+    /// superclasses. If several properties of one class differ only by case,
+    /// the one whose name sorts first is returned.
+    pub fn find_property_ignore_case(
+        &'a self,
+        class: &'a ClassDescriptor<'a>,
+        property_name: &str,
+    ) -> Option<&'a PropertyDescriptor<'a>> {
+        if let Some(property) = self.find_property(class, property_name) {
+            return Some(property);
+        }
+
+        let mut current_class = Some(class);
+
+        while let Some(class) = current_class {
+            let property = class
+                .properties
+                .values()
+                .filter(|property| property.name.eq_ignore_ascii_case(property_name))
+                .min_by(|a, b| a.name.cmp(&b.name));
+
+            if property.is_some() {
+                return property;
+            }
+
+            current_class = class
+                .superclass
+                .as_ref()
+                .and_then(|name| self.classes.get(name.as_ref()));
+        }
+
+        None
+    }
+
+    /// Attempts to convert `value` into the type that the given property is
+    /// declared to have, using the conversions described by
+    /// [`coerce_variant`](fn.coerce_variant.html).
+    ///
+    /// Returns `None` if the property isn't known or if there is no sensible
+    /// conversion.
+    pub fn coerce_property(
+        &'a self,
+        class: &'a ClassDescriptor<'a>,
+        property_name: &str,
+        value: Variant,
+    ) -> Option<Variant> {
+        let target = match &self.find_property(class, property_name)?.data_type {
+            DataType::Value(ty) => *ty,
+            DataType::Enum(_) => VariantType::EnumValue,
+        };
+
+        coerce_variant(value, target)
+    }
+
+    /// Finds the descriptor of the enum that the given property on the given
+    /// class or one of its superclasses holds.
+    ///
+    /// Returns `None` if the property isn't known, isn't an enum, or refers to
+    /// an enum that isn't in the database.
+    pub fn find_property_enum(
+        &'a self,
+        class: &'a ClassDescriptor<'a>,
+        property_name: &str,
+    ) -> Option<&'a EnumDescriptor<'a>> {
+        match &self.find_property(class, property_name)?.data_type {
+            DataType::Enum(enum_name) => self.enums.get(enum_name.as_ref()),
+            DataType::Value(_) => None,
+        }
+    }
+
+    /// Tells whether `class` is the class named `ancestor` or inherits from it,
+    /// like `Instance:IsA` in Lua.
+    ///
+    /// Returns `false` if a superclass in the chain isn't in the database.
+    pub fn is_subclass_of(&self, class: &ClassDescriptor, ancestor: &str) -> bool {
+        let mut current_class = Some(class);
+
+        while let Some(class) = current_class {
+            if class.name == ancestor {
+                return true;
+            }
+
+            current_class = class
+                .superclass
+                .as_ref()
+                .and_then(|name| self.classes.get(name.as_ref()));
+        }
+
+        false
+    }
+
+    /// Tells whether Roblox persists the given property when it serializes an
+    /// instance of the class named `class_name`. Aliases are checked through
+    /// the property they alias.
+    ///
+    /// Returns `true` if the class or property isn't in the database, since
+    /// unknown properties are assumed to be persisted.
+    pub fn property_serializes(&'a self, class_name: &str, property_name: &str) -> bool {
+        let class = match self.classes.get(class_name) {
+            Some(class) => class,
+            None => return true,
+        };
+
+        match self.find_property(class, property_name) {
+            Some(descriptor) => match &descriptor.kind {
+                PropertyKind::Alias { alias_for } => self
+                    .find_property(class, alias_for)
+                    .map_or(true, |canonical| canonical.serializes()),
+                PropertyKind::Canonical { .. } => descriptor.serializes(),
+            },
+            None => true,
         }
     }
 }
@@ -108,6 +298,58 @@ impl<'a> PropertyDescriptor<'a> {
             },
         }
     }
+
+    /// Tells whether this property is persisted when Roblox serializes an
+    /// instance, either as itself or under another name.
+    ///
+    /// Aliases never serialize on their own, so this always returns `false`
+    /// for them. Check the property they alias instead.
+    pub fn serializes(&self) -> bool {
+        match &self.kind {
+            PropertyKind::Canonical { serialization } => match serialization {
+                PropertySerialization::Serializes | PropertySerialization::SerializesAs(_) => true,
+                PropertySerialization::DoesNotSerialize => false,
+            },
+            PropertyKind::Alias { .. } => false,
+        }
+    }
+}
+
+/// Describes an enum, like `PartType` or `Material`, and the items it has.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+#[non_exhaustive]
+pub struct EnumDescriptor<'a> {
+    /// The name of the enum, like "PartType".
+    pub name: Cow<'a, str>,
+
+    /// A map from the name of each item in the enum to its value.
+    #[serde(serialize_with = "crate::serde_util::ordered_map")]
+    pub items: HashMap<Cow<'a, str>, u32>,
+}
+
+impl<'a> EnumDescriptor<'a> {
+    /// Creates a new `EnumDescriptor` with the given name and no items.
+    pub fn new<S: Into<Cow<'a, str>>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            items: HashMap::new(),
+        }
+    }
+
+    /// Finds the value of the item with the given name, like `Ball` in
+    /// `PartType`.
+    pub fn item_value(&self, name: &str) -> Option<u32> {
+        self.items.get(name).copied()
+    }
+
+    /// Finds the name of the item with the given value.
+    pub fn item_name(&self, value: u32) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|(_, &item_value)| item_value == value)
+            .map(|(name, _)| name.as_ref())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -170,3 +412,138 @@ pub enum Scriptability {
     /// through methods on `CollectionService`.
     Custom,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn part_type() -> EnumDescriptor<'static> {
+        let mut part_type = EnumDescriptor::new("PartType");
+        part_type.items.insert("Ball".into(), 0);
+        part_type.items.insert("Block".into(), 1);
+        part_type.items.insert("Cylinder".into(), 2);
+        part_type
+    }
+
+    #[test]
+    fn enum_items() {
+        let part_type = part_type();
+
+        assert_eq!(part_type.item_value("Block"), Some(1));
+        assert_eq!(part_type.item_value("Pizza"), None);
+        assert_eq!(part_type.item_name(2), Some("Cylinder"));
+        assert_eq!(part_type.item_name(3), None);
+    }
+
+    fn value_classes() -> ReflectionDatabase<'static> {
+        let mut database = ReflectionDatabase::new();
+
+        let mut instance = ClassDescriptor::new("Instance");
+        instance.properties.insert(
+            "Archivable".into(),
+            PropertyDescriptor::new("Archivable", DataType::Value(VariantType::Bool)),
+        );
+        database.classes.insert("Instance".into(), instance);
+
+        let mut string_value = ClassDescriptor::new("StringValue");
+        string_value.superclass = Some("Instance".into());
+        string_value.properties.insert(
+            "Value".into(),
+            PropertyDescriptor::new("Value", DataType::Value(VariantType::String)),
+        );
+        database.classes.insert("StringValue".into(), string_value);
+
+        database
+    }
+
+    #[test]
+    fn find_class_ignore_case() {
+        let database = value_classes();
+
+        let exact = database.find_class_ignore_case("StringValue").unwrap();
+        assert_eq!(exact.name, "StringValue");
+
+        let wrong_case = database.find_class_ignore_case("stringVALUE").unwrap();
+        assert_eq!(wrong_case.name, "StringValue");
+
+        assert!(database.find_class_ignore_case("StringValues").is_none());
+    }
+
+    #[test]
+    fn find_class_ignore_case_prefers_exact() {
+        let mut database = value_classes();
+        database
+            .classes
+            .insert("Stringvalue".into(), ClassDescriptor::new("Stringvalue"));
+
+        let exact = database.find_class_ignore_case("Stringvalue").unwrap();
+        assert_eq!(exact.name, "Stringvalue");
+
+        // Without an exact match, the first name in sorted order wins.
+        let wrong_case = database.find_class_ignore_case("stringvalue").unwrap();
+        assert_eq!(wrong_case.name, "StringValue");
+    }
+
+    #[test]
+    fn find_property_ignore_case() {
+        let database = value_classes();
+        let string_value = &database.classes["StringValue"];
+
+        let exact = database
+            .find_property_ignore_case(string_value, "Value")
+            .unwrap();
+        assert_eq!(exact.name, "Value");
+
+        let wrong_case = database
+            .find_property_ignore_case(string_value, "value")
+            .unwrap();
+        assert_eq!(wrong_case.name, "Value");
+
+        // Superclasses are searched too.
+        let inherited = database
+            .find_property_ignore_case(string_value, "archivable")
+            .unwrap();
+        assert_eq!(inherited.name, "Archivable");
+
+        assert!(database
+            .find_property_ignore_case(string_value, "Values")
+            .is_none());
+    }
+
+    #[test]
+    fn find_property_enum() {
+        let mut database = ReflectionDatabase::new();
+        database.enums.insert("PartType".into(), part_type());
+
+        let mut base_part = ClassDescriptor::new("BasePart");
+        base_part.properties.insert(
+            "Anchored".into(),
+            PropertyDescriptor::new("Anchored", DataType::Value(VariantType::Bool)),
+        );
+        database.classes.insert("BasePart".into(), base_part);
+
+        let mut part = ClassDescriptor::new("Part");
+        part.superclass = Some("BasePart".into());
+        part.properties.insert(
+            "Shape".into(),
+            PropertyDescriptor::new("Shape", DataType::Enum("PartType".into())),
+        );
+        part.properties.insert(
+            "Material".into(),
+            PropertyDescriptor::new("Material", DataType::Enum("Material".into())),
+        );
+        database.classes.insert("Part".into(), part);
+
+        let part = &database.classes["Part"];
+
+        let shape = database.find_property_enum(part, "Shape").unwrap();
+        assert_eq!(shape.name, "PartType");
+        assert_eq!(shape.item_name(0), Some("Ball"));
+
+        // Enums missing from the database, non-enum properties, and unknown
+        // properties don't have an enum descriptor.
+        assert!(database.find_property_enum(part, "Material").is_none());
+        assert!(database.find_property_enum(part, "Anchored").is_none());
+        assert!(database.find_property_enum(part, "Pizza").is_none());
+    }
+}
@@ -1,8 +1,10 @@
 mod class_tag;
+mod coercion;
 mod database;
 mod property_tag;
 mod serde_util;
 
 pub use class_tag::*;
+pub use coercion::*;
 pub use database::*;
 pub use property_tag::*;
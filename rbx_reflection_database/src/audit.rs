@@ -0,0 +1,122 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use rbx_dom_weak::WeakDom;
+use serde::Serialize;
+
+use crate::get;
+
+/// Describes the classes and properties in a DOM that the reflection database
+/// doesn't know about, created by [`audit`](fn.audit.html).
+///
+/// Serializers fall back to guessing how to write these, so they're often a
+/// sign of a typo or of a database that's out of date. Everything is kept
+/// sorted, so serializing a report is deterministic.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+#[non_exhaustive]
+pub struct AuditReport {
+    /// The ClassNames of instances whose class isn't in the database.
+    pub unknown_classes: BTreeSet<String>,
+
+    /// For each known class, the names of properties on instances of that
+    /// class that neither the class nor its superclasses describe.
+    pub unknown_properties: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl AuditReport {
+    /// Tells whether every class and property in the audited DOM was known.
+    pub fn is_empty(&self) -> bool {
+        self.unknown_classes.is_empty() && self.unknown_properties.is_empty()
+    }
+}
+
+/// Checks every instance in the DOM, including the root, against the bundled
+/// reflection database.
+///
+/// Properties of instances whose class is unknown aren't listed, since none of
+/// them can be known either.
+pub fn audit(dom: &WeakDom) -> AuditReport {
+    let database = get();
+    let mut report = AuditReport::default();
+
+    for referent in std::iter::once(dom.root_ref()).chain(dom.descendants_of_root()) {
+        let instance = dom.get_by_ref(referent).unwrap();
+
+        let class = match database.classes.get(instance.class.as_str()) {
+            Some(class) => class,
+            None => {
+                report.unknown_classes.insert(instance.class.clone());
+                continue;
+            }
+        };
+
+        for property_name in instance.properties.keys() {
+            if database.find_property(class, property_name).is_none() {
+                report
+                    .unknown_properties
+                    .entry(instance.class.clone())
+                    .or_default()
+                    .insert(property_name.clone());
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::InstanceBuilder;
+
+    #[test]
+    fn audit_known() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("StringValue")
+                    .with_property("Value", "Hello")
+                    // Archivable is inherited from Instance.
+                    .with_property("Archivable", true),
+            ),
+        );
+
+        assert!(audit(&dom).is_empty());
+    }
+
+    #[test]
+    fn audit_unknown() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_property("WILL_NEVER_EXIST", "Hi, mom!")
+                .with_child(
+                    InstanceBuilder::new("StringValue")
+                        .with_property("Value", "Hello")
+                        .with_property("Valeu", "Typo"),
+                )
+                .with_child(
+                    InstanceBuilder::new("StringValue").with_property("WILL_NEVER_EXIST", 1),
+                )
+                .with_child(
+                    InstanceBuilder::new("NotARealClass").with_property("AlsoNotReal", true),
+                ),
+        );
+
+        let report = audit(&dom);
+
+        let expected_classes: BTreeSet<String> =
+            vec!["NotARealClass".to_owned()].into_iter().collect();
+        assert_eq!(report.unknown_classes, expected_classes);
+
+        let folder: BTreeSet<String> = vec!["WILL_NEVER_EXIST".to_owned()].into_iter().collect();
+        let string_value: BTreeSet<String> =
+            vec!["Valeu".to_owned(), "WILL_NEVER_EXIST".to_owned()]
+                .into_iter()
+                .collect();
+
+        let mut expected_properties = BTreeMap::new();
+        expected_properties.insert("Folder".to_owned(), folder);
+        expected_properties.insert("StringValue".to_owned(), string_value);
+        assert_eq!(report.unknown_properties, expected_properties);
+    }
+}
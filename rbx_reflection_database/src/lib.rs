@@ -1,5 +1,11 @@
+mod audit;
+mod typed_properties;
+
 use rbx_reflection::ReflectionDatabase;
 
+pub use audit::*;
+pub use typed_properties::*;
+
 static ENCODED_DATABASE: &[u8] = include_bytes!("../database.msgpack");
 
 lazy_static::lazy_static! {
@@ -16,8 +22,98 @@ pub fn get() -> &'static ReflectionDatabase<'static> {
 mod test {
     use super::*;
 
+    use std::borrow::Cow;
+
+    use rbx_reflection::{DataType, PropertyDescriptor, PropertyKind, PropertySerialization};
+    use rbx_types::{Color3, Color3uint8, Variant, VariantType};
+
     #[test]
     fn smoke_test() {
         let _database = get();
     }
+
+    #[test]
+    fn find_default_property() {
+        let database = get();
+        let string_value = &database.classes["StringValue"];
+
+        assert_eq!(
+            database.find_default_property(string_value, "Value"),
+            Some(&Variant::String(String::new()))
+        );
+        assert_eq!(
+            database.find_default_property(string_value, "NotARealProperty"),
+            None
+        );
+    }
+
+    #[test]
+    fn is_subclass_of() {
+        let database = get();
+        let part = &database.classes["Part"];
+
+        assert!(database.is_subclass_of(part, "Part"));
+        assert!(database.is_subclass_of(part, "BasePart"));
+        assert!(database.is_subclass_of(part, "PVInstance"));
+        assert!(database.is_subclass_of(part, "Instance"));
+        assert!(!database.is_subclass_of(part, "GuiObject"));
+        assert!(!database.is_subclass_of(&database.classes["Instance"], "Part"));
+    }
+
+    #[test]
+    fn property_serializes() {
+        let database = get();
+        let value = &database.classes["StringValue"].properties["Value"];
+        assert!(value.serializes());
+
+        let mut descriptor = PropertyDescriptor::new("Value", DataType::Value(VariantType::String));
+        descriptor.kind = PropertyKind::Canonical {
+            serialization: PropertySerialization::DoesNotSerialize,
+        };
+        assert!(!descriptor.serializes());
+
+        descriptor.kind = PropertyKind::Alias {
+            alias_for: Cow::Borrowed("OtherValue"),
+        };
+        assert!(!descriptor.serializes());
+    }
+
+    #[test]
+    fn database_property_serializes() {
+        let database = get();
+
+        assert!(database.property_serializes("StringValue", "Value"));
+        assert!(!database.property_serializes("StringValue", "RobloxLocked"));
+        assert!(database.property_serializes("StringValue", "NotARealProperty"));
+        assert!(database.property_serializes("NotARealClass", "Value"));
+    }
+
+    #[test]
+    fn coerce_property() {
+        let database = get();
+
+        let number_value = &database.classes["NumberValue"];
+        assert_eq!(
+            database.coerce_property(number_value, "Value", Variant::Int32(5)),
+            Some(Variant::Float64(5.0))
+        );
+        assert_eq!(
+            database.coerce_property(number_value, "Value", Variant::Bool(true)),
+            None
+        );
+        assert_eq!(
+            database.coerce_property(number_value, "NotARealProperty", Variant::Int32(5)),
+            None
+        );
+
+        let color_value = &database.classes["Color3Value"];
+        assert_eq!(
+            database.coerce_property(
+                color_value,
+                "Value",
+                Variant::Color3uint8(Color3uint8::new(0, 255, 0))
+            ),
+            Some(Variant::Color3(Color3::new(0.0, 1.0, 0.0)))
+        );
+    }
 }
@@ -0,0 +1,78 @@
+use rbx_dom_weak::{types::Variant, Instance};
+use rbx_reflection::PropertyDescriptor;
+
+use crate::get;
+
+/// Iterates over the properties set on the given instance, each paired with
+/// the descriptor the bundled reflection database has for it, or `None` if
+/// neither the instance's class nor its superclasses describe it.
+///
+/// Properties are yielded sorted by name, so the order is stable across calls,
+/// which makes this suitable for displaying properties to users. Descriptors
+/// carry the property's type, and whether a property is set to its default can
+/// be checked with `ReflectionDatabase::find_default_property`.
+pub fn typed_properties(
+    instance: &Instance,
+) -> impl Iterator<Item = (&str, &Variant, Option<&'static PropertyDescriptor<'static>>)> {
+    let database = get();
+    let class = database.classes.get(instance.class.as_str());
+
+    let mut properties: Vec<_> = instance
+        .properties
+        .iter()
+        .map(|(name, value)| {
+            let descriptor = class.and_then(|class| database.find_property(class, name));
+            (name.as_str(), value, descriptor)
+        })
+        .collect();
+
+    properties.sort_unstable_by_key(|(name, _, _)| *name);
+    properties.into_iter()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::{InstanceBuilder, WeakDom};
+
+    #[test]
+    fn typed_properties_sorted() {
+        let dom = WeakDom::new(
+            InstanceBuilder::new("StringValue")
+                .with_property("Value", "Hello")
+                .with_property("WILL_NEVER_EXIST", 1)
+                // Archivable is inherited from Instance.
+                .with_property("Archivable", true),
+        );
+
+        let properties: Vec<_> = typed_properties(dom.root())
+            .map(|(name, value, descriptor)| {
+                (
+                    name,
+                    value.clone(),
+                    descriptor.map(|descriptor| descriptor.name.as_ref()),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            properties,
+            vec![
+                ("Archivable", Variant::Bool(true), Some("Archivable")),
+                ("Value", Variant::String("Hello".to_owned()), Some("Value")),
+                ("WILL_NEVER_EXIST", Variant::Int32(1), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn typed_properties_unknown_class() {
+        let dom =
+            WeakDom::new(InstanceBuilder::new("NotARealClass").with_property("Archivable", true));
+
+        let properties: Vec<_> = typed_properties(dom.root()).collect();
+        assert_eq!(properties.len(), 1);
+        assert!(properties[0].2.is_none());
+    }
+}
@@ -0,0 +1,565 @@
+use std::{
+    collections::{btree_map, BTreeMap},
+    fmt, io,
+    io::{Read, Write},
+};
+
+use crate::{
+    BinaryString, BrickColor, Color3, ColorSequence, ColorSequenceKeypoint, NumberRange,
+    NumberSequence, NumberSequenceKeypoint, Rect, UDim, UDim2, Variant, VariantType, Vector2,
+    Vector3,
+};
+
+/// The type IDs used in serialized attributes. These are independent from the
+/// type IDs used by the binary model format.
+mod type_id {
+    pub const STRING: u8 = 0x02;
+    pub const BOOL: u8 = 0x03;
+    pub const FLOAT32: u8 = 0x05;
+    pub const FLOAT64: u8 = 0x06;
+    pub const UDIM: u8 = 0x09;
+    pub const UDIM2: u8 = 0x0A;
+    pub const BRICK_COLOR: u8 = 0x0E;
+    pub const COLOR3: u8 = 0x0F;
+    pub const VECTOR2: u8 = 0x10;
+    pub const VECTOR3: u8 = 0x11;
+    pub const NUMBER_SEQUENCE: u8 = 0x17;
+    pub const COLOR_SEQUENCE: u8 = 0x19;
+    pub const NUMBER_RANGE: u8 = 0x1B;
+    pub const RECT: u8 = 0x1C;
+}
+
+/// A collection of attributes attached to an instance, keyed by name.
+///
+/// Roblox stores attributes in a binary blob in the `AttributesSerialize`
+/// property. Only some types can be attributes; attempting to serialize
+/// any other type will return an error.
+///
+/// ## See Also
+/// * [Attributes on Roblox Developer Hub](https://developer.roblox.com/en-us/articles/instance-attributes)
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Attributes {
+    data: BTreeMap<String, Variant>,
+}
+
+impl Attributes {
+    /// Creates an empty `Attributes` container.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the attribute with the given name, if it exists.
+    pub fn get(&self, name: &str) -> Option<&Variant> {
+        self.data.get(name)
+    }
+
+    /// Sets the attribute with the given name, returning the attribute's
+    /// previous value if there was one.
+    pub fn insert<K: Into<String>, V: Into<Variant>>(
+        &mut self,
+        name: K,
+        value: V,
+    ) -> Option<Variant> {
+        self.data.insert(name.into(), value.into())
+    }
+
+    /// Removes the attribute with the given name, returning its value if it
+    /// existed.
+    pub fn remove(&mut self, name: &str) -> Option<Variant> {
+        self.data.remove(name)
+    }
+
+    /// Returns an iterator over all attributes, ordered by name.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Variant> {
+        self.data.iter()
+    }
+
+    /// Returns the number of attributes in this container.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns whether this container has no attributes.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Decodes attributes from the binary format Roblox uses for the
+    /// `AttributesSerialize` property.
+    ///
+    /// String attributes are decoded as `Variant::String` if they're valid
+    /// UTF-8, and as `Variant::BinaryString` otherwise. Empty input decodes as
+    /// an empty container, since that's what Roblox writes for instances that
+    /// have no attributes.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, AttributeError> {
+        let mut len_buffer = Vec::with_capacity(4);
+        reader.by_ref().take(4).read_to_end(&mut len_buffer)?;
+
+        if len_buffer.is_empty() {
+            return Ok(Self::new());
+        }
+
+        let len = read_u32(&mut len_buffer.as_slice())?;
+        let mut data = BTreeMap::new();
+
+        for _ in 0..len {
+            let name = match String::from_utf8(read_buffer(&mut reader)?) {
+                Ok(name) => name,
+                Err(_) => return Err(AttributeError::InvalidName),
+            };
+
+            let value = read_value(&mut reader)?;
+            data.insert(name, value);
+        }
+
+        Ok(Self { data })
+    }
+
+    /// Encodes attributes into the binary format Roblox uses for the
+    /// `AttributesSerialize` property.
+    ///
+    /// An empty container writes nothing, matching what Roblox writes for
+    /// instances that have no attributes.
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> Result<(), AttributeError> {
+        if self.data.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_all(&(self.data.len() as u32).to_le_bytes())?;
+
+        for (name, value) in &self.data {
+            write_buffer(&mut writer, name.as_bytes())?;
+            write_value(&mut writer, name, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a Attributes {
+    type Item = (&'a String, &'a Variant);
+    type IntoIter = btree_map::Iter<'a, String, Variant>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An error that can occur when decoding or encoding [`Attributes`][Attributes].
+///
+/// [Attributes]: struct.Attributes.html
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AttributeError {
+    /// An I/O error occurred, like the input ending unexpectedly.
+    Io(io::Error),
+
+    /// The name of an attribute was not valid UTF-8.
+    InvalidName,
+
+    /// An attribute had a type ID that isn't known.
+    UnknownType(u8),
+
+    /// A BrickColor attribute had a value that isn't a known BrickColor.
+    InvalidBrickColor(u32),
+
+    /// An attribute holds a value of a type that can't be stored as an
+    /// attribute.
+    UnsupportedType { name: String, ty: VariantType },
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeError::Io(err) => write!(formatter, "{}", err),
+            AttributeError::InvalidName => {
+                write!(formatter, "Attribute name was not valid UTF-8")
+            }
+            AttributeError::UnknownType(type_id) => {
+                write!(formatter, "Unknown attribute type ID {:#04x}", type_id)
+            }
+            AttributeError::InvalidBrickColor(value) => {
+                write!(formatter, "Invalid BrickColor value {}", value)
+            }
+            AttributeError::UnsupportedType { name, ty } => write!(
+                formatter,
+                "Attribute {} has type {:?}, which cannot be stored as an attribute",
+                name, ty
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AttributeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AttributeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AttributeError {
+    fn from(err: io::Error) -> Self {
+        AttributeError::Io(err)
+    }
+}
+
+fn read_value<R: Read>(reader: &mut R) -> Result<Variant, AttributeError> {
+    let type_id = read_u8(reader)?;
+
+    Ok(match type_id {
+        type_id::STRING => {
+            let buffer = read_buffer(reader)?;
+
+            match String::from_utf8(buffer) {
+                Ok(value) => Variant::String(value),
+                Err(err) => Variant::BinaryString(BinaryString::from(err.into_bytes())),
+            }
+        }
+        type_id::BOOL => Variant::Bool(read_u8(reader)? != 0),
+        type_id::FLOAT32 => Variant::Float32(read_f32(reader)?),
+        type_id::FLOAT64 => Variant::Float64(read_f64(reader)?),
+        type_id::UDIM => Variant::UDim(read_udim(reader)?),
+        type_id::UDIM2 => Variant::UDim2(UDim2::new(read_udim(reader)?, read_udim(reader)?)),
+        type_id::BRICK_COLOR => {
+            let value = read_u32(reader)?;
+
+            if value > std::u16::MAX as u32 {
+                return Err(AttributeError::InvalidBrickColor(value));
+            }
+
+            BrickColor::from_number(value as u16)
+                .map(Variant::BrickColor)
+                .ok_or(AttributeError::InvalidBrickColor(value))?
+        }
+        type_id::COLOR3 => Variant::Color3(read_color3(reader)?),
+        type_id::VECTOR2 => Variant::Vector2(read_vector2(reader)?),
+        type_id::VECTOR3 => Variant::Vector3(Vector3::new(
+            read_f32(reader)?,
+            read_f32(reader)?,
+            read_f32(reader)?,
+        )),
+        type_id::NUMBER_SEQUENCE => {
+            let len = read_u32(reader)?;
+            let mut keypoints = Vec::new();
+
+            for _ in 0..len {
+                let envelope = read_f32(reader)?;
+                let time = read_f32(reader)?;
+                let value = read_f32(reader)?;
+
+                keypoints.push(NumberSequenceKeypoint::new(time, value, envelope));
+            }
+
+            Variant::NumberSequence(NumberSequence { keypoints })
+        }
+        type_id::COLOR_SEQUENCE => {
+            let len = read_u32(reader)?;
+            let mut keypoints = Vec::new();
+
+            for _ in 0..len {
+                // Each keypoint has an envelope, just like NumberSequence, but
+                // it's always zero.
+                let _envelope = read_f32(reader)?;
+                let time = read_f32(reader)?;
+                let color = read_color3(reader)?;
+
+                keypoints.push(ColorSequenceKeypoint::new(time, color));
+            }
+
+            Variant::ColorSequence(ColorSequence { keypoints })
+        }
+        type_id::NUMBER_RANGE => {
+            Variant::NumberRange(NumberRange::new(read_f32(reader)?, read_f32(reader)?))
+        }
+        type_id::RECT => Variant::Rect(Rect::new(read_vector2(reader)?, read_vector2(reader)?)),
+        _ => return Err(AttributeError::UnknownType(type_id)),
+    })
+}
+
+fn write_value<W: Write>(
+    writer: &mut W,
+    name: &str,
+    value: &Variant,
+) -> Result<(), AttributeError> {
+    match value {
+        Variant::String(value) => {
+            writer.write_all(&[type_id::STRING])?;
+            write_buffer(writer, value.as_bytes())?;
+        }
+        Variant::BinaryString(value) => {
+            writer.write_all(&[type_id::STRING])?;
+            write_buffer(writer, value.as_ref())?;
+        }
+        Variant::Bool(value) => {
+            writer.write_all(&[type_id::BOOL, *value as u8])?;
+        }
+        Variant::Float32(value) => {
+            writer.write_all(&[type_id::FLOAT32])?;
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Variant::Float64(value) => {
+            writer.write_all(&[type_id::FLOAT64])?;
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Variant::UDim(value) => {
+            writer.write_all(&[type_id::UDIM])?;
+            write_udim(writer, *value)?;
+        }
+        Variant::UDim2(value) => {
+            writer.write_all(&[type_id::UDIM2])?;
+            write_udim(writer, value.x)?;
+            write_udim(writer, value.y)?;
+        }
+        Variant::BrickColor(value) => {
+            writer.write_all(&[type_id::BRICK_COLOR])?;
+            writer.write_all(&(*value as u32).to_le_bytes())?;
+        }
+        Variant::Color3(value) => {
+            writer.write_all(&[type_id::COLOR3])?;
+            write_color3(writer, *value)?;
+        }
+        Variant::Vector2(value) => {
+            writer.write_all(&[type_id::VECTOR2])?;
+            write_vector2(writer, *value)?;
+        }
+        Variant::Vector3(value) => {
+            writer.write_all(&[type_id::VECTOR3])?;
+            write_f32s(writer, &[value.x, value.y, value.z])?;
+        }
+        Variant::NumberSequence(value) => {
+            writer.write_all(&[type_id::NUMBER_SEQUENCE])?;
+            writer.write_all(&(value.keypoints.len() as u32).to_le_bytes())?;
+
+            for keypoint in &value.keypoints {
+                write_f32s(writer, &[keypoint.envelope, keypoint.time, keypoint.value])?;
+            }
+        }
+        Variant::ColorSequence(value) => {
+            writer.write_all(&[type_id::COLOR_SEQUENCE])?;
+            writer.write_all(&(value.keypoints.len() as u32).to_le_bytes())?;
+
+            for keypoint in &value.keypoints {
+                write_f32s(writer, &[0.0, keypoint.time])?;
+                write_color3(writer, keypoint.color)?;
+            }
+        }
+        Variant::NumberRange(value) => {
+            writer.write_all(&[type_id::NUMBER_RANGE])?;
+            write_f32s(writer, &[value.min, value.max])?;
+        }
+        Variant::Rect(value) => {
+            writer.write_all(&[type_id::RECT])?;
+            write_vector2(writer, value.min)?;
+            write_vector2(writer, value.max)?;
+        }
+        _ => {
+            return Err(AttributeError::UnsupportedType {
+                name: name.to_owned(),
+                ty: value.ty(),
+            })
+        }
+    }
+
+    Ok(())
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buffer = [0; 1];
+    reader.read_exact(&mut buffer)?;
+    Ok(buffer[0])
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(u32::from_le_bytes(buffer))
+}
+
+fn read_f32<R: Read>(reader: &mut R) -> io::Result<f32> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer)?;
+    Ok(f32::from_le_bytes(buffer))
+}
+
+fn read_f64<R: Read>(reader: &mut R) -> io::Result<f64> {
+    let mut buffer = [0; 8];
+    reader.read_exact(&mut buffer)?;
+    Ok(f64::from_le_bytes(buffer))
+}
+
+fn read_buffer<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let len = read_u32(reader)?;
+
+    let mut buffer = Vec::new();
+    reader.take(len as u64).read_to_end(&mut buffer)?;
+
+    if buffer.len() != len as usize {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    Ok(buffer)
+}
+
+fn read_udim<R: Read>(reader: &mut R) -> io::Result<UDim> {
+    let scale = read_f32(reader)?;
+    let offset = read_u32(reader)? as i32;
+
+    Ok(UDim::new(scale, offset))
+}
+
+fn read_color3<R: Read>(reader: &mut R) -> io::Result<Color3> {
+    Ok(Color3::new(
+        read_f32(reader)?,
+        read_f32(reader)?,
+        read_f32(reader)?,
+    ))
+}
+
+fn read_vector2<R: Read>(reader: &mut R) -> io::Result<Vector2> {
+    Ok(Vector2::new(read_f32(reader)?, read_f32(reader)?))
+}
+
+fn write_buffer<W: Write>(writer: &mut W, buffer: &[u8]) -> io::Result<()> {
+    writer.write_all(&(buffer.len() as u32).to_le_bytes())?;
+    writer.write_all(buffer)
+}
+
+fn write_f32s<W: Write>(writer: &mut W, values: &[f32]) -> io::Result<()> {
+    for value in values {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn write_udim<W: Write>(writer: &mut W, value: UDim) -> io::Result<()> {
+    writer.write_all(&value.scale.to_le_bytes())?;
+    writer.write_all(&value.offset.to_le_bytes())
+}
+
+fn write_color3<W: Write>(writer: &mut W, value: Color3) -> io::Result<()> {
+    write_f32s(writer, &[value.r, value.g, value.b])
+}
+
+fn write_vector2<W: Write>(writer: &mut W, value: Vector2) -> io::Result<()> {
+    write_f32s(writer, &[value.x, value.y])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decode_empty() {
+        assert_eq!(Attributes::from_reader(&[][..]).unwrap(), Attributes::new());
+        assert_eq!(
+            Attributes::from_reader(&[0, 0, 0, 0][..]).unwrap(),
+            Attributes::new()
+        );
+        assert!(Attributes::from_reader(&[0, 0][..]).is_err());
+    }
+
+    #[test]
+    fn encode_empty() {
+        let mut buffer = Vec::new();
+        Attributes::new().to_writer(&mut buffer).unwrap();
+
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut attributes = Attributes::new();
+        attributes.insert("Name", "Hello");
+        attributes.insert("Enabled", true);
+        attributes.insert("Health", 100.0f64);
+        attributes.insert("Size", UDim2::new(UDim::new(0.5, 10), UDim::new(1.0, -4)));
+        attributes.insert("Color", Color3::new(1.0, 0.5, 0.25));
+        attributes.insert("Team", BrickColor::from_number(194).unwrap());
+        attributes.insert("Position", Vector3::new(1.0, 2.0, 3.0));
+        attributes.insert("Range", NumberRange::new(0.0, 10.0));
+        attributes.insert(
+            "Fade",
+            NumberSequence {
+                keypoints: vec![
+                    NumberSequenceKeypoint::new(0.0, 1.0, 0.0),
+                    NumberSequenceKeypoint::new(1.0, 0.0, 0.5),
+                ],
+            },
+        );
+        attributes.insert(
+            "Gradient",
+            ColorSequence {
+                keypoints: vec![
+                    ColorSequenceKeypoint::new(0.0, Color3::new(1.0, 0.0, 0.0)),
+                    ColorSequenceKeypoint::new(1.0, Color3::new(0.0, 0.0, 1.0)),
+                ],
+            },
+        );
+        attributes.insert(
+            "Bounds",
+            Rect::new(Vector2::new(0.0, 0.0), Vector2::new(5.0, 5.0)),
+        );
+
+        let mut buffer = Vec::new();
+        attributes.to_writer(&mut buffer).unwrap();
+
+        let decoded = Attributes::from_reader(buffer.as_slice()).unwrap();
+        assert_eq!(decoded, attributes);
+    }
+
+    #[test]
+    fn byte_layout() {
+        let mut attributes = Attributes::new();
+        attributes.insert("A", true);
+        attributes.insert("B", 1.0f64);
+
+        let mut buffer = Vec::new();
+        attributes.to_writer(&mut buffer).unwrap();
+
+        let mut expected = vec![2, 0, 0, 0];
+        expected.extend_from_slice(&[1, 0, 0, 0, b'A', type_id::BOOL, 1]);
+        expected.extend_from_slice(&[1, 0, 0, 0, b'B', type_id::FLOAT64]);
+        expected.extend_from_slice(&1.0f64.to_le_bytes());
+
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn unsupported_type() {
+        let mut attributes = Attributes::new();
+        attributes.insert("Int", 5i32);
+
+        let mut buffer = Vec::new();
+        match attributes.to_writer(&mut buffer) {
+            Err(AttributeError::UnsupportedType { name, ty }) => {
+                assert_eq!(name, "Int");
+                assert_eq!(ty, VariantType::Int32);
+            }
+            other => panic!("expected UnsupportedType error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_type() {
+        let buffer = [1, 0, 0, 0, 1, 0, 0, 0, b'A', 0xFF];
+
+        match Attributes::from_reader(&buffer[..]) {
+            Err(AttributeError::UnknownType(0xFF)) => {}
+            other => panic!("expected UnknownType error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let buffer = [1, 0, 0, 0, 10, 0, 0, 0, b'A'];
+        assert!(Attributes::from_reader(&buffer[..]).is_err());
+    }
+}
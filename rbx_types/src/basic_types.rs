@@ -1,3 +1,5 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
 /// Represents any Roblox enum value.
 ///
 /// Roblox enums are not strongly typed, so the meaning of a value depends on
@@ -25,6 +27,38 @@ impl EnumValue {
     }
 }
 
+/// A value of an enum along with the name of the enum it belongs to, like
+/// `Enum.PartType.Ball`.
+///
+/// Serializers write this the same way as an [`EnumValue`](struct.EnumValue.html),
+/// which only holds the number. Deserializers can produce `EnumItem` values
+/// instead when the reflection database says which enum a property holds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "PascalCase")
+)]
+pub struct EnumItem {
+    pub enum_name: String,
+    pub value: u32,
+}
+
+impl EnumItem {
+    pub fn new<S: Into<String>>(enum_name: S, value: u32) -> Self {
+        Self {
+            enum_name: enum_name.into(),
+            value,
+        }
+    }
+}
+
+impl From<EnumItem> for EnumValue {
+    fn from(item: EnumItem) -> Self {
+        EnumValue::from_u32(item.value)
+    }
+}
+
 /// The standard 2D vector type used in Roblox.
 ///
 /// ## See Also
@@ -40,6 +74,63 @@ impl Vector2 {
     pub fn new(x: f32, y: f32) -> Self {
         Self { x, y }
     }
+
+    /// The length of this vector.
+    pub fn magnitude(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// The dot product of this vector and `other`.
+    pub fn dot(self, other: Vector2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Returns a vector with the same direction as this one and a length of
+    /// one.
+    ///
+    /// The zero vector has no direction, so unlike `Vector2.Unit` in Roblox,
+    /// which produces NaN components, it is returned unchanged.
+    pub fn unit(self) -> Vector2 {
+        let magnitude = self.magnitude();
+
+        if magnitude == 0.0 {
+            self
+        } else {
+            self * (1.0 / magnitude)
+        }
+    }
+}
+
+impl Add for Vector2 {
+    type Output = Vector2;
+
+    fn add(self, other: Vector2) -> Vector2 {
+        Vector2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector2 {
+    type Output = Vector2;
+
+    fn sub(self, other: Vector2) -> Vector2 {
+        Vector2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for Vector2 {
+    type Output = Vector2;
+
+    fn mul(self, factor: f32) -> Vector2 {
+        Vector2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Neg for Vector2 {
+    type Output = Vector2;
+
+    fn neg(self) -> Vector2 {
+        Vector2::new(-self.x, -self.y)
+    }
 }
 
 /// A version of [`Vector2`][Vector2] whose coordinates are signed 16-bit
@@ -78,6 +169,74 @@ impl Vector3 {
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         Self { x, y, z }
     }
+
+    /// The length of this vector.
+    pub fn magnitude(self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// The dot product of this vector and `other`.
+    pub fn dot(self, other: Vector3) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// The cross product of this vector and `other`. Like in Roblox, this is
+    /// right-handed, so the cross product of the X axis and the Y axis is the
+    /// Z axis.
+    pub fn cross(self, other: Vector3) -> Vector3 {
+        Vector3::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    /// Returns a vector with the same direction as this one and a length of
+    /// one.
+    ///
+    /// The zero vector has no direction, so unlike `Vector3.Unit` in Roblox,
+    /// which produces NaN components, it is returned unchanged.
+    pub fn unit(self) -> Vector3 {
+        let magnitude = self.magnitude();
+
+        if magnitude == 0.0 {
+            self
+        } else {
+            self * (1.0 / magnitude)
+        }
+    }
+}
+
+impl Add for Vector3 {
+    type Output = Vector3;
+
+    fn add(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl Sub for Vector3 {
+    type Output = Vector3;
+
+    fn sub(self, other: Vector3) -> Vector3 {
+        Vector3::new(self.x - other.x, self.y - other.y, self.z - other.z)
+    }
+}
+
+impl Mul<f32> for Vector3 {
+    type Output = Vector3;
+
+    fn mul(self, factor: f32) -> Vector3 {
+        Vector3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
+impl Neg for Vector3 {
+    type Output = Vector3;
+
+    fn neg(self) -> Vector3 {
+        Vector3::new(-self.x, -self.y, -self.z)
+    }
 }
 
 /// A version of [`Vector3`][Vector3] whose coordinates are signed 16-bit
@@ -123,6 +282,108 @@ impl CFrame {
             orientation,
         }
     }
+
+    /// Creates a CFrame at the given position with no rotation, like
+    /// `CFrame.new(position)` from Roblox.
+    pub fn from_position(position: Vector3) -> Self {
+        Self::new(position, Matrix3::identity())
+    }
+
+    /// Creates a CFrame at the given position, rotated by the given angles in
+    /// radians around the X, Y, and Z axes. Like `CFrame.Angles` from Roblox,
+    /// the rotations are applied in Z, Y, X order.
+    pub fn from_position_and_angles(position: Vector3, rx: f32, ry: f32, rz: f32) -> Self {
+        let (sx, cx) = rx.sin_cos();
+        let (sy, cy) = ry.sin_cos();
+        let (sz, cz) = rz.sin_cos();
+
+        let orientation = Matrix3 {
+            x: Vector3::new(cy * cz, -cy * sz, sy),
+            y: Vector3::new(sx * sy * cz + cx * sz, cx * cz - sx * sy * sz, -sx * cy),
+            z: Vector3::new(sx * sz - cx * sy * cz, cx * sy * sz + sx * cz, cx * cy),
+        };
+
+        Self::new(position, orientation)
+    }
+
+    /// The direction of this CFrame's X axis, the first column of its
+    /// rotation.
+    pub fn right_vector(&self) -> Vector3 {
+        self.orientation.transpose().x
+    }
+
+    /// The direction of this CFrame's Y axis, the second column of its
+    /// rotation.
+    pub fn up_vector(&self) -> Vector3 {
+        self.orientation.transpose().y
+    }
+
+    /// The direction this CFrame is facing, which is the opposite of its Z
+    /// axis.
+    pub fn look_vector(&self) -> Vector3 {
+        -self.orientation.transpose().z
+    }
+
+    /// Returns a copy of this CFrame with its rotation corrected to be
+    /// orthonormal using the Gram-Schmidt process. The position is left
+    /// untouched.
+    ///
+    /// The direction of the right vector (the first column of the rotation) is
+    /// kept, the up vector is made perpendicular to it, and the back vector is
+    /// recomputed from the two. If the right or up vectors are degenerate, the
+    /// identity rotation is used instead.
+    pub fn orthonormalize(&self) -> CFrame {
+        let columns = self.orientation.transpose();
+
+        let right = match normalize(columns.x) {
+            Some(right) => right,
+            None => return CFrame::new(self.position, Matrix3::identity()),
+        };
+
+        let up = columns.y - right * columns.y.dot(right);
+        let up = match normalize(up) {
+            Some(up) => up,
+            None => return CFrame::new(self.position, Matrix3::identity()),
+        };
+
+        let back = right.cross(up);
+
+        let orientation = Matrix3 {
+            x: right,
+            y: up,
+            z: back,
+        }
+        .transpose();
+
+        CFrame::new(self.position, orientation)
+    }
+
+    /// Tells whether the rotation of this CFrame is orthonormal, allowing each
+    /// component to be off by up to `epsilon`.
+    pub fn is_orthonormal(&self, epsilon: f32) -> bool {
+        let columns = self.orientation.transpose();
+        let axes = [columns.x, columns.y, columns.z];
+
+        let unit_length = axes
+            .iter()
+            .all(|&axis| (axis.magnitude() - 1.0).abs() <= epsilon);
+
+        let orthogonal = axes[0].dot(axes[1]).abs() <= epsilon
+            && axes[0].dot(axes[2]).abs() <= epsilon
+            && axes[1].dot(axes[2]).abs() <= epsilon;
+
+        unit_length && orthogonal
+    }
+}
+
+fn normalize(a: Vector3) -> Option<Vector3> {
+    let length = a.magnitude();
+
+    if length <= std::f32::EPSILON {
+        None
+    } else {
+        Some(a * (1.0 / length))
+    }
 }
 
 /// Used to represent the `orientation` field of `CFrame` and not a standalone
@@ -137,11 +398,20 @@ pub struct Matrix3 {
 impl Matrix3 {
     pub fn identity() -> Self {
         Self {
-            x: Vector3::new(1.0, 0.0, 1.0),
+            x: Vector3::new(1.0, 0.0, 0.0),
             y: Vector3::new(0.0, 1.0, 0.0),
             z: Vector3::new(0.0, 0.0, 1.0),
         }
     }
+
+    /// Returns the transpose of this matrix, swapping its rows and columns.
+    pub fn transpose(&self) -> Self {
+        Self {
+            x: Vector3::new(self.x.x, self.y.x, self.z.x),
+            y: Vector3::new(self.x.y, self.y.y, self.z.y),
+            z: Vector3::new(self.x.z, self.y.z, self.z.z),
+        }
+    }
 }
 
 /// Represents any color, including HDR colors.
@@ -161,6 +431,36 @@ impl Color3 {
     pub fn new(r: f32, g: f32, b: f32) -> Self {
         Self { r, g, b }
     }
+
+    /// Creates a `Color3` from channels in the range 0-255, like
+    /// `Color3.fromRGB` in Lua.
+    pub fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Color3uint8::new(r, g, b).into()
+    }
+
+    /// Linearly interpolates each channel between this color and `other`, like
+    /// `Color3:Lerp` in Lua. `t` is clamped to the range 0.0-1.0, so a `t` of
+    /// 0.0 or less returns this color and 1.0 or more returns `other`.
+    pub fn lerp(&self, other: Color3, t: f32) -> Color3 {
+        let t = t.max(0.0).min(1.0);
+        let lerp = |a: f32, b: f32| a * (1.0 - t) + b * t;
+
+        Color3::new(
+            lerp(self.r, other.r),
+            lerp(self.g, other.g),
+            lerp(self.b, other.b),
+        )
+    }
+}
+
+impl From<Color3uint8> for Color3 {
+    fn from(value: Color3uint8) -> Self {
+        Self::new(
+            value.r as f32 / 255.0,
+            value.g as f32 / 255.0,
+            value.b as f32 / 255.0,
+        )
+    }
 }
 
 /// Represents non-HDR colors, i.e. those whose individual color channels do not
@@ -184,6 +484,44 @@ impl Color3uint8 {
     pub fn new(r: u8, g: u8, b: u8) -> Self {
         Self { r, g, b }
     }
+
+    /// Interpolates between this color and `other` like
+    /// [`Color3::lerp`](struct.Color3.html#method.lerp), converting the result
+    /// back to the range 0-255.
+    pub fn lerp(&self, other: Color3uint8, t: f32) -> Color3uint8 {
+        Color3::from(*self).lerp(other.into(), t).into()
+    }
+}
+
+/// Converts each channel to the range 0-255, clamping channels outside of
+/// 0.0-1.0 and rounding exact halves to the nearest even number. NaN channels
+/// become 0.
+impl From<Color3> for Color3uint8 {
+    fn from(value: Color3) -> Self {
+        Self::new(
+            channel_to_u8(value.r),
+            channel_to_u8(value.g),
+            channel_to_u8(value.b),
+        )
+    }
+}
+
+fn channel_to_u8(value: f32) -> u8 {
+    round_half_to_even(value.max(0.0).min(1.0) * 255.0) as u8
+}
+
+/// Rounds a non-negative value to the nearest integer, rounding exact halves
+/// to the nearest even integer.
+fn round_half_to_even(value: f32) -> f32 {
+    let rounded = value.round();
+
+    // `round` rounds halves away from zero, so exact halves that landed on an
+    // odd number need to be moved down to the even number below them.
+    if value - value.trunc() == 0.5 && rounded % 2.0 != 0.0 {
+        rounded - 1.0
+    } else {
+        rounded
+    }
 }
 
 /// Represents a ray in 3D space. Direction does not have to be a unit vector,
@@ -213,6 +551,12 @@ impl Ray {
 
 /// Represents a bounding box in 3D space.
 ///
+/// A `Region3` is described by its minimum and maximum corners. The `CFrame`
+/// and `Size` that scripts see can be computed from them.
+///
+/// Roblox never saves `Region3` values in model or place files, so neither
+/// rbx_binary nor rbx_xml can encode them.
+///
 /// ## See Also
 /// * [`Region3int16`](struct.Region3int16.html)
 /// * [Region3 on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Region3)
@@ -231,6 +575,9 @@ impl Region3 {
 /// A version of [`Region3`][Region3] that uses signed 16-bit integers instead
 /// of floats. `Region3int16` is generally used in Terrain APIs.
 ///
+/// Like `Region3`, Roblox never saves `Region3int16` values in model or place
+/// files.
+///
 /// ## See Also
 /// * [`Region`][Region3]
 /// * [Region3int16 on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Region3int16)
@@ -417,6 +764,243 @@ serde_tuple! {
     Matrix3(x: Vector3, y: Vector3, z: Vector3),
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn identity_is_orthonormal() {
+        let cframe = CFrame::new(Vector3::new(1.0, 2.0, 3.0), Matrix3::identity());
+
+        assert!(cframe.is_orthonormal(1e-6));
+        assert_eq!(cframe.orthonormalize(), cframe);
+    }
+
+    #[test]
+    fn orthonormalize_skewed() {
+        let position = Vector3::new(5.0, -2.0, 10.0);
+        let cframe = CFrame::new(
+            position,
+            Matrix3 {
+                x: Vector3::new(2.0, 0.1, 0.0),
+                y: Vector3::new(0.0, 1.0, 0.05),
+                z: Vector3::new(0.02, 0.0, 0.9),
+            },
+        );
+
+        assert!(!cframe.is_orthonormal(1e-4));
+
+        let fixed = cframe.orthonormalize();
+        assert!(fixed.is_orthonormal(1e-5));
+        assert_eq!(fixed.position, position);
+
+        // The result should still be a rotation, not a reflection.
+        let columns = fixed.orientation.transpose();
+        let handedness = columns.x.cross(columns.y).dot(columns.z);
+        assert!((handedness - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn orthonormalize_degenerate() {
+        let cframe = CFrame::new(
+            Vector3::new(1.0, 1.0, 1.0),
+            Matrix3 {
+                x: Vector3::new(0.0, 0.0, 0.0),
+                y: Vector3::new(0.0, 0.0, 0.0),
+                z: Vector3::new(0.0, 0.0, 0.0),
+            },
+        );
+
+        assert_eq!(cframe.orthonormalize().orientation, Matrix3::identity());
+    }
+
+    fn assert_vector3_near(actual: Vector3, expected: Vector3) {
+        let close = (actual.x - expected.x).abs() < 1e-6
+            && (actual.y - expected.y).abs() < 1e-6
+            && (actual.z - expected.z).abs() < 1e-6;
+
+        assert!(close, "expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn from_position() {
+        let position = Vector3::new(1.0, 2.0, 3.0);
+        let cframe = CFrame::from_position(position);
+
+        assert_eq!(cframe, CFrame::new(position, Matrix3::identity()));
+        assert_eq!(cframe.right_vector(), Vector3::new(1.0, 0.0, 0.0));
+        assert_eq!(cframe.up_vector(), Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(cframe.look_vector(), Vector3::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn from_position_and_angles() {
+        use std::f32::consts::FRAC_PI_2;
+
+        let position = Vector3::new(5.0, 0.0, -5.0);
+
+        // Each case lists the rows of CFrame.Angles(rx, ry, rz) from Roblox.
+        let cases = [
+            (
+                (FRAC_PI_2, 0.0, 0.0),
+                [(1.0, 0.0, 0.0), (0.0, 0.0, -1.0), (0.0, 1.0, 0.0)],
+            ),
+            (
+                (0.0, FRAC_PI_2, 0.0),
+                [(0.0, 0.0, 1.0), (0.0, 1.0, 0.0), (-1.0, 0.0, 0.0)],
+            ),
+            (
+                (0.0, 0.0, FRAC_PI_2),
+                [(0.0, -1.0, 0.0), (1.0, 0.0, 0.0), (0.0, 0.0, 1.0)],
+            ),
+            (
+                (FRAC_PI_2, FRAC_PI_2, 0.0),
+                [(0.0, 0.0, 1.0), (1.0, 0.0, 0.0), (0.0, 1.0, 0.0)],
+            ),
+        ];
+
+        for &((rx, ry, rz), rows) in &cases {
+            let cframe = CFrame::from_position_and_angles(position, rx, ry, rz);
+            assert_eq!(cframe.position, position);
+
+            let row = |(x, y, z)| Vector3::new(x, y, z);
+            assert_vector3_near(cframe.orientation.x, row(rows[0]));
+            assert_vector3_near(cframe.orientation.y, row(rows[1]));
+            assert_vector3_near(cframe.orientation.z, row(rows[2]));
+            assert!(cframe.is_orthonormal(1e-6));
+        }
+
+        // Turning 90 degrees around the Y axis faces the CFrame towards -X.
+        let turned = CFrame::from_position_and_angles(position, 0.0, FRAC_PI_2, 0.0);
+        assert_vector3_near(turned.look_vector(), Vector3::new(-1.0, 0.0, 0.0));
+        assert_vector3_near(turned.right_vector(), Vector3::new(0.0, 0.0, -1.0));
+        assert_vector3_near(turned.up_vector(), Vector3::new(0.0, 1.0, 0.0));
+
+        // Pitching up 90 degrees around the X axis faces the CFrame upwards.
+        let pitched = CFrame::from_position_and_angles(position, FRAC_PI_2, 0.0, 0.0);
+        assert_vector3_near(pitched.look_vector(), Vector3::new(0.0, 1.0, 0.0));
+        assert_vector3_near(pitched.up_vector(), Vector3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn color3_to_color3uint8() {
+        let convert = |value: f32| Color3uint8::from(Color3::new(value, value, value)).r;
+
+        assert_eq!(convert(0.0), 0);
+        assert_eq!(convert(1.0), 255);
+        assert_eq!(convert(-0.01), 0);
+        assert_eq!(convert(1.01), 255);
+        assert_eq!(convert(std::f32::NAN), 0);
+        assert_eq!(convert(0.2), 51);
+
+        // 0.5 * 255 is exactly 127.5, which rounds to the even 128.
+        assert_eq!(convert(0.5), 128);
+        assert_eq!(round_half_to_even(2.5), 2.0);
+        assert_eq!(round_half_to_even(3.5), 4.0);
+        assert_eq!(round_half_to_even(254.5), 254.0);
+        assert_eq!(round_half_to_even(2.6), 3.0);
+
+        assert_eq!(
+            Color3uint8::from(Color3::new(1.5, -1.0, 0.2)),
+            Color3uint8::new(255, 0, 51)
+        );
+    }
+
+    #[test]
+    fn color3uint8_to_color3() {
+        assert_eq!(
+            Color3::from(Color3uint8::new(0, 255, 51)),
+            Color3::new(0.0, 1.0, 0.2)
+        );
+        assert_eq!(Color3::from_rgb(255, 0, 51), Color3::new(1.0, 0.0, 0.2));
+
+        for channel in 0..=255 {
+            let color = Color3uint8::new(channel, channel, channel);
+            assert_eq!(Color3uint8::from(Color3::from(color)), color);
+        }
+    }
+
+    #[test]
+    fn color3_lerp() {
+        let from = Color3::new(0.1, 0.5, 1.0);
+        let to = Color3::new(0.7, 0.5, 0.0);
+
+        assert_eq!(from.lerp(to, 0.0), from);
+        assert_eq!(from.lerp(to, 1.0), to);
+
+        let halfway = from.lerp(to, 0.5);
+        assert!((halfway.r - 0.4).abs() < 1e-6);
+        assert_eq!(halfway.g, 0.5);
+        assert_eq!(halfway.b, 0.5);
+
+        assert_eq!(from.lerp(to, -1.0), from);
+        assert_eq!(from.lerp(to, 2.0), to);
+    }
+
+    #[test]
+    fn color3uint8_lerp() {
+        let from = Color3uint8::new(0, 100, 255);
+        let to = Color3uint8::new(255, 200, 255);
+
+        assert_eq!(from.lerp(to, 0.0), from);
+        assert_eq!(from.lerp(to, 1.0), to);
+        assert_eq!(from.lerp(to, 0.5), Color3uint8::new(128, 150, 255));
+        assert_eq!(from.lerp(to, 5.0), to);
+    }
+
+    #[test]
+    fn vector_arithmetic() {
+        let a = Vector3::new(1.0, 2.0, 3.0);
+        let b = Vector3::new(4.0, -5.0, 6.0);
+
+        assert_eq!(a + b, Vector3::new(5.0, -3.0, 9.0));
+        assert_eq!(a - b, Vector3::new(-3.0, 7.0, -3.0));
+        assert_eq!(a * 2.0, Vector3::new(2.0, 4.0, 6.0));
+        assert_eq!(-a, Vector3::new(-1.0, -2.0, -3.0));
+        assert_eq!(a.dot(b), 12.0);
+        assert_eq!(Vector3::new(2.0, 3.0, 6.0).magnitude(), 7.0);
+
+        let c = Vector2::new(3.0, 4.0);
+        let d = Vector2::new(1.0, -2.0);
+
+        assert_eq!(c + d, Vector2::new(4.0, 2.0));
+        assert_eq!(c - d, Vector2::new(2.0, 6.0));
+        assert_eq!(c * 0.5, Vector2::new(1.5, 2.0));
+        assert_eq!(-c, Vector2::new(-3.0, -4.0));
+        assert_eq!(c.dot(d), -5.0);
+        assert_eq!(c.magnitude(), 5.0);
+        assert_eq!(c.unit(), Vector2::new(0.6, 0.8));
+    }
+
+    #[test]
+    fn cross_is_right_handed() {
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        let y = Vector3::new(0.0, 1.0, 0.0);
+        let z = Vector3::new(0.0, 0.0, 1.0);
+
+        assert_eq!(x.cross(y), z);
+        assert_eq!(y.cross(z), x);
+        assert_eq!(z.cross(x), y);
+        assert_eq!(y.cross(x), -z);
+    }
+
+    #[test]
+    fn unit() {
+        assert_eq!(
+            Vector3::new(0.0, 3.0, 4.0).unit(),
+            Vector3::new(0.0, 0.6, 0.8)
+        );
+
+        // The zero vector has no direction, so it's returned as-is instead of
+        // dividing by zero.
+        let zero = Vector3::new(0.0, 0.0, 0.0);
+        assert_eq!(zero.unit(), zero);
+
+        let zero = Vector2::new(0.0, 0.0);
+        assert_eq!(zero.unit(), zero);
+    }
+}
+
 #[cfg(all(test, feature = "serde"))]
 mod serde_test {
     use super::*;
@@ -427,4 +1011,30 @@ mod serde_test {
 
         assert_eq!(out, "[2.0,3.5]");
     }
+
+    #[test]
+    fn region3_json() {
+        let region = Region3::new(Vector3::new(-1.5, 0.0, 2.0), Vector3::new(4.0, 8.0, 16.5));
+        let out = serde_json::to_string(&region).unwrap();
+
+        assert_eq!(out, "[[-1.5,0.0,2.0],[4.0,8.0,16.5]]");
+        assert_eq!(serde_json::from_str::<Region3>(&out).unwrap(), region);
+    }
+
+    #[test]
+    fn region3int16_json() {
+        let region = Region3int16::new(
+            Vector3int16::new(std::i16::MIN, -1, 0),
+            Vector3int16::new(std::i16::MAX, 1, -300),
+        );
+        let out = serde_json::to_string(&region).unwrap();
+
+        assert_eq!(out, "[[-32768,-1,0],[32767,1,-300]]");
+        assert_eq!(serde_json::from_str::<Region3int16>(&out).unwrap(), region);
+
+        // Components outside of the range of i16 are rejected instead of
+        // wrapping around.
+        assert!(serde_json::from_str::<Region3int16>("[[-32769,0,0],[0,0,0]]").is_err());
+        assert!(serde_json::from_str::<Region3int16>("[[0,0,0],[32768,0,0]]").is_err());
+    }
 }
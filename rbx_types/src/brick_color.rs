@@ -1,5 +1,7 @@
 use std::fmt;
 
+use crate::{Color3, Color3uint8};
+
 macro_rules! make_brick_color {
     ({
         $([
@@ -62,6 +64,48 @@ macro_rules! make_brick_color {
                     _ => None,
                 }
             }
+
+            /// Returns the associated value of this BrickColor, which can be
+            /// turned back into a BrickColor with `from_number`.
+            ///
+            /// This is equivalent to `BrickColor.Number` from within Roblox.
+            pub fn to_number(self) -> u16 {
+                self as u16
+            }
+
+            /// Returns the name of this BrickColor, like `Bright red`.
+            ///
+            /// This is equivalent to `BrickColor.Name` from within Roblox.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(
+                        BrickColor::$enum => $name,
+                    )+
+                }
+            }
+
+            /// Returns the color that this BrickColor represents with 8 bits
+            /// per channel, which is how Roblox defines its palette.
+            pub fn to_color3uint8(self) -> Color3uint8 {
+                match self {
+                    $(
+                        BrickColor::$enum => Color3uint8::new($color3_r, $color3_g, $color3_b),
+                    )+
+                }
+            }
+
+            /// Returns the color that this BrickColor represents.
+            ///
+            /// This is equivalent to `BrickColor.Color` from within Roblox.
+            pub fn to_color3(self) -> Color3 {
+                let color = self.to_color3uint8();
+
+                Color3::new(
+                    color.r as f32 / 255.0,
+                    color.g as f32 / 255.0,
+                    color.b as f32 / 255.0,
+                )
+            }
         }
 
         impl fmt::Display for BrickColor {
@@ -335,6 +379,23 @@ mod tests {
     fn from_number() {
         assert_eq!(BrickColor::from_number(1030), Some(BrickColor::PastelBrown));
     }
+
+    #[test]
+    fn lookups() {
+        let value = BrickColor::from_number(21).unwrap();
+
+        assert_eq!(value, BrickColor::BrightRed);
+        assert_eq!(value.to_number(), 21);
+        assert_eq!(value.name(), "Bright red");
+        assert_eq!(value.to_color3uint8(), Color3uint8::new(196, 40, 28));
+        assert_eq!(
+            value.to_color3(),
+            Color3::new(196.0 / 255.0, 40.0 / 255.0, 28.0 / 255.0)
+        );
+
+        assert_eq!(BrickColor::from_name(value.name()), Some(value));
+        assert_eq!(BrickColor::from_number(0), None);
+    }
 }
 
 #[cfg(all(test, feature = "serde"))]
@@ -0,0 +1,173 @@
+/// The weight of a [`Font`][Font], given as one of the standard numeric
+/// weights used by OpenType fonts.
+///
+/// ## See Also
+/// * [FontWeight on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/enum/FontWeight)
+///
+/// [Font]: struct.Font.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u16)]
+pub enum FontWeight {
+    Thin = 100,
+    ExtraLight = 200,
+    Light = 300,
+    Regular = 400,
+    Medium = 500,
+    SemiBold = 600,
+    Bold = 700,
+    ExtraBold = 800,
+    Heavy = 900,
+}
+
+impl FontWeight {
+    /// Finds the `FontWeight` with the given numeric weight, if it exists.
+    pub fn from_u16(value: u16) -> Option<Self> {
+        Some(match value {
+            100 => Self::Thin,
+            200 => Self::ExtraLight,
+            300 => Self::Light,
+            400 => Self::Regular,
+            500 => Self::Medium,
+            600 => Self::SemiBold,
+            700 => Self::Bold,
+            800 => Self::ExtraBold,
+            900 => Self::Heavy,
+            _ => return None,
+        })
+    }
+
+    pub fn to_u16(self) -> u16 {
+        self as u16
+    }
+}
+
+/// The style of a [`Font`][Font].
+///
+/// ## See Also
+/// * [FontStyle on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/enum/FontStyle)
+///
+/// [Font]: struct.Font.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum FontStyle {
+    Normal = 0,
+    Italic = 1,
+}
+
+impl FontStyle {
+    /// Finds the `FontStyle` with the given numeric value, if it exists.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Some(match value {
+            0 => Self::Normal,
+            1 => Self::Italic,
+            _ => return None,
+        })
+    }
+
+    pub fn to_u8(self) -> u8 {
+        self as u8
+    }
+}
+
+/// A font face, made up of a font family, a weight, and a style. This is used
+/// by properties like `TextLabel.FontFace`.
+///
+/// ## See Also
+/// * [Font on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/datatype/Font)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "PascalCase")
+)]
+pub struct Font {
+    /// The asset URI of the font family, like
+    /// `rbxasset://fonts/families/SourceSansPro.json`.
+    pub family: String,
+    pub weight: FontWeight,
+    pub style: FontStyle,
+
+    /// The asset URI of the specific face that Roblox resolved this font to
+    /// when it was last saved, if any.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cached_face_id: Option<String>,
+}
+
+impl Font {
+    pub fn new(family: &str, weight: FontWeight, style: FontStyle) -> Self {
+        Self {
+            family: family.to_owned(),
+            weight,
+            style,
+            cached_face_id: None,
+        }
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self::new(
+            "rbxasset://fonts/families/SourceSansPro.json",
+            FontWeight::Regular,
+            FontStyle::Normal,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn weight_numbers() {
+        for &weight in &[
+            FontWeight::Thin,
+            FontWeight::ExtraLight,
+            FontWeight::Light,
+            FontWeight::Regular,
+            FontWeight::Medium,
+            FontWeight::SemiBold,
+            FontWeight::Bold,
+            FontWeight::ExtraBold,
+            FontWeight::Heavy,
+        ] {
+            assert_eq!(FontWeight::from_u16(weight.to_u16()), Some(weight));
+        }
+
+        assert_eq!(FontWeight::Regular.to_u16(), 400);
+        assert_eq!(FontWeight::from_u16(450), None);
+    }
+
+    #[test]
+    fn style_numbers() {
+        assert_eq!(FontStyle::Normal.to_u8(), 0);
+        assert_eq!(FontStyle::Italic.to_u8(), 1);
+        assert_eq!(FontStyle::from_u8(2), None);
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_test {
+    use super::*;
+
+    #[test]
+    fn human() {
+        let font = Font {
+            family: "rbxasset://fonts/families/Arial.json".to_owned(),
+            weight: FontWeight::Bold,
+            style: FontStyle::Italic,
+            cached_face_id: None,
+        };
+
+        let ser = serde_json::to_string(&font).unwrap();
+        assert_eq!(
+            ser,
+            r#"{"Family":"rbxasset://fonts/families/Arial.json","Weight":"Bold","Style":"Italic","CachedFaceId":null}"#
+        );
+
+        let de: Font = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de, font);
+    }
+}
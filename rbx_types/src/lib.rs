@@ -5,25 +5,37 @@ mod serde_util;
 #[cfg(feature = "legacy-compat")]
 mod legacy_compat;
 
+mod attributes;
 mod axes;
 mod basic_types;
 mod binary_string;
 mod brick_color;
 mod content;
 mod faces;
+mod font;
 mod lister;
 mod physical_properties;
 mod referent;
+mod security_capabilities;
 mod shared_string;
+mod tags;
+mod unique_id;
+mod unknown_value;
 mod variant;
 
+pub use attributes::*;
 pub use axes::*;
 pub use basic_types::*;
 pub use binary_string::*;
 pub use brick_color::*;
 pub use content::*;
 pub use faces::*;
+pub use font::*;
 pub use physical_properties::*;
 pub use referent::*;
+pub use security_capabilities::*;
 pub use shared_string::*;
+pub use tags::*;
+pub use unique_id::*;
+pub use unknown_value::*;
 pub use variant::*;
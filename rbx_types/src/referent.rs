@@ -1,14 +1,61 @@
-use std::num::NonZeroU128;
+use std::{cell::Cell, fmt, num::NonZeroU128, str::FromStr};
 
 /// An universally unique, optional reference to a Roblox instance.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Ref(Option<NonZeroU128>);
 
+thread_local! {
+    /// The seed and number of referents generated so far while inside of
+    /// `Ref::with_seed` on this thread.
+    static SEEDED_SEQUENCE: Cell<Option<(u64, u64)>> = Cell::new(None);
+}
+
 impl Ref {
     /// Generate a new random `Ref`.
+    ///
+    /// Inside of [`Ref::with_seed`](#method.with_seed), this instead generates
+    /// the next `Ref` in that seed's sequence.
     #[inline]
     pub fn new() -> Self {
-        Ref(Some(rand::random()))
+        let seeded = SEEDED_SEQUENCE.with(|sequence| {
+            let (seed, count) = sequence.get()?;
+            sequence.set(Some((seed, count + 1)));
+
+            Some(((seed as u128) << 64) | (count as u128 + 1))
+        });
+
+        match seeded {
+            Some(value) => Ref(NonZeroU128::new(value)),
+            None => Ref(Some(rand::random())),
+        }
+    }
+
+    /// Calls `f`, making every `Ref` generated on this thread until it returns
+    /// come from a counter based on `seed` instead of being random.
+    ///
+    /// This makes referents reproducible: building the same tree inside of two
+    /// calls with the same seed produces the same referents both times, which
+    /// is useful for golden-file tests. For the same reason, instances created
+    /// under the same seed in separate calls will have conflicting referents,
+    /// so they shouldn't be put in the same `WeakDom`.
+    ///
+    /// Calls can be nested. The previous behavior of `Ref::new` is restored
+    /// when `f` returns or panics.
+    pub fn with_seed<F, T>(seed: u64, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        struct Restore(Option<(u64, u64)>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                SEEDED_SEQUENCE.with(|sequence| sequence.set(self.0));
+            }
+        }
+
+        let _restore = Restore(SEEDED_SEQUENCE.with(|sequence| sequence.replace(Some((seed, 0)))));
+
+        f()
     }
 
     /// Generate a `Ref` that points to nothing.
@@ -29,6 +76,24 @@ impl Ref {
         self.0.is_none()
     }
 
+    /// Parse a `Ref` from the 32-character hexadecimal representation produced
+    /// by its `Display` implementation. The all-zero string parses to
+    /// `Ref::none()`.
+    pub fn from_hex(input: &str) -> Result<Self, ParseRefError> {
+        if input.len() != 32 {
+            return Err(ParseRefError::InvalidLength(input.len()));
+        }
+
+        if let Some(invalid) = input.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ParseRefError::InvalidCharacter(invalid));
+        }
+
+        // Every character is a hex digit and there are exactly 32 of them, so
+        // this can't fail or overflow.
+        let value = u128::from_str_radix(input, 16).unwrap();
+        Ok(Ref(NonZeroU128::new(value)))
+    }
+
     fn value(&self) -> u128 {
         match self.0 {
             Some(value) => value.get(),
@@ -37,12 +102,54 @@ impl Ref {
     }
 }
 
+impl fmt::Display for Ref {
+    /// Formats the `Ref` as a 32-character hexadecimal string, the same
+    /// representation used by human-readable Serde formats.
+    fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
+        write!(out, "{:032x}", self.value())
+    }
+}
+
+impl FromStr for Ref {
+    type Err = ParseRefError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Ref::from_hex(input)
+    }
+}
+
+/// An error that can occur when parsing a `Ref` from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseRefError {
+    /// The input was not 32 characters long.
+    InvalidLength(usize),
+
+    /// The input contained a character that isn't a hexadecimal digit.
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for ParseRefError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRefError::InvalidLength(len) => write!(
+                formatter,
+                "Ref must be 32 hexadecimal characters long, but was {} characters",
+                len
+            ),
+            ParseRefError::InvalidCharacter(c) => {
+                write!(formatter, "Ref contained invalid character {:?}", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseRefError {}
+
 #[cfg(feature = "serde")]
 mod serde_impl {
     use super::*;
 
-    use std::fmt;
-
     use serde::{
         de::{Error, Visitor},
         Deserialize, Deserializer, Serialize, Serializer,
@@ -54,7 +161,7 @@ mod serde_impl {
             S: Serializer,
         {
             if serializer.is_human_readable() {
-                serializer.serialize_str(&format!("{:032x}", self.value()))
+                serializer.serialize_str(&self.to_string())
             } else {
                 serializer.serialize_u128(self.value())
             }
@@ -94,6 +201,65 @@ mod serde_impl {
     }
 }
 
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_round_trip() {
+        for _ in 0..16 {
+            let value = Ref::new();
+            assert_eq!(value.to_string().parse::<Ref>(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let first: Vec<_> = Ref::with_seed(7, || (0..4).map(|_| Ref::new()).collect());
+        let second: Vec<_> = Ref::with_seed(7, || (0..4).map(|_| Ref::new()).collect());
+        let other_seed: Vec<_> = Ref::with_seed(8, || (0..4).map(|_| Ref::new()).collect());
+
+        assert_eq!(first, second);
+        assert_ne!(first, other_seed);
+        assert!(first.iter().all(Ref::is_some));
+
+        let unique: std::collections::HashSet<_> = first.iter().collect();
+        assert_eq!(unique.len(), first.len());
+
+        // Nested calls restore the outer sequence when they return.
+        let (outer, inner) = Ref::with_seed(7, || {
+            let before = Ref::new();
+            let inner = Ref::with_seed(8, Ref::new);
+            (vec![before, Ref::new()], inner)
+        });
+        assert_eq!(outer[..], first[..2]);
+        assert_eq!(inner, other_seed[0]);
+
+        assert_ne!(Ref::new(), Ref::new());
+    }
+
+    #[test]
+    fn parse_none() {
+        let none = Ref::from_hex("00000000000000000000000000000000").unwrap();
+        assert!(none.is_none());
+        assert_eq!(Ref::none().to_string().parse::<Ref>(), Ok(Ref::none()));
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(Ref::from_hex(""), Err(ParseRefError::InvalidLength(0)));
+        assert_eq!(Ref::from_hex("abc"), Err(ParseRefError::InvalidLength(3)));
+        assert_eq!(
+            Ref::from_hex("0000000000000000000000000000000g"),
+            Err(ParseRefError::InvalidCharacter('g'))
+        );
+        assert_eq!(
+            Ref::from_hex("+0000000000000000000000000000000"),
+            Err(ParseRefError::InvalidCharacter('+'))
+        );
+    }
+}
+
 #[cfg(all(test, feature = "serde"))]
 mod serde_test {
     use super::*;
@@ -0,0 +1,69 @@
+/// The set of security capabilities granted to an instance and its
+/// descendants, used by the `Instance.Capabilities` property for sandboxing.
+///
+/// Roblox stores this as a 64-bit bitmask where each bit is one capability.
+/// rbx_types doesn't assign names to the bits, so they are kept as-is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct SecurityCapabilities {
+    bits: u64,
+}
+
+impl SecurityCapabilities {
+    /// Creates a `SecurityCapabilities` from its raw bitmask.
+    pub const fn from_bits(bits: u64) -> Self {
+        Self { bits }
+    }
+
+    /// Returns the raw bitmask of this `SecurityCapabilities`.
+    pub const fn bits(self) -> u64 {
+        self.bits
+    }
+
+    /// Returns whether every capability in `other` is also in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.bits & other.bits == other.bits
+    }
+}
+
+impl From<u64> for SecurityCapabilities {
+    fn from(bits: u64) -> Self {
+        Self::from_bits(bits)
+    }
+}
+
+impl From<SecurityCapabilities> for u64 {
+    fn from(value: SecurityCapabilities) -> Self {
+        value.bits
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn contains() {
+        let capabilities = SecurityCapabilities::from_bits(0b1011);
+
+        assert!(capabilities.contains(SecurityCapabilities::from_bits(0b0011)));
+        assert!(capabilities.contains(SecurityCapabilities::default()));
+        assert!(!capabilities.contains(SecurityCapabilities::from_bits(0b0100)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let capabilities = SecurityCapabilities::from_bits(0x8000_0000_0000_0001);
+
+        let serialized = serde_json::to_string(&capabilities).unwrap();
+        assert_eq!(serialized, "9223372036854775809");
+
+        let deserialized: SecurityCapabilities = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, capabilities);
+    }
+}
@@ -121,20 +121,34 @@ impl SharedStringHash {
 mod serde_impl {
     use super::*;
 
-    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 
-    // Mock implementations of traits to get things compiling. We'll need to
-    // decide how to actually serialize SharedString at some point.
+    // SharedString values are serialized by their contents, just like
+    // BinaryString. Deduplication happens again when they're deserialized.
 
     impl Serialize for SharedString {
-        fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
-            unimplemented!();
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                let encoded = base64::encode(self.data());
+
+                serializer.serialize_str(&encoded)
+            } else {
+                serializer.serialize_bytes(self.data())
+            }
         }
     }
 
     impl<'de> Deserialize<'de> for SharedString {
-        fn deserialize<D: Deserializer<'de>>(_deserializer: D) -> Result<Self, D::Error> {
-            unimplemented!();
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                let encoded = <&str>::deserialize(deserializer)?;
+                let buffer = base64::decode(encoded).map_err(D::Error::custom)?;
+
+                Ok(SharedString::new(buffer))
+            } else {
+                let buffer = <Vec<u8>>::deserialize(deserializer)?;
+                Ok(SharedString::new(buffer))
+            }
         }
     }
 }
@@ -165,3 +179,29 @@ mod test {
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_test {
+    use super::*;
+
+    #[test]
+    fn human() {
+        let value = SharedString::new(b"hello".to_vec());
+
+        let ser = serde_json::to_string(&value).unwrap();
+        assert_eq!(ser, "\"aGVsbG8=\"");
+
+        let de: SharedString = serde_json::from_str(&ser).unwrap();
+        assert_eq!(de.data(), value.data());
+    }
+
+    #[test]
+    fn non_human() {
+        let value = SharedString::new(b"world".to_vec());
+
+        let ser = bincode::serialize(&value).unwrap();
+        let de: SharedString = bincode::deserialize(&ser).unwrap();
+
+        assert_eq!(de.data(), value.data());
+    }
+}
@@ -0,0 +1,234 @@
+use std::{convert::TryFrom, fmt};
+
+/// The list of tags on an instance, used by `CollectionService`.
+///
+/// Roblox stores tags in the `Tags` property as a single buffer containing
+/// each tag separated by a null byte. Tags can't be empty or contain null
+/// bytes, since neither could be told apart from the separators.
+///
+/// ## See Also
+/// * [CollectionService on Roblox Developer Hub](https://developer.roblox.com/en-us/api-reference/class/CollectionService)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "Vec<String>", into = "Vec<String>")
+)]
+pub struct Tags {
+    members: Vec<String>,
+}
+
+impl Tags {
+    /// Creates an empty list of tags.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes tags from the null-delimited format Roblox stores them in.
+    ///
+    /// Returns `None` if the buffer can't be represented as a list of tags
+    /// that encodes back to the same bytes: if a tag isn't valid UTF-8, or if
+    /// the buffer contains an empty tag, like the ones produced by a trailing
+    /// separator. An empty buffer decodes to an empty list.
+    pub fn decode(buffer: &[u8]) -> Option<Self> {
+        let mut tags = Self::new();
+
+        if buffer.is_empty() {
+            return Some(tags);
+        }
+
+        for tag in buffer.split(|&byte| byte == 0) {
+            let tag = std::str::from_utf8(tag).ok()?;
+
+            // Duplicate tags would be dropped, which changes the encoding.
+            if tag.is_empty() || tags.contains(tag) {
+                return None;
+            }
+
+            tags.members.push(tag.to_owned());
+        }
+
+        Some(tags)
+    }
+
+    /// Encodes the tags into the null-delimited format Roblox stores them in.
+    pub fn encode(&self) -> Vec<u8> {
+        self.members.join("\0").into_bytes()
+    }
+
+    /// Adds a tag to the end of the list if it isn't already present.
+    ///
+    /// Returns an error without changing the list if the tag is empty or
+    /// contains a null byte.
+    pub fn push<S: Into<String>>(&mut self, tag: S) -> Result<(), TagError> {
+        let tag = tag.into();
+
+        if tag.is_empty() {
+            return Err(TagError::Empty);
+        }
+
+        if tag.contains('\0') {
+            return Err(TagError::ContainsNull(tag));
+        }
+
+        if !self.contains(&tag) {
+            self.members.push(tag);
+        }
+
+        Ok(())
+    }
+
+    /// Removes a tag from the list, returning whether it was present.
+    pub fn remove(&mut self, tag: &str) -> bool {
+        let len = self.members.len();
+        self.members.retain(|member| member != tag);
+        self.members.len() != len
+    }
+
+    /// Tells whether the list contains the given tag.
+    pub fn contains(&self, tag: &str) -> bool {
+        self.members.iter().any(|member| member == tag)
+    }
+
+    /// Returns an iterator over the tags in the list, in order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(String::as_str)
+    }
+
+    /// Returns the number of tags in the list.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Tells whether the list has no tags.
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+/// Builds a list of tags, skipping duplicates. Fails if any of the tags is
+/// empty or contains a null byte.
+impl TryFrom<Vec<String>> for Tags {
+    type Error = TagError;
+
+    fn try_from(members: Vec<String>) -> Result<Self, Self::Error> {
+        let mut tags = Tags::new();
+
+        for member in members {
+            tags.push(member)?;
+        }
+
+        Ok(tags)
+    }
+}
+
+impl From<Tags> for Vec<String> {
+    fn from(tags: Tags) -> Self {
+        tags.members
+    }
+}
+
+/// An error that can occur when adding a tag to [`Tags`][Tags].
+///
+/// [Tags]: struct.Tags.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TagError {
+    /// The tag was empty.
+    Empty,
+
+    /// The tag contained a null byte, which Roblox uses to separate tags.
+    ContainsNull(String),
+}
+
+impl fmt::Display for TagError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TagError::Empty => write!(formatter, "Tags cannot be empty"),
+            TagError::ContainsNull(tag) => {
+                write!(formatter, "Tag {:?} contains a null byte", tag)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TagError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tags(members: &[&str]) -> Tags {
+        let members: Vec<String> = members.iter().map(|&tag| tag.to_owned()).collect();
+        Tags::try_from(members).unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let tags = tags(&["Enemy", "Boss"]);
+
+        assert_eq!(tags.encode(), b"Enemy\0Boss");
+        assert_eq!(Tags::decode(&tags.encode()), Some(tags));
+    }
+
+    #[test]
+    fn decode_edge_cases() {
+        assert_eq!(Tags::decode(b""), Some(Tags::new()));
+        assert_eq!(Tags::new().encode(), b"");
+
+        let single = Tags::decode(b"Single").unwrap();
+        assert_eq!(single.iter().collect::<Vec<_>>(), vec!["Single"]);
+
+        // Buffers that wouldn't encode back to the same bytes aren't decoded.
+        assert_eq!(Tags::decode(b"First\0\0Second"), None);
+        assert_eq!(Tags::decode(b"First\0"), None);
+        assert_eq!(Tags::decode(b"Same\0Same"), None);
+        assert_eq!(Tags::decode(b"Bad\0\xFF"), None);
+    }
+
+    #[test]
+    fn push_and_remove() {
+        let mut tags = Tags::new();
+        tags.push("Enemy").unwrap();
+        tags.push("Boss").unwrap();
+        tags.push("Enemy").unwrap();
+
+        assert_eq!(tags.len(), 2);
+        assert!(tags.contains("Boss"));
+
+        assert!(tags.remove("Enemy"));
+        assert!(!tags.remove("Enemy"));
+        assert_eq!(tags.iter().collect::<Vec<_>>(), vec!["Boss"]);
+    }
+
+    #[test]
+    fn invalid_tags() {
+        let mut tags = Tags::new();
+        assert_eq!(tags.push(""), Err(TagError::Empty));
+        assert_eq!(
+            tags.push("Two\0Tags"),
+            Err(TagError::ContainsNull("Two\0Tags".to_owned()))
+        );
+        assert!(tags.is_empty());
+
+        assert_eq!(
+            Tags::try_from(vec!["Enemy".to_owned(), String::new()]),
+            Err(TagError::Empty)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_test {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let tags: Tags = serde_json::from_str(r#"["Enemy", "Boss", "Enemy"]"#).unwrap();
+        assert_eq!(tags.iter().collect::<Vec<_>>(), vec!["Enemy", "Boss"]);
+        assert_eq!(serde_json::to_string(&tags).unwrap(), r#"["Enemy","Boss"]"#);
+
+        assert!(serde_json::from_str::<Tags>(r#"["Enemy", ""]"#).is_err());
+        assert!(serde_json::from_str::<Tags>(r#"["Two\u0000Tags"]"#).is_err());
+    }
+}
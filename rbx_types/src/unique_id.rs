@@ -0,0 +1,133 @@
+use std::{fmt, str::FromStr};
+
+/// A unique identifier assigned to an instance by Roblox, used by properties
+/// like `Instance.UniqueId`.
+///
+/// Roblox generates these from the time the identifier was created, an index
+/// that increments for every identifier generated at that time, and a random
+/// number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "PascalCase")
+)]
+pub struct UniqueId {
+    pub index: u32,
+    pub time: u32,
+    pub random: i64,
+}
+
+impl UniqueId {
+    pub fn new(index: u32, time: u32, random: i64) -> Self {
+        Self {
+            index,
+            time,
+            random,
+        }
+    }
+
+    /// Parse a `UniqueId` from the 32-character hexadecimal representation
+    /// produced by its `Display` implementation, which is also how it's stored
+    /// in XML files.
+    pub fn from_hex(input: &str) -> Result<Self, ParseUniqueIdError> {
+        if input.len() != 32 {
+            return Err(ParseUniqueIdError::InvalidLength(input.len()));
+        }
+
+        if let Some(invalid) = input.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(ParseUniqueIdError::InvalidCharacter(invalid));
+        }
+
+        // Every character is a hex digit and there are exactly 32 of them, so
+        // these can't fail or overflow.
+        let random = u64::from_str_radix(&input[0..16], 16).unwrap();
+        let time = u32::from_str_radix(&input[16..24], 16).unwrap();
+        let index = u32::from_str_radix(&input[24..32], 16).unwrap();
+
+        Ok(Self::new(index, time, random as i64))
+    }
+}
+
+impl fmt::Display for UniqueId {
+    /// Formats the `UniqueId` as a 32-character hexadecimal string containing
+    /// the random number, time, and index, in that order.
+    fn fmt(&self, out: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            out,
+            "{:016x}{:08x}{:08x}",
+            self.random as u64, self.time, self.index
+        )
+    }
+}
+
+impl FromStr for UniqueId {
+    type Err = ParseUniqueIdError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        UniqueId::from_hex(input)
+    }
+}
+
+/// An error that can occur when parsing a `UniqueId` from a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseUniqueIdError {
+    /// The input was not 32 characters long.
+    InvalidLength(usize),
+
+    /// The input contained a character that isn't a hexadecimal digit.
+    InvalidCharacter(char),
+}
+
+impl fmt::Display for ParseUniqueIdError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseUniqueIdError::InvalidLength(len) => write!(
+                formatter,
+                "UniqueId must be 32 hexadecimal characters long, but was {} characters",
+                len
+            ),
+            ParseUniqueIdError::InvalidCharacter(c) => {
+                write!(formatter, "UniqueId contained invalid character {:?}", c)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseUniqueIdError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let value = UniqueId::new(0x0102_0304, 0x0a0b_0c0d, -2);
+        assert_eq!(value.to_string(), "fffffffffffffffe0a0b0c0d01020304");
+    }
+
+    #[test]
+    fn parse_round_trip() {
+        for &value in &[
+            UniqueId::new(0, 0, 0),
+            UniqueId::new(1, 2, 3),
+            UniqueId::new(std::u32::MAX, std::u32::MAX, std::i64::MIN),
+            UniqueId::new(0x0048_15fc, 0x02e9_c68d, 0x44b1_88da_ce63_2b47),
+        ] {
+            assert_eq!(value.to_string().parse::<UniqueId>(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert_eq!(
+            UniqueId::from_hex("abc"),
+            Err(ParseUniqueIdError::InvalidLength(3))
+        );
+        assert_eq!(
+            UniqueId::from_hex("0000000000000000000000000000000g"),
+            Err(ParseUniqueIdError::InvalidCharacter('g'))
+        );
+    }
+}
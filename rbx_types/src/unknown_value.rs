@@ -0,0 +1,52 @@
+use crate::BinaryString;
+
+/// The raw contents of a property whose type couldn't be decoded, kept so that
+/// the property can be written back out unchanged.
+///
+/// `type_id` and `data` are only meaningful to the format the value was read
+/// from. rbx_binary, for example, stores the type ID from the file's `PROP`
+/// chunk and this instance's share of the chunk's bytes.
+///
+/// Formats that store properties column-wise can't always split a column into
+/// values for each instance, so they also record where in the column the value
+/// came from. A column can only be written back out if it's made of exactly
+/// the same values, in the same order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "PascalCase")
+)]
+pub struct UnknownValue {
+    pub type_id: u8,
+    pub data: BinaryString,
+
+    /// The position of this value in the column it was read from.
+    pub column_index: usize,
+
+    /// The number of values in the column this value was read from.
+    pub column_len: usize,
+}
+
+impl UnknownValue {
+    /// Creates an `UnknownValue` that makes up an entire column by itself.
+    pub fn new(type_id: u8, data: BinaryString) -> Self {
+        Self::in_column(type_id, data, 0, 1)
+    }
+
+    /// Creates an `UnknownValue` that was read from position `column_index` of
+    /// a column holding `column_len` values.
+    pub fn in_column(
+        type_id: u8,
+        data: BinaryString,
+        column_index: usize,
+        column_len: usize,
+    ) -> Self {
+        Self {
+            type_id,
+            data,
+            column_index,
+            column_len,
+        }
+    }
+}
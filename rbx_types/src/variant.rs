@@ -1,7 +1,11 @@
+use std::mem;
+
 use crate::{
-    Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence, Content, EnumValue,
-    Faces, NumberRange, NumberSequence, PhysicalProperties, Ray, Rect, Ref, Region3, Region3int16,
-    SharedString, UDim, UDim2, Vector2, Vector2int16, Vector3, Vector3int16,
+    Attributes, Axes, BinaryString, BrickColor, CFrame, Color3, Color3uint8, ColorSequence,
+    ColorSequenceKeypoint, Content, EnumItem, EnumValue, Faces, Font, NumberRange, NumberSequence,
+    NumberSequenceKeypoint, PhysicalProperties, Ray, Rect, Ref, Region3, Region3int16,
+    SecurityCapabilities, SharedString, Tags, UDim, UDim2, UniqueId, UnknownValue, Vector2,
+    Vector2int16, Vector3, Vector3int16,
 };
 
 /// Reduces boilerplate from listing different values of Variant by wrapping
@@ -36,6 +40,12 @@ macro_rules! make_variant {
                     )*
                 }
             }
+
+            /// Returns the name of the type of this value, like `"CFrame"`.
+            /// See [`VariantType::name`](enum.VariantType.html#method.name).
+            pub fn type_name(&self) -> &'static str {
+                self.ty().name()
+            }
         }
 
         $(
@@ -44,6 +54,15 @@ macro_rules! make_variant {
                     Self::$variant_name(value)
                 }
             }
+
+            impl FromVariant for $inner_type {
+                fn from_variant(variant: &Variant) -> Option<Self> {
+                    match variant {
+                        Variant::$variant_name(value) => Some(value.clone()),
+                        _ => None,
+                    }
+                }
+            }
         )*
 
         /// Represents any type that can be held in a `Variant`.
@@ -59,6 +78,19 @@ macro_rules! make_variant {
             )*
         }
 
+        impl VariantType {
+            /// Returns the name of this type, like `"CFrame"` or `"Ref"`. This
+            /// is the same name used when `VariantType` is serialized, which is
+            /// how the reflection database refers to types.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(
+                        VariantType::$variant_name => stringify!($variant_name),
+                    )*
+                }
+            }
+        }
+
         #[cfg(test)]
         mod generated_test {
             use super::*;
@@ -74,10 +106,54 @@ macro_rules! make_variant {
 
                 $( trait_test::<$inner_type>(); )*
             }
+
+            /// Every type represented in `Variant` should also be readable
+            /// back out of one with `FromVariant`.
+            #[allow(dead_code)]
+            fn from_variant_is_exhaustive() {
+                fn trait_test<T: FromVariant>() {}
+
+                $( trait_test::<$inner_type>(); )*
+            }
+
+            /// Every type should have a distinct, non-empty name.
+            #[test]
+            fn names_are_unique() {
+                let all_types = [$( VariantType::$variant_name, )*];
+                let mut seen = std::collections::HashSet::new();
+
+                for ty in all_types.iter() {
+                    let name = ty.name();
+                    assert!(!name.is_empty());
+                    assert!(seen.insert(name), "duplicate type name {}", name);
+                }
+            }
+
+            #[test]
+            fn type_name() {
+                assert_eq!(Variant::String(String::new()).type_name(), "String");
+                assert_eq!(Variant::Ref(Ref::none()).type_name(), "Ref");
+                assert_eq!(Variant::OptionalCFrame(None).type_name(), "OptionalCFrame");
+            }
+
+            /// Type names should match the names used when serializing
+            /// `VariantType`.
+            #[cfg(feature = "serde")]
+            #[test]
+            fn names_match_serde() {
+                let all_types = [$( VariantType::$variant_name, )*];
+
+                for ty in all_types.iter() {
+                    let serialized = serde_json::to_string(ty).unwrap();
+                    assert_eq!(serialized, format!("\"{}\"", ty.name()));
+                }
+            }
         }
     };
 }
 
+// The reflection database refers to variants by their index, so new variants
+// must be added to the end of this list to keep existing databases readable.
 make_variant! {
     Axes(Axes),
     BinaryString(BinaryString),
@@ -110,6 +186,24 @@ make_variant! {
     Vector2int16(Vector2int16),
     Vector3(Vector3),
     Vector3int16(Vector3int16),
+    Font(Font),
+    Attributes(Attributes),
+    OptionalCFrame(Option<CFrame>),
+    UniqueId(UniqueId),
+    Tags(Tags),
+    Unknown(UnknownValue),
+    SecurityCapabilities(SecurityCapabilities),
+    EnumItem(EnumItem),
+}
+
+/// Types that can be read out of a [`Variant`](enum.Variant.html) holding
+/// exactly that type. This is implemented for the type inside every variant.
+///
+/// No conversions are done between types, so reading an `f64` out of a
+/// `Float32` value returns `None`.
+pub trait FromVariant: Sized {
+    /// Returns a copy of the value if `variant` holds a value of this type.
+    fn from_variant(variant: &Variant) -> Option<Self>;
 }
 
 impl From<&'_ str> for Variant {
@@ -118,6 +212,364 @@ impl From<&'_ str> for Variant {
     }
 }
 
+/// Accessors for the value inside of a `Variant` when its type is already
+/// known, similar to the ones on `serde_json::Value`.
+///
+/// Each accessor returns `None` if the `Variant` holds a different type. No
+/// conversions are done between types, so `as_f64` returns `None` for a
+/// `Float32` value. Accessors for types that are cheap to copy return them by
+/// value, while others borrow from the `Variant`.
+impl Variant {
+    /// Returns the value if this is a `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Variant::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an `Int32`.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Variant::Int32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an `Int64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Variant::Int64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `Float32`.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Variant::Float32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `Float64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Variant::Float64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string if this is a `String`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Variant::String(value) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes if this is a `BinaryString`.
+    pub fn as_binary_string(&self) -> Option<&[u8]> {
+        match self {
+            Variant::BinaryString(value) => Some(value.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `Content`.
+    pub fn as_content(&self) -> Option<&Content> {
+        match self {
+            Variant::Content(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `SharedString`.
+    pub fn as_shared_string(&self) -> Option<&SharedString> {
+        match self {
+            Variant::SharedString(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `Vector2`.
+    pub fn as_vector2(&self) -> Option<Vector2> {
+        match self {
+            Variant::Vector2(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `Vector3`.
+    pub fn as_vector3(&self) -> Option<Vector3> {
+        match self {
+            Variant::Vector3(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `CFrame`.
+    pub fn as_cframe(&self) -> Option<CFrame> {
+        match self {
+            Variant::CFrame(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is a `Color3`.
+    pub fn as_color3(&self) -> Option<Color3> {
+        match self {
+            Variant::Color3(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an `UDim`.
+    pub fn as_udim(&self) -> Option<UDim> {
+        match self {
+            Variant::UDim(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an `UDim2`.
+    pub fn as_udim2(&self) -> Option<UDim2> {
+        match self {
+            Variant::UDim2(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an `EnumValue`.
+    pub fn as_enum(&self) -> Option<EnumValue> {
+        match self {
+            Variant::EnumValue(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the referent if this is a `Ref`.
+    ///
+    /// This isn't named `as_ref` to avoid confusion with `AsRef::as_ref`.
+    pub fn as_referent(&self) -> Option<Ref> {
+        match self {
+            Variant::Ref(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if this is an `Attributes`.
+    pub fn as_attributes(&self) -> Option<&Attributes> {
+        match self {
+            Variant::Attributes(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl Variant {
+    /// Returns an estimate of the number of bytes of heap memory owned by this
+    /// value, not counting the size of the `Variant` itself. This is meant for
+    /// finding out what's using memory in a large DOM.
+    ///
+    /// This is only an estimate. It counts the capacity of strings and lists
+    /// where that's known and their length otherwise, and it doesn't include
+    /// the overhead of the allocator or of maps. `SharedString` values count
+    /// their whole contents even though they're shared with every other copy
+    /// of the same value, so adding up many of them overestimates.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            Variant::String(value) => value.capacity(),
+            Variant::BinaryString(value) => AsRef::<Vec<u8>>::as_ref(value).capacity(),
+            Variant::Content(value) => AsRef::<String>::as_ref(value).capacity(),
+            Variant::SharedString(value) => value.data().len(),
+            Variant::ColorSequence(value) => {
+                value.keypoints.capacity() * mem::size_of::<ColorSequenceKeypoint>()
+            }
+            Variant::NumberSequence(value) => {
+                value.keypoints.capacity() * mem::size_of::<NumberSequenceKeypoint>()
+            }
+            Variant::Font(value) => value.family.capacity(),
+            Variant::Attributes(value) => value
+                .iter()
+                .map(|(key, value)| key.capacity() + mem::size_of::<Variant>() + value.heap_size())
+                .sum(),
+            Variant::Tags(value) => value
+                .iter()
+                .map(|tag| mem::size_of::<String>() + tag.len())
+                .sum(),
+            Variant::Unknown(value) => AsRef::<Vec<u8>>::as_ref(&value.data).capacity(),
+            Variant::EnumItem(value) => value.enum_name.capacity(),
+
+            Variant::Axes(_)
+            | Variant::Bool(_)
+            | Variant::BrickColor(_)
+            | Variant::CFrame(_)
+            | Variant::Color3(_)
+            | Variant::Color3uint8(_)
+            | Variant::EnumValue(_)
+            | Variant::Faces(_)
+            | Variant::Float32(_)
+            | Variant::Float64(_)
+            | Variant::Int32(_)
+            | Variant::Int64(_)
+            | Variant::NumberRange(_)
+            | Variant::PhysicalProperties(_)
+            | Variant::Ray(_)
+            | Variant::Rect(_)
+            | Variant::Ref(_)
+            | Variant::Region3(_)
+            | Variant::Region3int16(_)
+            | Variant::UDim(_)
+            | Variant::UDim2(_)
+            | Variant::Vector2(_)
+            | Variant::Vector2int16(_)
+            | Variant::Vector3(_)
+            | Variant::Vector3int16(_)
+            | Variant::OptionalCFrame(_)
+            | Variant::UniqueId(_)
+            | Variant::SecurityCapabilities(_) => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod heap_size_test {
+    use super::*;
+
+    #[test]
+    fn inline_values() {
+        assert_eq!(Variant::Bool(true).heap_size(), 0);
+        assert_eq!(Variant::Vector3(Vector3::new(1.0, 2.0, 3.0)).heap_size(), 0);
+        assert_eq!(Variant::Ref(Ref::new()).heap_size(), 0);
+    }
+
+    #[test]
+    fn owned_values() {
+        let mut value = String::with_capacity(64);
+        value.push_str("hello");
+        assert_eq!(Variant::String(value).heap_size(), 64);
+
+        let binary = Variant::BinaryString(b"hello".to_vec().into());
+        assert!(binary.heap_size() >= 5);
+
+        let shared = Variant::SharedString(SharedString::new(vec![0; 100]));
+        assert_eq!(shared.heap_size(), 100);
+
+        let keypoints = vec![
+            NumberSequenceKeypoint::new(0.0, 1.0, 0.0),
+            NumberSequenceKeypoint::new(1.0, 0.0, 0.0),
+        ];
+        let sequence = Variant::NumberSequence(NumberSequence { keypoints });
+        assert!(sequence.heap_size() >= 2 * mem::size_of::<NumberSequenceKeypoint>());
+    }
+
+    #[test]
+    fn nested_values() {
+        let mut attributes = Attributes::new();
+        attributes.insert("Name".to_owned(), Variant::String("x".repeat(100)));
+        attributes.insert(
+            "Color".to_owned(),
+            Variant::Color3(Color3::new(1.0, 0.0, 0.0)),
+        );
+
+        let size = Variant::Attributes(attributes).heap_size();
+        assert!(size >= 100 + 2 * mem::size_of::<Variant>());
+    }
+}
+
+#[cfg(test)]
+mod accessor_test {
+    use super::*;
+
+    use crate::Matrix3;
+
+    #[test]
+    fn accessors_match_types() {
+        type Accessor = fn(&Variant) -> bool;
+
+        let cases: &[(Variant, Accessor)] = &[
+            (Variant::Bool(true), |v| v.as_bool().is_some()),
+            (Variant::Int32(5), |v| v.as_i32().is_some()),
+            (Variant::Int64(5), |v| v.as_i64().is_some()),
+            (Variant::Float32(5.0), |v| v.as_f32().is_some()),
+            (Variant::Float64(5.0), |v| v.as_f64().is_some()),
+            (Variant::String("hello".to_owned()), |v| {
+                v.as_str().is_some()
+            }),
+            (Variant::BinaryString(b"hello".to_vec().into()), |v| {
+                v.as_binary_string().is_some()
+            }),
+            (Variant::Content("rbxassetid://1".into()), |v| {
+                v.as_content().is_some()
+            }),
+            (
+                Variant::SharedString(SharedString::new(b"hello".to_vec())),
+                |v| v.as_shared_string().is_some(),
+            ),
+            (Variant::Vector2(Vector2::new(1.0, 2.0)), |v| {
+                v.as_vector2().is_some()
+            }),
+            (Variant::Vector3(Vector3::new(1.0, 2.0, 3.0)), |v| {
+                v.as_vector3().is_some()
+            }),
+            (
+                Variant::CFrame(CFrame::new(
+                    Vector3::new(1.0, 2.0, 3.0),
+                    Matrix3::identity(),
+                )),
+                |v| v.as_cframe().is_some(),
+            ),
+            (Variant::Color3(Color3::new(1.0, 0.5, 0.0)), |v| {
+                v.as_color3().is_some()
+            }),
+            (Variant::UDim(UDim::new(0.5, 10)), |v| v.as_udim().is_some()),
+            (
+                Variant::UDim2(UDim2::new(UDim::new(0.5, 10), UDim::new(1.0, -5))),
+                |v| v.as_udim2().is_some(),
+            ),
+            (Variant::EnumValue(EnumValue::from_u32(3)), |v| {
+                v.as_enum().is_some()
+            }),
+            (Variant::Ref(Ref::new()), |v| v.as_referent().is_some()),
+            (Variant::Attributes(Attributes::new()), |v| {
+                v.as_attributes().is_some()
+            }),
+        ];
+
+        for (i, (_, accessor)) in cases.iter().enumerate() {
+            for (j, (value, _)) in cases.iter().enumerate() {
+                assert_eq!(
+                    accessor(value),
+                    i == j,
+                    "accessor {} returned the wrong result for {:?}",
+                    i,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn accessors_return_values() {
+        let referent = Ref::new();
+
+        assert_eq!(Variant::Bool(true).as_bool(), Some(true));
+        assert_eq!(Variant::Float64(2.5).as_f64(), Some(2.5));
+        assert_eq!(Variant::Float32(2.5).as_f64(), None);
+        assert_eq!(Variant::from("hello").as_str(), Some("hello"));
+        assert_eq!(
+            Variant::BinaryString(b"bytes".to_vec().into()).as_binary_string(),
+            Some(&b"bytes"[..])
+        );
+        assert_eq!(
+            Variant::Vector3(Vector3::new(1.0, 2.0, 3.0)).as_vector3(),
+            Some(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(Variant::Ref(referent).as_referent(), Some(referent));
+    }
+}
+
 #[cfg(all(test, feature = "serde"))]
 mod serde_test {
     use super::*;
@@ -19,6 +19,9 @@ enum Subcommand {
 
     /// View a binary file as an undefined text representation.
     ViewBinary { input: PathBuf },
+
+    /// Summarize the instances and property types in a binary file.
+    Stats { input: PathBuf },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,6 +48,7 @@ fn run(options: Options) -> anyhow::Result<()> {
     match options.subcommand {
         Subcommand::Convert { input, output } => convert(&input, &output)?,
         Subcommand::ViewBinary { input } => view_binary(&input)?,
+        Subcommand::Stats { input } => stats(&input)?,
     }
 
     Ok(())
@@ -109,6 +113,24 @@ fn view_binary(input_path: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+fn stats(input_path: &Path) -> anyhow::Result<()> {
+    let input_kind = ModelKind::from_path(input_path)?;
+
+    if input_kind != ModelKind::Binary {
+        bail!("not a binary model or place file: {}", input_path.display());
+    }
+
+    let input_file = BufReader::new(File::open(input_path)?);
+
+    let stats = rbx_binary::text_format::ModelStats::from_reader(input_file);
+
+    let stdout = io::stdout();
+    let output = BufWriter::new(stdout.lock());
+    serde_yaml::to_writer(output, &stats)?;
+
+    Ok(())
+}
+
 fn main() {
     let options = Options::from_args();
 
@@ -2,7 +2,9 @@
 
 #![allow(unused)]
 
-use rbx_dom_weak::types::{Variant, VariantType};
+use std::convert::TryFrom;
+
+use rbx_dom_weak::types::{BinaryString, BrickColor, Variant, VariantType};
 
 pub enum TodoValueConversionType {
     Converted(Variant),
@@ -16,4 +18,29 @@ pub trait TodoValueConversion {
     }
 }
 
-impl TodoValueConversion for Variant {}
+impl TodoValueConversion for Variant {
+    fn try_convert_ref(&self, target_type: VariantType) -> TodoValueConversionType {
+        match (self, target_type) {
+            // BrickColor values are stored as plain ints in XML files.
+            (Variant::Int32(value), VariantType::BrickColor) => {
+                match u16::try_from(*value).ok().and_then(BrickColor::from_number) {
+                    Some(color) => TodoValueConversionType::Converted(color.into()),
+                    None => TodoValueConversionType::Failed,
+                }
+            }
+            // The reflection database describes tags as a BinaryString.
+            (Variant::Tags(tags), VariantType::BinaryString) => {
+                TodoValueConversionType::Converted(BinaryString::from(tags.encode()).into())
+            }
+            // Attributes are described as a BinaryString too.
+            (Variant::Attributes(attributes), VariantType::BinaryString) => {
+                let mut buffer = Vec::new();
+                match attributes.to_writer(&mut buffer) {
+                    Ok(()) => TodoValueConversionType::Converted(BinaryString::from(buffer).into()),
+                    Err(_) => TodoValueConversionType::Failed,
+                }
+            }
+            _ => TodoValueConversionType::Unnecessary,
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::{collections::HashMap, io::Read};
 
 use log::trace;
 use rbx_dom_weak::{
-    types::{Ref, SharedString, Variant, VariantType},
+    types::{Attributes, EnumItem, Ref, SharedString, Tags, Variant, VariantType},
     InstanceBuilder, WeakDom,
 };
 use rbx_reflection::DataType;
@@ -70,6 +70,8 @@ pub enum DecodePropertyBehavior {
 #[derive(Debug, Clone)]
 pub struct DecodeOptions {
     property_behavior: DecodePropertyBehavior,
+    enum_items: bool,
+    strict_classes: bool,
 }
 
 impl DecodeOptions {
@@ -78,6 +80,8 @@ impl DecodeOptions {
     pub fn new() -> Self {
         DecodeOptions {
             property_behavior: DecodePropertyBehavior::IgnoreUnknown,
+            enum_items: false,
+            strict_classes: false,
         }
     }
 
@@ -91,6 +95,32 @@ impl DecodeOptions {
         }
     }
 
+    /// Determines whether properties that the reflection database describes as
+    /// enums are decoded as `Variant::EnumItem` values, which carry the name of
+    /// their enum. Defaults to `false`, which decodes them as
+    /// `Variant::EnumValue` values.
+    ///
+    /// Enums of unknown properties are always decoded as `EnumValue` values,
+    /// since their enum can't be known.
+    #[inline]
+    pub fn enum_items(self, enum_items: bool) -> Self {
+        DecodeOptions { enum_items, ..self }
+    }
+
+    /// Determines whether decoding should return an error when an `Item` has
+    /// a class that isn't in the reflection database. Defaults to `false`,
+    /// which decodes those instances like any other.
+    ///
+    /// This is useful for catching typos in hand-written files. It has no
+    /// effect when using `DecodePropertyBehavior::NoReflection`.
+    #[inline]
+    pub fn strict_classes(self, strict_classes: bool) -> Self {
+        DecodeOptions {
+            strict_classes,
+            ..self
+        }
+    }
+
     /// A utility function to determine whether or not we should reference the
     /// reflection database at all.
     pub(crate) fn use_reflection(&self) -> bool {
@@ -195,7 +225,17 @@ fn apply_referent_rewrites(state: &mut ParseState) {
     for rewrite in &state.referent_rewrites {
         let new_value = match state.referents_to_ids.get(&rewrite.referent_value) {
             Some(id) => *id,
-            None => continue,
+            None => {
+                // Like Studio, we tolerate Ref properties that point to
+                // instances that aren't in this file. These properties were
+                // already deserialized as null refs, so we leave them alone.
+                trace!(
+                    "Property {} points to unknown referent {}",
+                    rewrite.property_name,
+                    rewrite.referent_value
+                );
+                continue;
+            }
         };
 
         let instance = state
@@ -262,9 +302,10 @@ fn deserialize_root<R: Read>(
                     "Item" => {
                         deserialize_instance(reader, state, parent_id)?;
                     }
-                    "External" => {
+                    "External" | "external" => {
                         // This tag is always meaningless, there's nothing to do
-                        // here except skip it.
+                        // here except skip it. Some older files spell it in
+                        // lowercase.
                         reader.eat_unknown_tag()?;
                     }
                     "Meta" => {
@@ -409,6 +450,15 @@ fn deserialize_instance<R: Read>(
         let class =
             class.ok_or_else(|| reader.error(DecodeErrorKind::MissingAttribute("class")))?;
 
+        if state.options.strict_classes
+            && state.options.use_reflection()
+            && !rbx_reflection_database::get()
+                .classes
+                .contains_key(class.as_str())
+        {
+            return Err(reader.error(DecodeErrorKind::UnknownClass(class)));
+        }
+
         (class, referent)
     };
 
@@ -577,6 +627,15 @@ fn deserialize_properties<R: Read>(
                 }
             };
 
+            let value = match (&descriptor.data_type, value) {
+                (DataType::Enum(enum_name), Variant::EnumValue(value))
+                    if state.options.enum_items =>
+                {
+                    Variant::EnumItem(EnumItem::new(enum_name.as_ref(), value.to_u32()))
+                }
+                (_, value) => decode_binary_string(&descriptor.name, value),
+            };
+
             props.insert(descriptor.name.to_string(), value);
         } else {
             match state.options.property_behavior {
@@ -594,7 +653,8 @@ fn deserialize_properties<R: Read>(
                 }
                 DecodePropertyBehavior::ReadUnknown | DecodePropertyBehavior::NoReflection => {
                     // We'll take this value as-is with no conversions on either
-                    // the name or value.
+                    // the name or value, other than decoding tags and
+                    // attributes.
 
                     let value = read_value_xml(
                         reader,
@@ -603,6 +663,7 @@ fn deserialize_properties<R: Read>(
                         instance_id,
                         &xml_property_name,
                     )?;
+                    let value = decode_binary_string(&xml_property_name, value);
                     props.insert(xml_property_name, value);
                 }
                 DecodePropertyBehavior::ErrorOnUnknown => {
@@ -616,3 +677,304 @@ fn deserialize_properties<R: Read>(
         }
     }
 }
+
+/// Tags and attributes are both stored as a BinaryString, which we decode into
+/// types that are easier to work with.
+///
+/// Tags are separated by a null byte. Attributes use the same format as binary
+/// files. Either can hold values that we can't decode without changing them,
+/// like tags that aren't valid UTF-8 or attributes of types we don't
+/// understand yet, so rather than failing, we keep those in their serialized
+/// form so that they round-trip.
+fn decode_binary_string(property_name: &str, value: Variant) -> Variant {
+    match value {
+        Variant::BinaryString(buffer) if property_name == "Tags" => {
+            match Tags::decode(buffer.as_ref()) {
+                Some(tags) => Variant::Tags(tags),
+                None => Variant::BinaryString(buffer),
+            }
+        }
+        Variant::BinaryString(buffer) if property_name == "AttributesSerialize" => {
+            let bytes: &[u8] = buffer.as_ref();
+
+            match Attributes::from_reader(bytes) {
+                Ok(attributes) => Variant::Attributes(attributes),
+                Err(err) => {
+                    log::warn!(
+                        "Could not decode attributes, keeping them as a BinaryString: {}",
+                        err
+                    );
+                    Variant::BinaryString(buffer)
+                }
+            }
+        }
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use rbx_dom_weak::types::EnumValue;
+
+    #[test]
+    fn dangling_ref() {
+        let _ = env_logger::try_init();
+
+        let document = r#"
+            <roblox version="4">
+                <Item class="ObjectValue" referent="RBX0">
+                    <Properties>
+                        <string name="Name">Dangling</string>
+                        <Ref name="Value">RBX1234</Ref>
+                    </Properties>
+                </Item>
+                <Item class="ObjectValue" referent="RBX1">
+                    <Properties>
+                        <string name="Name">Resolved</string>
+                        <Ref name="Value">RBX0</Ref>
+                    </Properties>
+                </Item>
+            </roblox>
+        "#;
+
+        let tree = decode_internal(document.as_bytes(), DecodeOptions::default()).unwrap();
+        let children = tree.root().children();
+
+        let dangling = tree.get_by_ref(children[0]).unwrap();
+        assert_eq!(dangling.name, "Dangling");
+        assert_eq!(
+            dangling.properties.get("Value"),
+            Some(&Variant::Ref(Ref::none()))
+        );
+
+        let resolved = tree.get_by_ref(children[1]).unwrap();
+        assert_eq!(resolved.name, "Resolved");
+        assert_eq!(
+            resolved.properties.get("Value"),
+            Some(&Variant::Ref(children[0]))
+        );
+    }
+
+    #[test]
+    fn legacy_refs() {
+        let _ = env_logger::try_init();
+
+        let modern = r#"
+            <roblox version="4">
+                <Item class="ObjectValue" referent="RBX0">
+                    <Properties>
+                        <Ref name="Value">null</Ref>
+                    </Properties>
+                </Item>
+                <Item class="ObjectValue" referent="RBX1">
+                    <Properties>
+                        <Ref name="Value">RBX0</Ref>
+                    </Properties>
+                </Item>
+            </roblox>
+        "#;
+
+        let legacy = [
+            r#"
+                <roblox version="4">
+                    <External>null</External>
+                    <External>nil</External>
+                    <Item class="ObjectValue" referent="RBX0">
+                        <Properties>
+                            <Ref name="Value">nil</Ref>
+                        </Properties>
+                    </Item>
+                    <Item class="ObjectValue" referent="RBX1">
+                        <Properties>
+                            <Ref name="Value">RBX0</Ref>
+                        </Properties>
+                    </Item>
+                </roblox>
+            "#,
+            r#"
+                <roblox version="4">
+                    <external>null</external>
+                    <external>nil</external>
+                    <Item class="ObjectValue" referent="RBX0">
+                        <Properties>
+                            <Ref name="Value"></Ref>
+                        </Properties>
+                    </Item>
+                    <Item class="ObjectValue" referent="RBX1">
+                        <Properties>
+                            <Ref name="Value">
+                                RBX0
+                            </Ref>
+                        </Properties>
+                    </Item>
+                </roblox>
+            "#,
+            r#"
+                <roblox version="4">
+                    <Item class="ObjectValue" referent="RBX0">
+                        <Properties>
+                            <Ref name="Value"/>
+                        </Properties>
+                    </Item>
+                    <Item class="ObjectValue" referent="RBX1">
+                        <Properties>
+                            <Ref name="Value"> RBX0 </Ref>
+                        </Properties>
+                    </Item>
+                </roblox>
+            "#,
+        ];
+
+        // Refs differ between DOMs, so we describe each one in terms of child
+        // indices instead.
+        let describe = |document: &str| -> Vec<Option<usize>> {
+            let tree = decode_internal(document.as_bytes(), DecodeOptions::default()).unwrap();
+            let children = tree.root().children();
+
+            children
+                .iter()
+                .map(|referent| {
+                    let instance = tree.get_by_ref(*referent).unwrap();
+                    match instance.properties.get("Value") {
+                        Some(Variant::Ref(target)) => {
+                            children.iter().position(|child| child == target)
+                        }
+                        other => panic!("unexpected Value {:?}", other),
+                    }
+                })
+                .collect()
+        };
+
+        let expected = describe(modern);
+        assert_eq!(expected, vec![None, Some(0)]);
+
+        for document in &legacy {
+            assert_eq!(describe(document), expected);
+        }
+    }
+
+    #[test]
+    fn strict_classes() {
+        static TEST_FILE: &[u8] = include_bytes!("../test-files/unknown-class.rbxmx");
+
+        let dom = decode_internal(TEST_FILE, DecodeOptions::default()).unwrap();
+        let folder = dom.get_by_ref(dom.root().children()[0]).unwrap();
+        let misspelled = dom.get_by_ref(folder.children()[0]).unwrap();
+        assert_eq!(misspelled.class, "Fodler");
+
+        let err =
+            decode_internal(TEST_FILE, DecodeOptions::new().strict_classes(true)).unwrap_err();
+        assert_eq!(err.line(), 6);
+        assert_eq!(err.to_string(), "line 6, column 3: Class Fodler is unknown");
+
+        let options = DecodeOptions::new()
+            .strict_classes(true)
+            .property_behavior(DecodePropertyBehavior::NoReflection);
+        assert!(decode_internal(TEST_FILE, options).is_ok());
+    }
+
+    #[test]
+    fn error_positions() {
+        let decode_error = |document: &str| {
+            decode_internal(document.as_bytes(), DecodeOptions::default()).unwrap_err()
+        };
+
+        let unexpected_element = decode_error(
+            "<roblox version=\"4\">\n\
+             \t<Item class=\"Folder\" referent=\"RBX0\">\n\
+             \t\t<Properties>\n\
+             \t\t\t<string name=\"Name\">Folder</string>\n\
+             \t\t</Properties>\n\
+             \t\t<Foo/>\n\
+             \t</Item>\n\
+             </roblox>",
+        );
+        assert_eq!(unexpected_element.line(), 6);
+        assert_eq!(unexpected_element.column(), 3);
+        assert_eq!(
+            unexpected_element.to_string(),
+            "line 6, column 3: Unexpected element <Foo>"
+        );
+
+        let bad_float = decode_error(
+            "<roblox version=\"4\">\n\
+             \t<Item class=\"NumberValue\" referent=\"RBX0\">\n\
+             \t\t<Properties>\n\
+             \t\t\t<double name=\"Value\">one</double>\n\
+             \t\t</Properties>\n\
+             \t</Item>\n\
+             </roblox>",
+        );
+        assert_eq!(bad_float.line(), 4);
+
+        let mismatched_tag = decode_error(
+            "<roblox version=\"4\">\n\
+             \t<Item class=\"Folder\" referent=\"RBX0\">\n\
+             \t\t<Properties>\n\
+             \t\t\t<string name=\"Name\">Folder</strin>\n\
+             \t\t</Properties>\n\
+             \t</Item>\n\
+             </roblox>",
+        );
+        assert_eq!(mismatched_tag.line(), 4);
+        assert!(!mismatched_tag.to_string().contains("4:"));
+    }
+
+    #[test]
+    fn enum_items() {
+        let document = r#"
+            <roblox version="4">
+                <Item class="Part" referent="RBX0">
+                    <Properties>
+                        <token name="Material">256</token>
+                    </Properties>
+                </Item>
+            </roblox>
+        "#;
+
+        // Material is declared on BasePart, so the enum has to be found
+        // through Part's superclasses.
+        let decode_material = |options: DecodeOptions| {
+            let tree = decode_internal(document.as_bytes(), options).unwrap();
+            let part = tree.get_by_ref(tree.root().children()[0]).unwrap();
+            part.properties["Material"].clone()
+        };
+
+        assert_eq!(
+            decode_material(DecodeOptions::new()),
+            Variant::EnumValue(EnumValue::from_u32(256))
+        );
+        assert_eq!(
+            decode_material(DecodeOptions::new().enum_items(true)),
+            Variant::EnumItem(EnumItem::new("Material", 256))
+        );
+    }
+
+    #[test]
+    fn empty_attributes() {
+        let _ = env_logger::try_init();
+
+        // Roblox writes an empty AttributesSerialize blob on instances that
+        // have no attributes.
+        let document = r#"
+            <roblox version="4">
+                <Item class="Folder" referent="RBX0">
+                    <Properties>
+                        <BinaryString name="AttributesSerialize"></BinaryString>
+                    </Properties>
+                </Item>
+            </roblox>
+        "#;
+
+        let options = DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+        let tree = decode_internal(document.as_bytes(), options).unwrap();
+        let folder = tree.get_by_ref(tree.root().children()[0]).unwrap();
+
+        assert_eq!(
+            folder.properties.get("AttributesSerialize"),
+            Some(&Variant::Attributes(Attributes::new()))
+        );
+    }
+}
@@ -1,7 +1,11 @@
 use std::io::Read;
 
 use log::trace;
-use xml::{attribute::OwnedAttribute, reader::ParserConfig};
+use xml::{
+    attribute::OwnedAttribute,
+    common::{Position, TextPosition},
+    reader::ParserConfig,
+};
 
 use crate::{
     core::XmlType,
@@ -15,7 +19,12 @@ pub type XmlReadResult = Result<XmlReadEvent, XmlReadError>;
 /// A wrapper around an XML event iterator created by xml-rs.
 pub struct XmlEventReader<R: Read> {
     reader: xml::EventReader<R>,
-    peeked: Option<Result<XmlReadEvent, xml::reader::Error>>,
+    peeked: Option<(XmlReadResult, TextPosition)>,
+
+    /// The position of the last event returned by `next`. Errors are reported
+    /// at this position, even if another event has been peeked since.
+    position: TextPosition,
+
     finished: bool,
 }
 
@@ -23,10 +32,34 @@ impl<R: Read> Iterator for XmlEventReader<R> {
     type Item = XmlReadResult;
 
     fn next(&mut self) -> Option<XmlReadResult> {
-        if let Some(value) = self.peeked.take() {
-            return Some(value);
+        let (event, position) = match self.peeked.take() {
+            Some(peeked) => peeked,
+            None => self.read_event()?,
+        };
+
+        self.position = position;
+        Some(event)
+    }
+}
+
+impl<R: Read> XmlEventReader<R> {
+    /// Constructs a new `XmlEventReader` from a source that implements `Read`.
+    pub fn from_source(source: R) -> XmlEventReader<R> {
+        let reader = ParserConfig::new()
+            .ignore_comments(true)
+            .create_reader(source);
+
+        XmlEventReader {
+            reader,
+            peeked: None,
+            position: TextPosition::new(),
+            finished: false,
         }
+    }
 
+    /// Reads the next event from xml-rs that isn't whitespace, along with the
+    /// position it started at.
+    fn read_event(&mut self) -> Option<(XmlReadResult, TextPosition)> {
         if self.finished {
             return None;
         }
@@ -37,45 +70,30 @@ impl<R: Read> Iterator for XmlEventReader<R> {
                     XmlReadEvent::Whitespace(_) => continue,
                     XmlReadEvent::EndDocument => {
                         self.finished = true;
-                        return Some(Ok(item));
+                        return Some((Ok(item), self.reader.position()));
                     }
-                    _ => return Some(Ok(item)),
+                    _ => return Some((Ok(item), self.reader.position())),
                 },
                 Err(err) => {
                     self.finished = true;
-                    return Some(Err(err));
+                    let position = err.position();
+                    return Some((Err(err), position));
                 }
             }
         }
     }
-}
-
-impl<R: Read> XmlEventReader<R> {
-    /// Constructs a new `XmlEventReader` from a source that implements `Read`.
-    pub fn from_source(source: R) -> XmlEventReader<R> {
-        let reader = ParserConfig::new()
-            .ignore_comments(true)
-            .create_reader(source);
-
-        XmlEventReader {
-            reader,
-            peeked: None,
-            finished: false,
-        }
-    }
 
     /// Borrows the next element from the event stream without consuming it.
     pub fn peek(&mut self) -> Option<&XmlReadResult> {
-        if self.peeked.is_some() {
-            return self.peeked.as_ref();
+        if self.peeked.is_none() {
+            self.peeked = self.read_event();
         }
 
-        self.peeked = self.next();
-        self.peeked.as_ref()
+        self.peeked.as_ref().map(|(event, _)| event)
     }
 
     pub(crate) fn error<T: Into<DecodeErrorKind>>(&self, kind: T) -> NewDecodeError {
-        NewDecodeError::new_from_reader(kind.into(), &self.reader)
+        NewDecodeError::new(kind.into(), self.position)
     }
 
     pub fn expect_next(&mut self) -> Result<XmlReadEvent, NewDecodeError> {
@@ -1,9 +1,10 @@
 use std::{
     fmt,
-    io::{self, Read, Write},
+    io::{self, Write},
 };
 
-use rbx_dom_weak::types::VariantType;
+use rbx_dom_weak::types::{AttributeError, VariantType};
+use xml::{common::TextPosition, reader::XmlEvent};
 
 /// An error that can occur when deserializing an XML-format model or place.
 #[derive(Debug)]
@@ -15,19 +16,13 @@ pub struct DecodeError {
 }
 
 impl DecodeError {
-    pub(crate) fn new_from_reader<R: Read>(
-        kind: DecodeErrorKind,
-        reader: &xml::EventReader<R>,
-    ) -> DecodeError {
-        use xml::common::Position;
-
-        let pos = reader.position();
-
+    pub(crate) fn new(kind: DecodeErrorKind, position: TextPosition) -> DecodeError {
+        // xml-rs counts rows and columns starting at zero.
         DecodeError {
             inner: Box::new(DecodeErrorImpl {
                 kind,
-                line: (pos.row + 1) as usize,
-                column: pos.column as usize,
+                line: (position.row + 1) as usize,
+                column: (position.column + 1) as usize,
             }),
         }
     }
@@ -79,6 +74,7 @@ pub(crate) enum DecodeErrorKind {
     UnexpectedEof,
     UnexpectedXmlEvent(xml::reader::XmlEvent),
     MissingAttribute(&'static str),
+    UnknownClass(String),
     UnknownProperty {
         class_name: String,
         property_name: String,
@@ -99,7 +95,14 @@ impl fmt::Display for DecodeErrorKind {
         use self::DecodeErrorKind::*;
 
         match self {
-            Xml(err) => write!(output, "{}", err),
+            // The position of the error is already part of DecodeError, so we
+            // leave out the one that xml-rs includes in its errors.
+            Xml(err) => match err.kind() {
+                xml::reader::ErrorKind::Syntax(message) => write!(output, "{}", message),
+                xml::reader::ErrorKind::Io(err) => write!(output, "{}", err),
+                xml::reader::ErrorKind::Utf8(err) => write!(output, "{}", err),
+                xml::reader::ErrorKind::UnexpectedEof => write!(output, "Unexpected end-of-file"),
+            },
             ParseFloat(err) => write!(output, "{}", err),
             ParseInt(err) => write!(output, "{}", err),
             DecodeBase64(err) => write!(output, "{}", err),
@@ -108,10 +111,23 @@ impl fmt::Display for DecodeErrorKind {
                 write!(output, "Invalid version '{}', expected version 4", version)
             }
             UnexpectedEof => write!(output, "Unexpected end-of-file"),
-            UnexpectedXmlEvent(event) => write!(output, "Unexpected XML event {:?}", event),
+            UnexpectedXmlEvent(event) => match event {
+                XmlEvent::StartElement { name, .. } => {
+                    write!(output, "Unexpected element <{}>", name.local_name)
+                }
+                XmlEvent::EndElement { name } => {
+                    write!(output, "Unexpected closing tag </{}>", name.local_name)
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    write!(output, "Unexpected text {:?}", text)
+                }
+                XmlEvent::EndDocument => write!(output, "Unexpected end-of-file"),
+                other => write!(output, "Unexpected XML event {:?}", other),
+            },
             MissingAttribute(attribute_name) => {
                 write!(output, "Missing attribute '{}'", attribute_name)
             }
+            UnknownClass(class_name) => write!(output, "Class {} is unknown", class_name),
             UnknownProperty {
                 class_name,
                 property_name,
@@ -157,6 +173,7 @@ impl std::error::Error for DecodeErrorKind {
             | UnexpectedEof
             | UnexpectedXmlEvent(_)
             | MissingAttribute(_)
+            | UnknownClass(_)
             | UnknownProperty { .. }
             | UnknownPropertyType(_)
             | InvalidContent(_)
@@ -236,6 +253,18 @@ pub(crate) enum EncodeErrorKind {
         expected_type: VariantType,
         actual_type: VariantType,
     },
+    FloatOutOfRange {
+        class_name: String,
+        property_name: String,
+        value: f64,
+    },
+    InvalidEnumValue {
+        class_name: String,
+        property_name: String,
+        enum_name: String,
+        value: u32,
+    },
+    InvalidAttributes(AttributeError),
 }
 
 impl fmt::Display for EncodeErrorKind {
@@ -267,6 +296,26 @@ impl fmt::Display for EncodeErrorKind {
                 "Property {}.{} is expected to be of type {:?}, but it was of type {:?}",
                 class_name, property_name, expected_type, actual_type
             ),
+            FloatOutOfRange {
+                class_name,
+                property_name,
+                value,
+            } => write!(
+                output,
+                "Property {}.{} is a Float32, but {} is too large to be stored as one",
+                class_name, property_name, value
+            ),
+            InvalidEnumValue {
+                class_name,
+                property_name,
+                enum_name,
+                value,
+            } => write!(
+                output,
+                "Property {}.{} is set to {}, which is not an item of the enum {}",
+                class_name, property_name, value, enum_name
+            ),
+            InvalidAttributes(err) => write!(output, "Could not serialize attributes: {}", err),
         }
     }
 }
@@ -278,10 +327,13 @@ impl std::error::Error for EncodeErrorKind {
         match self {
             Io(err) => Some(err),
             Xml(err) => Some(err),
+            InvalidAttributes(err) => Some(err),
 
             UnknownProperty { .. }
             | UnsupportedPropertyType(_)
-            | UnsupportedPropertyConversion { .. } => None,
+            | UnsupportedPropertyConversion { .. }
+            | FloatOutOfRange { .. }
+            | InvalidEnumValue { .. } => None,
         }
     }
 }
@@ -130,6 +130,9 @@ mod serializer;
 mod serializer_core;
 mod types;
 
+#[cfg(any(test, feature = "unstable_text_format"))]
+mod text_deserializer;
+
 #[cfg(test)]
 mod test_util;
 
@@ -139,10 +142,16 @@ use rbx_dom_weak::{types::Ref, WeakDom};
 
 use crate::{deserializer::decode_internal, serializer::encode_internal};
 
+/// An unstable textual format that can be used to debug XML models.
+#[cfg(feature = "unstable_text_format")]
+pub mod text_format {
+    pub use crate::text_deserializer::*;
+}
+
 pub use crate::{
     deserializer::{DecodeOptions, DecodePropertyBehavior},
     error::{DecodeError, EncodeError},
-    serializer::{EncodeOptions, EncodePropertyBehavior},
+    serializer::{EncodeOptions, EncodePropertyBehavior, FloatFormat},
 };
 
 /// Decodes an XML-format model or place from something that implements the
@@ -179,6 +188,20 @@ pub fn to_writer<W: Write>(
     encode_internal(writer, tree, ids, options)
 }
 
+/// Serializes the given instances and their descendants to an XML format model
+/// or place using the default encoder options.
+///
+/// Each instance in `refs` is written as a top-level item. `Ref` properties
+/// pointing to instances that aren't being written are written as null refs.
+///
+/// The document is written to `writer` as the tree is walked instead of being
+/// built in memory first, so even very large places can be written without
+/// holding a copy of the output. Many small writes are made, so wrapping
+/// `writer` in a `BufWriter` is recommended.
+pub fn encode<W: Write>(dom: &WeakDom, refs: &[Ref], writer: W) -> Result<(), EncodeError> {
+    encode_internal(writer, dom, refs, EncodeOptions::default())
+}
+
 /// Serializes a subset of the given tree to an XML format model or place,
 /// writing to something that implements the `std::io::Write` trait using the
 /// default encoder options.
@@ -1,10 +1,15 @@
-use std::{borrow::Cow, collections::HashMap, io::Write};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::Write,
+    sync::Arc,
+};
 
 use rbx_dom_weak::{
     types::{Ref, SharedString, SharedStringHash, Variant, VariantType},
     WeakDom,
 };
-use rbx_reflection::DataType;
+use rbx_reflection::{DataType, ReflectionDatabase};
 
 use crate::{
     compat::{TodoValueConversion, TodoValueConversionType},
@@ -21,9 +26,14 @@ pub fn encode_internal<W: Write>(
     ids: &[Ref],
     options: EncodeOptions,
 ) -> Result<(), NewEncodeError> {
-    let mut writer = XmlEventWriter::from_output(output);
+    let mut writer = XmlEventWriter::from_output(output).with_float_format(options.float_format);
     let mut state = EmitState::new(options);
 
+    for id in ids {
+        state.serialized_ids.insert(*id);
+        state.serialized_ids.extend(tree.descendants(*id));
+    }
+
     writer.write(XmlWriteEvent::start_element("roblox").attr("version", "4"))?;
 
     let mut property_buffer = Vec::new();
@@ -70,10 +80,42 @@ pub enum EncodePropertyBehavior {
     __Nonexhaustive,
 }
 
+/// Describes how rbx_xml should write floating point values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FloatFormat {
+    /// Writes the fewest digits that still parse back to exactly the same
+    /// value, like `0.1` for the `f32` closest to 0.1.
+    ///
+    /// This is the default. It's the easiest to read and diff, but it doesn't
+    /// match how Roblox Studio writes floats, so saving a file in Studio will
+    /// change many values.
+    Shortest,
+
+    /// Writes as many significant digits as are needed to represent any value
+    /// of the type: 9 for `Float32` and 17 for `Float64`. The `f32` closest to
+    /// 0.1 is written as `0.100000001`.
+    ///
+    /// This matches Roblox Studio's output more closely, at the cost of noisy
+    /// values. Both formats parse back to exactly the same value.
+    Full,
+}
+
+impl Default for FloatFormat {
+    fn default() -> FloatFormat {
+        FloatFormat::Shortest
+    }
+}
+
 /// Options available for serializing an XML-format model or place.
 #[derive(Debug, Clone)]
 pub struct EncodeOptions {
     property_behavior: EncodePropertyBehavior,
+    widen_floats: bool,
+    float_format: FloatFormat,
+    validate_enums: bool,
+    enum_database: Option<Arc<ReflectionDatabase<'static>>>,
+    skip_non_serialized: bool,
 }
 
 impl EncodeOptions {
@@ -82,6 +124,11 @@ impl EncodeOptions {
     pub fn new() -> Self {
         EncodeOptions {
             property_behavior: EncodePropertyBehavior::IgnoreUnknown,
+            widen_floats: false,
+            float_format: FloatFormat::Shortest,
+            validate_enums: false,
+            enum_database: None,
+            skip_non_serialized: false,
         }
     }
 
@@ -95,6 +142,86 @@ impl EncodeOptions {
         }
     }
 
+    /// Determines whether `Float32` values can be written to properties that
+    /// the reflection database declares as `Float64`. Defaults to `false`,
+    /// which returns an error instead.
+    ///
+    /// `Float64` values written to `Float32` properties are always narrowed,
+    /// since that's the only way to write them. A warning is logged if that
+    /// loses more precision than rounding to the nearest `f32` would, and
+    /// values too large to fit in an `f32` return an error.
+    #[inline]
+    pub fn widen_floats(self, widen_floats: bool) -> Self {
+        EncodeOptions {
+            widen_floats,
+            ..self
+        }
+    }
+
+    /// Determines how floating point values are written, including the
+    /// components of types like `Vector3` and `CFrame`. Defaults to
+    /// `FloatFormat::Shortest`.
+    #[inline]
+    pub fn float_format(self, float_format: FloatFormat) -> Self {
+        EncodeOptions {
+            float_format,
+            ..self
+        }
+    }
+
+    /// Determines whether enum properties are checked against the items of
+    /// their enum. Defaults to `false`.
+    ///
+    /// When enabled, writing a value to an enum property returns an error if
+    /// the enum that the reflection database declares for the property has no
+    /// item with that value. Properties whose enum isn't in the database
+    /// aren't checked.
+    ///
+    /// The database bundled with rbx_xml doesn't describe any enums yet, so
+    /// this only has an effect when combined with
+    /// [`enum_database`](#method.enum_database).
+    #[inline]
+    pub fn validate_enums(self, validate_enums: bool) -> Self {
+        EncodeOptions {
+            validate_enums,
+            ..self
+        }
+    }
+
+    /// Sets the reflection database whose enums are used by
+    /// [`validate_enums`](#method.validate_enums). Properties are still looked
+    /// up in the database bundled with rbx_xml. Defaults to the bundled
+    /// database.
+    #[inline]
+    pub fn enum_database(self, database: ReflectionDatabase<'static>) -> Self {
+        EncodeOptions {
+            enum_database: Some(Arc::new(database)),
+            ..self
+        }
+    }
+
+    /// Determines whether properties that the reflection database says Roblox
+    /// doesn't persist are always left out. Defaults to `false`.
+    ///
+    /// The reflection database doesn't describe how these properties are
+    /// serialized, so by default they're treated like unknown properties
+    /// according to [`property_behavior`](#method.property_behavior). When
+    /// enabled, they're left out even with
+    /// `EncodePropertyBehavior::WriteUnknown`, and don't cause errors with
+    /// `EncodePropertyBehavior::ErrorOnUnknown`. This has no effect with
+    /// `EncodePropertyBehavior::NoReflection`.
+    ///
+    /// The database bundled with rbx_xml marks some properties that are
+    /// persisted, like `BasePart.Size`, as not serializing without naming the
+    /// property they're persisted as, so enabling this drops them too.
+    #[inline]
+    pub fn skip_non_serialized(self, skip_non_serialized: bool) -> Self {
+        EncodeOptions {
+            skip_non_serialized,
+            ..self
+        }
+    }
+
     pub(crate) fn use_reflection(&self) -> bool {
         self.property_behavior != EncodePropertyBehavior::NoReflection
     }
@@ -116,6 +243,11 @@ pub struct EmitState {
     /// The referent value that will be used for emitting the next instance.
     next_referent: u32,
 
+    /// Every instance that will be written, including descendants. Ref
+    /// properties pointing to instances outside of this set are written as
+    /// null refs.
+    serialized_ids: HashSet<Ref>,
+
     /// A map of all shared strings referenced so far while generating XML. This
     /// map will be written as the file's SharedString dictionary.
     shared_strings_to_emit: HashMap<SharedStringHash, SharedString>,
@@ -127,6 +259,7 @@ impl EmitState {
             options,
             referent_map: HashMap::new(),
             next_referent: 0,
+            serialized_ids: HashSet::new(),
             shared_strings_to_emit: HashMap::new(),
         }
     }
@@ -143,6 +276,11 @@ impl EmitState {
         }
     }
 
+    /// Tells whether the instance with the given ID is being written.
+    pub fn is_serialized(&self, id: Ref) -> bool {
+        self.serialized_ids.contains(&id)
+    }
+
     pub fn add_shared_string(&mut self, value: SharedString) {
         self.shared_strings_to_emit.insert(value.hash(), value);
     }
@@ -198,7 +336,40 @@ fn serialize_instance<'a, W: Write>(
                 _ => unimplemented!(),
             };
 
-            let converted_value = match value.try_convert_ref(data_type) {
+            let conversion = match (value, data_type) {
+                (Variant::Float64(wide), VariantType::Float32) => match narrow_float(*wide) {
+                    Some(narrow) => {
+                        if lost_precision(*wide, narrow) {
+                            log::warn!(
+                                    "Property {}.{} lost precision when written as a Float32: {} became {}",
+                                    instance.class,
+                                    property_name,
+                                    wide,
+                                    narrow
+                                );
+                        }
+
+                        TodoValueConversionType::Converted(Variant::Float32(narrow))
+                    }
+                    None => {
+                        return Err(writer.error(EncodeErrorKind::FloatOutOfRange {
+                            class_name: instance.class.clone(),
+                            property_name: property_name.to_string(),
+                            value: *wide,
+                        }))
+                    }
+                },
+                (Variant::Float32(narrow), VariantType::Float64) => {
+                    if state.options.widen_floats {
+                        TodoValueConversionType::Converted(Variant::Float64(f64::from(*narrow)))
+                    } else {
+                        TodoValueConversionType::Failed
+                    }
+                }
+                _ => value.try_convert_ref(data_type),
+            };
+
+            let converted_value = match conversion {
                 TodoValueConversionType::Converted(converted) => Cow::Owned(converted),
                 TodoValueConversionType::Unnecessary => Cow::Borrowed(value),
                 TodoValueConversionType::Failed => {
@@ -213,7 +384,30 @@ fn serialize_instance<'a, W: Write>(
                 }
             };
 
+            if let DataType::Enum(enum_name) = &serialized_descriptor.data_type {
+                if state.options.validate_enums {
+                    let database = match &state.options.enum_database {
+                        Some(database) => database,
+                        None => rbx_reflection_database::get(),
+                    };
+
+                    validate_enum(
+                        database,
+                        &instance.class,
+                        property_name,
+                        enum_name,
+                        &converted_value,
+                    )
+                    .map_err(|kind| writer.error(kind))?;
+                }
+            }
+
             write_value_xml(writer, state, &serialized_descriptor.name, &converted_value)?;
+        } else if state.options.use_reflection()
+            && state.options.skip_non_serialized
+            && !rbx_reflection_database::get().property_serializes(&instance.class, property_name)
+        {
+            // Roblox doesn't persist this property, so it's left out.
         } else {
             match state.options.property_behavior {
                 EncodePropertyBehavior::IgnoreUnknown => {}
@@ -245,6 +439,62 @@ fn serialize_instance<'a, W: Write>(
     Ok(())
 }
 
+/// Checks that `value` is one of the items of the enum named `enum_name`. Enums
+/// that aren't in the database are assumed to be valid.
+fn validate_enum(
+    database: &ReflectionDatabase,
+    class_name: &str,
+    property_name: &str,
+    enum_name: &str,
+    value: &Variant,
+) -> Result<(), EncodeErrorKind> {
+    let value = match value {
+        Variant::EnumValue(value) => value.to_u32(),
+        Variant::EnumItem(item) => item.value,
+        _ => return Ok(()),
+    };
+
+    let enum_descriptor = match database.enums.get(enum_name) {
+        Some(enum_descriptor) => enum_descriptor,
+        None => return Ok(()),
+    };
+
+    if enum_descriptor.item_name(value).is_some() {
+        Ok(())
+    } else {
+        Err(EncodeErrorKind::InvalidEnumValue {
+            class_name: class_name.to_owned(),
+            property_name: property_name.to_owned(),
+            enum_name: enum_name.to_owned(),
+            value,
+        })
+    }
+}
+
+/// Narrows a `Float64` value so that it can be written to a `Float32`
+/// property, returning `None` if it's too large to be represented.
+fn narrow_float(value: f64) -> Option<f32> {
+    // Casting a finite value that's out of range isn't defined before
+    // Rust 1.45, so it has to be checked before casting.
+    if value.is_finite() && value.abs() > f64::from(std::f32::MAX) {
+        None
+    } else {
+        Some(value as f32)
+    }
+}
+
+/// Tells whether narrowing `wide` to `narrow` lost more precision than rounding
+/// to the nearest `f32` can, which happens when `wide` is too small to be
+/// represented as a normal `f32`.
+fn lost_precision(wide: f64, narrow: f32) -> bool {
+    if !wide.is_finite() || wide == 0.0 {
+        return false;
+    }
+
+    let relative_error = ((f64::from(narrow) - wide) / wide).abs();
+    relative_error > f64::from(std::f32::EPSILON)
+}
+
 fn serialize_shared_strings<W: Write>(
     writer: &mut XmlEventWriter<W>,
     state: &mut EmitState,
@@ -255,7 +505,12 @@ fn serialize_shared_strings<W: Write>(
 
     writer.write(XmlWriteEvent::start_element("SharedStrings"))?;
 
-    for value in state.shared_strings_to_emit.values() {
+    // Sort shared strings by hash so that output doesn't depend on HashMap
+    // iteration order.
+    let mut shared_strings: Vec<_> = state.shared_strings_to_emit.iter().collect();
+    shared_strings.sort_unstable_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+
+    for (_, value) in shared_strings {
         writer.write(
             XmlWriteEvent::start_element("SharedString")
                 .attr("md5", &base64::encode(value.hash().as_bytes())),
@@ -268,3 +523,545 @@ fn serialize_shared_strings<W: Write>(
     writer.end_element()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::convert::TryFrom;
+
+    use rbx_dom_weak::{
+        types::{Attributes, BinaryString, BrickColor, EnumItem, EnumValue, Tags, Vector3},
+        InstanceBuilder,
+    };
+    use rbx_reflection::EnumDescriptor;
+
+    use crate::{text_deserializer::DecodedXmlModel, DecodeOptions, DecodePropertyBehavior};
+
+    fn encode(tree: &WeakDom) -> Vec<u8> {
+        let options = EncodeOptions::new().property_behavior(EncodePropertyBehavior::NoReflection);
+
+        let mut buffer = Vec::new();
+        encode_internal(&mut buffer, tree, tree.root().children(), options).unwrap();
+        buffer
+    }
+
+    fn make_tree() -> WeakDom {
+        WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Folder")
+                    .with_property("Zeta", 1.0f32)
+                    .with_property("Alpha", true)
+                    .with_property("Mu", "Hello")
+                    .with_property("Beta", 5i32)
+                    .with_property("Omega", SharedString::new(b"foo".to_vec()))
+                    .with_property("Gamma", SharedString::new(b"bar".to_vec())),
+            ),
+        )
+    }
+
+    #[test]
+    fn deterministic_property_order() {
+        let _ = env_logger::try_init();
+
+        let tree = make_tree();
+        let first = encode(&tree);
+        let second = encode(&tree);
+        assert_eq!(first, second);
+
+        // A separately-built tree will have different HashMap iteration order
+        // for its properties, but should still produce the same bytes.
+        let third = encode(&make_tree());
+        assert_eq!(first, third);
+
+        let output = std::str::from_utf8(&first).unwrap();
+        let positions: Vec<usize> = ["Alpha", "Beta", "Gamma", "Mu", "Omega", "Zeta"]
+            .iter()
+            .map(|name| output.find(&format!("name=\"{}\"", name)).unwrap())
+            .collect();
+
+        let mut sorted = positions.clone();
+        sorted.sort_unstable();
+        assert_eq!(positions, sorted);
+    }
+
+    #[test]
+    fn text_format() {
+        let _ = env_logger::try_init();
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Folder")
+                    .with_name("Parent")
+                    .with_property("Enabled", true)
+                    .with_property("Data", BinaryString::from(b"hello".to_vec()))
+                    .with_property("Contents", SharedString::new(b"foo".to_vec()))
+                    .with_child(
+                        InstanceBuilder::new("Part")
+                            .with_name("Child")
+                            .with_property("Size", Vector3::new(1.0, 2.0, 3.0)),
+                    ),
+            ),
+        );
+
+        let encoded = encode(&tree);
+        let decoded = DecodedXmlModel::from_reader(encoded.as_slice());
+        insta::assert_yaml_snapshot!(decoded);
+    }
+
+    #[test]
+    fn child_order_round_trip() {
+        let _ = env_logger::try_init();
+
+        let names = ["Zebra", "Apple", "Mango", "Banana", "Cherry"];
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Folder").with_children(
+                    names
+                        .iter()
+                        .map(|name| InstanceBuilder::new("Folder").with_name(*name)),
+                ),
+            ),
+        );
+
+        let encoded = encode(&tree);
+        let decoded = crate::from_reader_default(encoded.as_slice()).unwrap();
+        let parent = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+
+        let decoded_names: Vec<&str> = parent
+            .children()
+            .iter()
+            .map(|referent| decoded.get_by_ref(*referent).unwrap().name.as_str())
+            .collect();
+
+        assert_eq!(decoded_names, names);
+    }
+
+    #[test]
+    fn brick_color_round_trip() {
+        let _ = env_logger::try_init();
+
+        let tree = WeakDom::new(InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("SpawnLocation").with_property("TeamColor", BrickColor::BrightRed),
+        ));
+
+        let mut encoded = Vec::new();
+        encode_internal(
+            &mut encoded,
+            &tree,
+            tree.root().children(),
+            EncodeOptions::default(),
+        )
+        .unwrap();
+
+        let output = std::str::from_utf8(&encoded).unwrap();
+        assert!(output.contains(r#"<int name="TeamColor">21</int>"#));
+
+        let decoded = crate::from_reader_default(encoded.as_slice()).unwrap();
+        let spawn = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+
+        assert_eq!(
+            spawn.properties.get("TeamColor"),
+            Some(&Variant::BrickColor(BrickColor::BrightRed))
+        );
+    }
+
+    #[test]
+    fn tags_round_trip() {
+        let _ = env_logger::try_init();
+
+        let tags = Tags::try_from(vec!["Enemy".to_owned(), "Boss".to_owned()]).unwrap();
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_child(InstanceBuilder::new("Folder").with_property("Tags", tags.clone()))
+                .with_child(InstanceBuilder::new("Folder").with_property("Tags", Tags::new())),
+        );
+
+        let mut encoded = Vec::new();
+        encode_internal(
+            &mut encoded,
+            &tree,
+            tree.root().children(),
+            EncodeOptions::new().property_behavior(EncodePropertyBehavior::WriteUnknown),
+        )
+        .unwrap();
+
+        let output = std::str::from_utf8(&encoded).unwrap();
+        assert!(output
+            .contains(r#"<BinaryString name="Tags"><![CDATA[RW5lbXkAQm9zcw==]]></BinaryString>"#));
+
+        let decode_options =
+            DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+        let decoded = crate::from_reader(encoded.as_slice(), decode_options).unwrap();
+        let children = decoded.root().children();
+
+        let first = decoded.get_by_ref(children[0]).unwrap();
+        assert_eq!(first.properties.get("Tags"), Some(&Variant::Tags(tags)));
+
+        let second = decoded.get_by_ref(children[1]).unwrap();
+        assert_eq!(
+            second.properties.get("Tags"),
+            Some(&Variant::Tags(Tags::new()))
+        );
+    }
+
+    #[test]
+    fn attributes_round_trip() {
+        let _ = env_logger::try_init();
+
+        let mut attributes = Attributes::new();
+        attributes.insert("Greeting", "Hello");
+        attributes.insert("Health", 100.0f64);
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_child(
+                    InstanceBuilder::new("Folder")
+                        .with_property("AttributesSerialize", attributes.clone()),
+                )
+                .with_child(
+                    InstanceBuilder::new("Folder")
+                        .with_property("AttributesSerialize", Attributes::new()),
+                ),
+        );
+
+        let mut encoded = Vec::new();
+        encode_internal(
+            &mut encoded,
+            &tree,
+            tree.root().children(),
+            EncodeOptions::new().property_behavior(EncodePropertyBehavior::WriteUnknown),
+        )
+        .unwrap();
+
+        // Instances without attributes get an empty blob, like Roblox writes,
+        // so only the first instance has any attribute data.
+        let encoded_str = std::str::from_utf8(&encoded).unwrap();
+        assert_eq!(encoded_str.matches("<![CDATA[").count(), 1);
+
+        let decode_options =
+            DecodeOptions::new().property_behavior(DecodePropertyBehavior::ReadUnknown);
+        let decoded = crate::from_reader(encoded.as_slice(), decode_options).unwrap();
+        let children = decoded.root().children();
+
+        let first = decoded.get_by_ref(children[0]).unwrap();
+        assert_eq!(
+            first.properties.get("AttributesSerialize"),
+            Some(&Variant::Attributes(attributes))
+        );
+
+        let second = decoded.get_by_ref(children[1]).unwrap();
+        assert_eq!(
+            second.properties.get("AttributesSerialize"),
+            Some(&Variant::Attributes(Attributes::new()))
+        );
+    }
+
+    fn encode_with(tree: &WeakDom, options: EncodeOptions) -> Result<String, NewEncodeError> {
+        let mut encoded = Vec::new();
+        encode_internal(&mut encoded, tree, tree.root().children(), options)?;
+        Ok(String::from_utf8(encoded).unwrap())
+    }
+
+    #[test]
+    fn narrow_float64_to_float32() {
+        let _ = env_logger::try_init();
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_child(InstanceBuilder::new("Part").with_property("Transparency", 0.5f64)),
+        );
+
+        let output = encode_with(&tree, EncodeOptions::default()).unwrap();
+        assert!(output.contains(r#"<float name="Transparency">0.5</float>"#));
+
+        let decoded = crate::from_reader_default(output.as_bytes()).unwrap();
+        let part = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+        assert_eq!(
+            part.properties.get("Transparency"),
+            Some(&Variant::Float32(0.5))
+        );
+
+        let too_large = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_child(InstanceBuilder::new("Part").with_property("Transparency", 1e300f64)),
+        );
+
+        assert!(encode_with(&too_large, EncodeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn widen_float32_to_float64() {
+        let _ = env_logger::try_init();
+
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder")
+                .with_child(InstanceBuilder::new("NumberValue").with_property("Value", 0.25f32)),
+        );
+
+        assert!(encode_with(&tree, EncodeOptions::default()).is_err());
+
+        let output = encode_with(&tree, EncodeOptions::new().widen_floats(true)).unwrap();
+        assert!(output.contains(r#"<double name="Value">0.25</double>"#));
+    }
+
+    #[test]
+    fn float_precision_loss() {
+        assert!(!lost_precision(0.1, 0.1f64 as f32));
+        assert!(!lost_precision(f64::from(std::f32::MAX), std::f32::MAX));
+        assert!(!lost_precision(0.0, 0.0));
+        assert!(lost_precision(1e-40, 1e-40f64 as f32));
+        assert!(lost_precision(1e-50, 1e-50f64 as f32));
+
+        assert_eq!(narrow_float(1e300), None);
+        assert_eq!(narrow_float(-1e300), None);
+        assert_eq!(narrow_float(f64::from(std::f32::MAX)), Some(std::f32::MAX));
+        assert_eq!(narrow_float(std::f64::INFINITY), Some(std::f32::INFINITY));
+        assert!(narrow_float(std::f64::NAN).unwrap().is_nan());
+    }
+
+    #[test]
+    fn selected_instances() {
+        let _ = env_logger::try_init();
+
+        let mut tree = WeakDom::new(InstanceBuilder::new("Folder"));
+        let root_ref = tree.root_ref();
+
+        let first = tree.insert(root_ref, InstanceBuilder::new("Folder").with_name("First"));
+        let second = tree.insert(root_ref, InstanceBuilder::new("Folder").with_name("Second"));
+        let third = tree.insert(root_ref, InstanceBuilder::new("Folder").with_name("Third"));
+
+        tree.insert(
+            first,
+            InstanceBuilder::new("ObjectValue")
+                .with_name("Inside")
+                .with_property("Value", second),
+        );
+        tree.insert(
+            first,
+            InstanceBuilder::new("ObjectValue")
+                .with_name("Outside")
+                .with_property("Value", third),
+        );
+
+        let mut encoded = Vec::new();
+        crate::encode(&tree, &[first, second], &mut encoded).unwrap();
+
+        let output = std::str::from_utf8(&encoded).unwrap();
+        assert!(output.contains("First"));
+        assert!(output.contains("Second"));
+        assert!(!output.contains("Third"));
+
+        let decoded = crate::from_reader_default(encoded.as_slice()).unwrap();
+        let children = decoded.root().children();
+        assert_eq!(children.len(), 2);
+
+        let decoded_first = children[0];
+        let decoded_second = children[1];
+        assert_eq!(decoded.get_by_ref(decoded_second).unwrap().name, "Second");
+
+        let value_of = |name: &str| {
+            let child = decoded.find_first_child(decoded_first, name).unwrap();
+            decoded
+                .get_by_ref(child)
+                .unwrap()
+                .properties
+                .get("Value")
+                .cloned()
+        };
+
+        assert_eq!(value_of("Inside"), Some(Variant::Ref(decoded_second)));
+        assert_eq!(value_of("Outside"), Some(Variant::Ref(Ref::none())));
+    }
+
+    #[test]
+    fn float_format() {
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Part")
+                    .with_property("Transparency", 0.1f32)
+                    .with_property("Velocity", Vector3::new(0.1, 1.0, -2.5)),
+            ),
+        );
+
+        let shortest = encode_with(&tree, EncodeOptions::new()).unwrap();
+        assert!(shortest.contains(r#"<float name="Transparency">0.1</float>"#));
+        assert!(shortest.contains("<X>0.1</X>"));
+        assert!(shortest.contains("<Y>1</Y>"));
+
+        let full =
+            encode_with(&tree, EncodeOptions::new().float_format(FloatFormat::Full)).unwrap();
+        assert!(full.contains(r#"<float name="Transparency">0.100000001</float>"#));
+        assert!(full.contains("<X>0.100000001</X>"));
+        assert!(full.contains("<Y>1</Y>"));
+        assert!(full.contains("<Z>-2.5</Z>"));
+
+        // Both formats decode to exactly the same values.
+        let decode = |encoded: &str| {
+            let decoded = crate::from_str_default(encoded).unwrap();
+            let part = decoded.get_by_ref(decoded.root().children()[0]).unwrap();
+            (
+                part.properties["Transparency"].clone(),
+                part.properties["Velocity"].clone(),
+            )
+        };
+        assert_eq!(decode(&shortest), decode(&full));
+        assert_eq!(decode(&full).0, Variant::Float32(0.1));
+    }
+
+    #[test]
+    fn enum_item() {
+        let tree = WeakDom::new(InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("Part").with_property("Material", EnumItem::new("Material", 256)),
+        ));
+
+        let encoded = encode_with(&tree, EncodeOptions::new()).unwrap();
+        assert!(encoded.contains(r#"<token name="Material">256</token>"#));
+    }
+
+    /// Ensures that the document is written to the output as it's built,
+    /// instead of being built in memory and written all at once.
+    #[test]
+    fn writes_incrementally() {
+        #[derive(Default)]
+        struct CountingSink {
+            total: usize,
+            writes: usize,
+            largest_write: usize,
+        }
+
+        impl Write for CountingSink {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.total += buf.len();
+                self.writes += 1;
+                self.largest_write = self.largest_write.max(buf.len());
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let value = "x".repeat(1024);
+        let tree = WeakDom::new(InstanceBuilder::new("Folder").with_children(
+            (0..2000).map(|_| {
+                InstanceBuilder::new("StringValue").with_property("Value", value.as_str())
+            }),
+        ));
+
+        let mut sink = CountingSink::default();
+        encode_internal(
+            &mut sink,
+            &tree,
+            tree.root().children(),
+            EncodeOptions::new(),
+        )
+        .unwrap();
+
+        assert!(sink.total > 2000 * value.len());
+        assert!(sink.writes > 2000);
+
+        // No write is much larger than the largest value in the tree, so the
+        // document was never held in one buffer.
+        assert!(sink.largest_write <= 2 * value.len());
+    }
+
+    #[test]
+    fn validate_enum_values() {
+        let mut part_type = EnumDescriptor::new("PartType");
+        part_type.items.insert("Ball".into(), 0);
+        part_type.items.insert("Block".into(), 1);
+        part_type.items.insert("Cylinder".into(), 2);
+
+        let mut database = ReflectionDatabase::new();
+        database.enums.insert("PartType".into(), part_type);
+
+        let validate = |enum_name: &str, value: Variant| {
+            validate_enum(&database, "Part", "Shape", enum_name, &value)
+        };
+
+        assert!(validate("PartType", Variant::EnumValue(EnumValue::from_u32(1))).is_ok());
+        assert!(validate("PartType", Variant::EnumItem(EnumItem::new("PartType", 2))).is_ok());
+
+        let error = validate("PartType", Variant::EnumValue(EnumValue::from_u32(99))).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Property Part.Shape is set to 99, which is not an item of the enum PartType"
+        );
+
+        // Enums that the database doesn't describe can't be checked.
+        assert!(validate("Material", Variant::EnumValue(EnumValue::from_u32(99))).is_ok());
+    }
+
+    #[test]
+    fn validate_enums_option() {
+        let tree = WeakDom::new(InstanceBuilder::new("Folder").with_child(
+            InstanceBuilder::new("Part").with_property("Material", EnumValue::from_u32(256)),
+        ));
+
+        // The bundled database doesn't describe any enums, so nothing can be
+        // checked against it.
+        let encoded = encode_with(&tree, EncodeOptions::new().validate_enums(true)).unwrap();
+        assert!(encoded.contains(r#"<token name="Material">256</token>"#));
+    }
+
+    #[test]
+    fn validate_enums_with_database() {
+        let mut material = EnumDescriptor::new("Material");
+        material.items.insert("Plastic".into(), 256);
+        material.items.insert("Wood".into(), 512);
+
+        let mut database = ReflectionDatabase::new();
+        database.enums.insert("Material".into(), material);
+
+        let part_with_material = |value: u32| {
+            WeakDom::new(InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("Part").with_property("Material", EnumValue::from_u32(value)),
+            ))
+        };
+        let options = EncodeOptions::new()
+            .validate_enums(true)
+            .enum_database(database);
+
+        let encoded = encode_with(&part_with_material(512), options.clone()).unwrap();
+        assert!(encoded.contains(r#"<token name="Material">512</token>"#));
+
+        let error = encode_with(&part_with_material(99), options.clone()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Property Part.Material is set to 99, which is not an item of the enum Material"
+        );
+
+        // Without validation, any value is written.
+        let options = options.validate_enums(false);
+        assert!(encode_with(&part_with_material(99), options).is_ok());
+    }
+
+    #[test]
+    fn skip_non_serialized() {
+        let tree = WeakDom::new(
+            InstanceBuilder::new("Folder").with_child(
+                InstanceBuilder::new("StringValue")
+                    .with_property("Value", "Hello")
+                    .with_property("RobloxLocked", true),
+            ),
+        );
+        let locked = r#"<bool name="RobloxLocked">true</bool>"#;
+
+        let write_unknown =
+            EncodeOptions::new().property_behavior(EncodePropertyBehavior::WriteUnknown);
+        let encoded = encode_with(&tree, write_unknown.clone()).unwrap();
+        assert!(encoded.contains(locked));
+
+        let encoded = encode_with(&tree, write_unknown.skip_non_serialized(true)).unwrap();
+        assert!(!encoded.contains(locked));
+        assert!(encoded.contains(r#"<string name="Value">Hello</string>"#));
+
+        let error_on_unknown =
+            EncodeOptions::new().property_behavior(EncodePropertyBehavior::ErrorOnUnknown);
+        assert!(encode_with(&tree, error_on_unknown.clone()).is_err());
+        assert!(encode_with(&tree, error_on_unknown.skip_non_serialized(true)).is_ok());
+    }
+}
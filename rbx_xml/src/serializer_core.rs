@@ -1,4 +1,7 @@
-use std::{fmt::Write as FmtWrite, io::Write};
+use std::{
+    fmt::{self, Write as FmtWrite},
+    io::Write,
+};
 
 use xml::writer::{EmitterConfig, EventWriter};
 
@@ -7,6 +10,7 @@ pub use xml::writer::XmlEvent as XmlWriteEvent;
 use crate::{
     core::XmlType,
     error::{EncodeError as NewEncodeError, EncodeErrorKind},
+    serializer::FloatFormat,
 };
 
 /// A wrapper around an xml-rs `EventWriter` as well as other state kept around
@@ -14,6 +18,7 @@ use crate::{
 pub struct XmlEventWriter<W> {
     inner: EventWriter<W>,
     character_buffer: String,
+    float_format: FloatFormat,
 }
 
 impl<W: Write> XmlEventWriter<W> {
@@ -28,6 +33,16 @@ impl<W: Write> XmlEventWriter<W> {
         XmlEventWriter {
             inner,
             character_buffer: String::new(),
+            float_format: FloatFormat::default(),
+        }
+    }
+
+    /// Sets how floating point values written with `write_float` should be
+    /// formatted.
+    pub fn with_float_format(self, float_format: FloatFormat) -> Self {
+        XmlEventWriter {
+            float_format,
+            ..self
         }
     }
 
@@ -67,6 +82,33 @@ impl<W: Write> XmlEventWriter<W> {
         Ok(())
     }
 
+    /// Writes a floating point value as characters, formatted according to the
+    /// writer's `FloatFormat`.
+    ///
+    /// Infinity and NaN are written as `INF`, `-INF`, and `NAN`, which is how
+    /// Roblox writes them. Rust's own spellings, `inf` and `NaN`, can't be read
+    /// by Roblox Studio.
+    pub fn write_float<T: XmlFloat>(&mut self, value: T) -> Result<(), NewEncodeError> {
+        if value.is_nan() {
+            self.character_buffer.push_str("NAN");
+        } else if value.is_infinite() {
+            let sign = if value.is_sign_negative() { "-" } else { "" };
+            write!(self.character_buffer, "{}INF", sign).unwrap();
+        } else {
+            match self.float_format {
+                FloatFormat::Shortest => write!(self.character_buffer, "{}", value).unwrap(),
+                FloatFormat::Full => {
+                    write_significant_digits(&mut self.character_buffer, value, T::DIGITS).unwrap()
+                }
+            }
+        }
+
+        write_characters_or_cdata(&mut self.inner, &self.character_buffer)?;
+        self.character_buffer.clear();
+
+        Ok(())
+    }
+
     pub fn write_value<T: XmlType>(&mut self, value: &T) -> Result<(), NewEncodeError> {
         value.write_xml(self)
     }
@@ -92,22 +134,78 @@ impl<W: Write> XmlEventWriter<W> {
         self.write_characters(value)?;
         self.write(XmlWriteEvent::end_element())
     }
+}
 
-    /// Writes a list of values that implement `Display`, with each wrapped in
-    /// an associated tag. This method uses the same optimization as
-    /// `write_characters` to avoid extra allocations.
-    pub fn write_tag_array<T: std::fmt::Display>(
-        &mut self,
-        values: &[T],
-        tags: &[&str],
-    ) -> Result<(), NewEncodeError> {
-        assert_eq!(values.len(), tags.len());
+/// Floating point types that can be written with `XmlEventWriter::write_float`.
+pub trait XmlFloat: fmt::Display + fmt::LowerExp + Copy {
+    /// The number of significant decimal digits needed to tell apart every
+    /// value of this type.
+    const DIGITS: usize;
+
+    fn is_nan(self) -> bool;
+    fn is_infinite(self) -> bool;
+    fn is_sign_negative(self) -> bool;
+}
+
+macro_rules! xml_float {
+    ($rust_type: ident, $digits: expr) => {
+        impl XmlFloat for $rust_type {
+            const DIGITS: usize = $digits;
 
-        for (index, component) in values.iter().enumerate() {
-            self.write_tag_characters(tags[index], component)?;
+            fn is_nan(self) -> bool {
+                $rust_type::is_nan(self)
+            }
+
+            fn is_infinite(self) -> bool {
+                $rust_type::is_infinite(self)
+            }
+
+            fn is_sign_negative(self) -> bool {
+                $rust_type::is_sign_negative(self)
+            }
         }
+    };
+}
 
-        Ok(())
+xml_float!(f32, 9);
+xml_float!(f64, 17);
+
+/// Writes a float rounded to the given number of significant digits, like the
+/// `%g` format of C's `printf`, which is how Roblox writes floats. Trailing
+/// zeros are removed, and scientific notation is only used for very large or
+/// very small values.
+fn write_significant_digits<T: XmlFloat>(
+    output: &mut String,
+    value: T,
+    digits: usize,
+) -> fmt::Result {
+    let scientific = format!("{:.*e}", digits - 1, value);
+
+    // Infinity and NaN have no exponent, and are written as-is.
+    let (mantissa, exponent) = match scientific.find('e') {
+        Some(index) => (
+            &scientific[..index],
+            scientific[index + 1..].parse::<i32>().unwrap(),
+        ),
+        None => return write!(output, "{}", value),
+    };
+
+    if exponent < -5 || exponent >= digits as i32 {
+        write!(output, "{}e{}", trim_fraction(mantissa), exponent)
+    } else {
+        let precision = (digits as i32 - 1 - exponent) as usize;
+        let fixed = format!("{:.*}", precision, value);
+        write!(output, "{}", trim_fraction(&fixed))
+    }
+}
+
+/// Removes trailing zeros after the decimal point of a number, along with the
+/// decimal point itself if nothing is left after it.
+fn trim_fraction(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
     }
 }
 
@@ -145,3 +243,68 @@ fn write_characters_or_cdata<W: Write>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn full<T: XmlFloat>(value: T) -> String {
+        let mut output = String::new();
+        write_significant_digits(&mut output, value, T::DIGITS).unwrap();
+        output
+    }
+
+    #[test]
+    fn significant_digits() {
+        assert_eq!(full(0.1f32), "0.100000001");
+        assert_eq!(full(0.1f64), "0.10000000000000001");
+        assert_eq!(full(1.0f32), "1");
+        assert_eq!(full(-0.5f32), "-0.5");
+        assert_eq!(full(100.0f32), "100");
+        assert_eq!(full(1e20f32), "1.00000002e20");
+        assert_eq!(full(1e-7f32), "1.00000001e-7");
+        assert_eq!(full(std::f32::INFINITY), "inf");
+    }
+
+    #[test]
+    fn non_finite_floats() {
+        for &float_format in &[FloatFormat::Shortest, FloatFormat::Full] {
+            let mut output = Vec::new();
+            let mut writer =
+                XmlEventWriter::from_output(&mut output).with_float_format(float_format);
+
+            writer
+                .write(XmlWriteEvent::start_element("floats"))
+                .unwrap();
+            for &value in &[std::f32::INFINITY, std::f32::NEG_INFINITY, std::f32::NAN] {
+                writer.write_float(value).unwrap();
+                writer.write(XmlWriteEvent::characters(" ")).unwrap();
+            }
+            writer.write_float(-std::f64::NAN).unwrap();
+            writer.end_element().unwrap();
+
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                "<floats>INF -INF NAN NAN</floats>"
+            );
+        }
+    }
+
+    #[test]
+    fn significant_digits_round_trip() {
+        for &value in &[
+            0.1f32,
+            1.0 / 3.0,
+            123_456.79,
+            std::f32::MAX,
+            std::f32::MIN_POSITIVE,
+            -2.5e-9,
+        ] {
+            assert_eq!(full(value).parse::<f32>().unwrap(), value);
+        }
+
+        for &value in &[0.1f64, 1.0 / 3.0, 1e300, std::f64::MIN_POSITIVE, -7.25e-12] {
+            assert_eq!(full(value).parse::<f64>().unwrap(), value);
+        }
+    }
+}
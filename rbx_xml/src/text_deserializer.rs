@@ -0,0 +1,193 @@
+//! Deserializer that reads an XML file and creates a debug representation of
+//! its element tree. It's intended to be used to snapshot test the XML
+//! serializer without suffering from same-inverse-bug problems.
+
+#![allow(missing_docs)]
+
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+use xml::{
+    attribute::OwnedAttribute,
+    reader::{EventReader, ParserConfig, XmlEvent},
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodedXmlModel {
+    pub attributes: Vec<DecodedAttribute>,
+    pub children: Vec<DecodedNode>,
+}
+
+impl DecodedXmlModel {
+    pub fn from_reader<R: Read>(reader: R) -> Self {
+        let config = ParserConfig::new().ignore_comments(true);
+        let mut stack: Vec<DecodedElement> = Vec::new();
+        let mut root = None;
+
+        for event in EventReader::new_with_config(reader, config) {
+            match event.expect("invalid XML") {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => {
+                    stack.push(DecodedElement {
+                        tag: name.local_name,
+                        attributes: decode_attributes(attributes),
+                        text: None,
+                        cdata: false,
+                        children: Vec::new(),
+                    });
+                }
+                XmlEvent::EndElement { .. } => {
+                    let element = stack.pop().expect("unbalanced XML elements");
+
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => root = Some(element),
+                    }
+                }
+                XmlEvent::Characters(text) => {
+                    let element = stack.last_mut().expect("text outside of an element");
+                    element.text.get_or_insert_with(String::new).push_str(&text);
+                }
+                XmlEvent::CData(text) => {
+                    let element = stack.last_mut().expect("CDATA outside of an element");
+                    element.text.get_or_insert_with(String::new).push_str(&text);
+                    element.cdata = true;
+                }
+                _ => {}
+            }
+        }
+
+        let root = root.expect("file had no root element");
+        assert_eq!(root.tag, "roblox", "root element must be named roblox");
+
+        DecodedXmlModel {
+            attributes: root.attributes,
+            children: root.children.into_iter().map(DecodedNode::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum DecodedNode {
+    Item(DecodedItem),
+    Element(DecodedElement),
+}
+
+impl From<DecodedElement> for DecodedNode {
+    fn from(element: DecodedElement) -> Self {
+        if element.tag == "Item" {
+            DecodedNode::Item(DecodedItem::from(element))
+        } else {
+            DecodedNode::Element(element)
+        }
+    }
+}
+
+/// An `Item` element, which holds one instance, its properties, and its
+/// children.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodedItem {
+    pub class: Option<String>,
+    pub referent: Option<String>,
+    pub properties: Vec<DecodedProperty>,
+    pub children: Vec<DecodedNode>,
+}
+
+impl From<DecodedElement> for DecodedItem {
+    fn from(element: DecodedElement) -> Self {
+        let class = find_attribute(&element.attributes, "class");
+        let referent = find_attribute(&element.attributes, "referent");
+        let mut properties = Vec::new();
+        let mut children = Vec::new();
+
+        for child in element.children {
+            if child.tag == "Properties" {
+                properties.extend(child.children.into_iter().map(DecodedProperty::from));
+            } else {
+                children.push(DecodedNode::from(child));
+            }
+        }
+
+        DecodedItem {
+            class,
+            referent,
+            properties,
+            children,
+        }
+    }
+}
+
+/// A property element, like `<string name="Name">Folder</string>`. The tag
+/// of the element is the property's type.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodedProperty {
+    pub type_name: String,
+    pub name: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub cdata: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DecodedElement>,
+}
+
+impl From<DecodedElement> for DecodedProperty {
+    fn from(element: DecodedElement) -> Self {
+        DecodedProperty {
+            name: find_attribute(&element.attributes, "name"),
+            type_name: element.tag,
+            text: element.text,
+            cdata: element.cdata,
+            children: element.children,
+        }
+    }
+}
+
+/// Any other element, kept exactly as it appeared in the file.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodedElement {
+    pub tag: String,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<DecodedAttribute>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub cdata: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DecodedElement>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodedAttribute {
+    pub name: String,
+    pub value: String,
+}
+
+fn decode_attributes(attributes: Vec<OwnedAttribute>) -> Vec<DecodedAttribute> {
+    attributes
+        .into_iter()
+        .map(|attribute| DecodedAttribute {
+            name: attribute.name.local_name,
+            value: attribute.value,
+        })
+        .collect()
+}
+
+fn find_attribute(attributes: &[DecodedAttribute], name: &str) -> Option<String> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name == name)
+        .map(|attribute| attribute.value.clone())
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
@@ -0,0 +1,80 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::Axes;
+
+use crate::{
+    core::XmlType,
+    deserializer_core::XmlEventReader,
+    error::{DecodeError, DecodeErrorKind, EncodeError},
+    serializer_core::XmlEventWriter,
+};
+
+// Axes are stored as their bitmask, wrapped in an inner tag:
+//
+// <Axes name="foo">
+//     <axes>7</axes>
+// </Axes>
+impl XmlType for Axes {
+    const XML_TAG_NAME: &'static str = "Axes";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        writer.write_tag_characters("axes", self.bits())
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        let bits: u8 = reader
+            .read_tag_contents("axes")?
+            .parse()
+            .map_err(|e| reader.error(e))?;
+
+        Axes::from_bits(bits)
+            .ok_or_else(|| reader.error(DecodeErrorKind::InvalidContent("invalid Axes bitmask")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    #[test]
+    fn round_trip_axes() {
+        test_util::test_xml_round_trip(&Axes::empty());
+        test_util::test_xml_round_trip(&Axes::Y);
+        test_util::test_xml_round_trip(&Axes::all());
+    }
+
+    #[test]
+    fn deserialize_axes() {
+        test_util::test_xml_deserialize(
+            r#"
+                <Axes name="foo">
+                    <axes>2</axes>
+                </Axes>
+            "#,
+            &Axes::Y,
+        );
+    }
+
+    #[test]
+    fn serialize_axes() {
+        test_util::test_xml_serialize(
+            r#"
+                <Axes name="foo">
+                    <axes>7</axes>
+                </Axes>
+            "#,
+            &Axes::all(),
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_axes() {
+        let mut reader =
+            XmlEventReader::from_source(r#"<Axes name="foo"><axes>8</axes></Axes>"#.as_bytes());
+        reader.next().unwrap().unwrap();
+
+        assert!(Axes::read_outer_xml(&mut reader).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use rbx_dom_weak::types::{CFrame, Matrix3, Vector3};
 
 use crate::{
     core::XmlType,
-    deserializer_core::XmlEventReader,
+    deserializer_core::{XmlEventReader, XmlReadEvent},
     error::{DecodeError, EncodeError},
     serializer_core::XmlEventWriter,
 };
@@ -33,7 +33,9 @@ impl XmlType for CFrame {
             self.orientation.z.z,
         ];
 
-        writer.write_tag_array(as_slice, &TAG_NAMES)?;
+        for (component, tag_name) in as_slice.iter().zip(&TAG_NAMES) {
+            writer.write_value_in_tag(component, tag_name)?;
+        }
 
         Ok(())
     }
@@ -70,6 +72,38 @@ impl XmlType for CFrame {
     }
 }
 
+/// Used by properties that may or may not have a CFrame value, like
+/// `Model.WorldPivotData`.
+pub type OptionalCFrame = Option<CFrame>;
+
+// An OptionalCFrame with a value is serialized as:
+// <OptionalCoordinateFrame name="WorldPivotData">
+//     <CFrame><X>0</X> ... <R22>1</R22></CFrame>
+// </OptionalCoordinateFrame>
+//
+// An OptionalCFrame without a value has no contents.
+
+impl XmlType for OptionalCFrame {
+    const XML_TAG_NAME: &'static str = "OptionalCoordinateFrame";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        if let Some(value) = self {
+            writer.write_value_in_tag(value, "CFrame")?;
+        }
+
+        Ok(())
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        match reader.expect_peek()? {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "CFrame" => {
+                Ok(Some(reader.read_value_in_tag("CFrame")?))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -89,4 +123,27 @@ mod test {
 
         test_util::test_xml_round_trip(&test_input);
     }
+
+    #[test]
+    fn round_trip_optional_some() {
+        let test_input: OptionalCFrame = Some(CFrame::new(
+            Vector3::new(1.0, 2.0, 3.0),
+            Matrix3::identity(),
+        ));
+
+        test_util::test_xml_round_trip(&test_input);
+    }
+
+    #[test]
+    fn round_trip_optional_none() {
+        test_util::test_xml_round_trip(&OptionalCFrame::None);
+    }
+
+    #[test]
+    fn deserialize_optional_none() {
+        test_util::test_xml_deserialize(
+            r#"<OptionalCoordinateFrame name="foo"></OptionalCoordinateFrame>"#,
+            &OptionalCFrame::None,
+        );
+    }
 }
@@ -7,6 +7,7 @@ use crate::{
     deserializer_core::XmlEventReader,
     error::{DecodeError, DecodeErrorKind, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
+    types::numbers::parse_xml_float,
 };
 
 impl XmlType for ColorSequence {
@@ -14,13 +15,13 @@ impl XmlType for ColorSequence {
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
         for keypoint in &self.keypoints {
-            writer.write_characters(keypoint.time)?;
+            writer.write_float(keypoint.time)?;
             writer.write(XmlWriteEvent::characters(" "))?;
-            writer.write_characters(keypoint.color.r)?;
+            writer.write_float(keypoint.color.r)?;
             writer.write(XmlWriteEvent::characters(" "))?;
-            writer.write_characters(keypoint.color.g)?;
+            writer.write_float(keypoint.color.g)?;
             writer.write(XmlWriteEvent::characters(" "))?;
-            writer.write_characters(keypoint.color.b)?;
+            writer.write_float(keypoint.color.b)?;
             writer.write(XmlWriteEvent::characters(" "))?;
 
             // Envelope is always 0 for ColorSequenceKeypoint. This value isn't
@@ -37,7 +38,7 @@ impl XmlType for ColorSequence {
         let mut pieces = contents
             .split(" ")
             .filter(|slice| !slice.is_empty())
-            .map(|piece| piece.parse::<f32>().map_err(|e| reader.error(e)));
+            .map(|piece| parse_xml_float(piece).map_err(|e| reader.error(e)));
         let mut keypoints = Vec::new();
 
         let wrong_length = || {
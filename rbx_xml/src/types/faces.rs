@@ -0,0 +1,81 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::Faces;
+
+use crate::{
+    core::XmlType,
+    deserializer_core::XmlEventReader,
+    error::{DecodeError, DecodeErrorKind, EncodeError},
+    serializer_core::XmlEventWriter,
+};
+
+// Faces are stored as their bitmask, wrapped in an inner tag:
+//
+// <Faces name="foo">
+//     <faces>63</faces>
+// </Faces>
+impl XmlType for Faces {
+    const XML_TAG_NAME: &'static str = "Faces";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        writer.write_tag_characters("faces", self.bits())
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        let bits: u8 = reader
+            .read_tag_contents("faces")?
+            .parse()
+            .map_err(|e| reader.error(e))?;
+
+        Faces::from_bits(bits)
+            .ok_or_else(|| reader.error(DecodeErrorKind::InvalidContent("invalid Faces bitmask")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    #[test]
+    fn round_trip_faces() {
+        test_util::test_xml_round_trip(&Faces::empty());
+        test_util::test_xml_round_trip(&Faces::BOTTOM);
+        test_util::test_xml_round_trip(&Faces::all());
+    }
+
+    #[test]
+    fn deserialize_faces() {
+        test_util::test_xml_deserialize(
+            r#"
+                <Faces name="foo">
+                    <faces>16</faces>
+                </Faces>
+            "#,
+            &Faces::BOTTOM,
+        );
+    }
+
+    #[test]
+    fn serialize_faces() {
+        test_util::test_xml_serialize(
+            r#"
+                <Faces name="foo">
+                    <faces>63</faces>
+                </Faces>
+            "#,
+            &Faces::all(),
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_faces() {
+        let mut reader = XmlEventReader::from_source(
+            r#"<Faces name="foo"><faces>64</faces></Faces>"#.as_bytes(),
+        );
+        reader.next().unwrap().unwrap();
+
+        assert!(Faces::read_outer_xml(&mut reader).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::{Content, Font, FontStyle, FontWeight};
+
+use crate::{
+    core::XmlType,
+    deserializer_core::{XmlEventReader, XmlReadEvent},
+    error::{DecodeError, DecodeErrorKind, EncodeError},
+    serializer_core::XmlEventWriter,
+};
+
+// A Font is serialized as:
+// <Font name="FontFace">
+//     <Family><url>rbxasset://fonts/families/SourceSansPro.json</url></Family>
+//     <Weight>400</Weight>
+//     <Style>Normal</Style>
+//     <CachedFaceId><url>rbxasset://fonts/SourceSansPro-Regular.ttf</url></CachedFaceId>
+// </Font>
+//
+// The CachedFaceId tag is optional.
+impl XmlType for Font {
+    const XML_TAG_NAME: &'static str = "Font";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        writer.write_value_in_tag(&Content::from(self.family.as_str()), "Family")?;
+        writer.write_tag_characters("Weight", self.weight.to_u16())?;
+        writer.write_tag_characters("Style", style_to_str(self.style))?;
+
+        if let Some(cached_face_id) = &self.cached_face_id {
+            writer.write_value_in_tag(&Content::from(cached_face_id.as_str()), "CachedFaceId")?;
+        }
+
+        Ok(())
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        let family: Content = reader.read_value_in_tag("Family")?;
+
+        let weight: u16 = reader
+            .read_tag_contents("Weight")?
+            .parse()
+            .map_err(|e| reader.error(e))?;
+        let weight = FontWeight::from_u16(weight)
+            .ok_or_else(|| reader.error(DecodeErrorKind::InvalidContent("unknown font weight")))?;
+
+        let style = reader.read_tag_contents("Style")?;
+        let style = style_from_str(&style)
+            .ok_or_else(|| reader.error(DecodeErrorKind::InvalidContent("unknown font style")))?;
+
+        let cached_face_id = match reader.expect_peek()? {
+            XmlReadEvent::StartElement { name, .. } if name.local_name == "CachedFaceId" => {
+                let content: Content = reader.read_value_in_tag("CachedFaceId")?;
+                Some(content.into_string())
+            }
+            _ => None,
+        };
+
+        Ok(Font {
+            family: family.into_string(),
+            weight,
+            style,
+            cached_face_id,
+        })
+    }
+}
+
+fn style_to_str(style: FontStyle) -> &'static str {
+    match style {
+        FontStyle::Normal => "Normal",
+        FontStyle::Italic => "Italic",
+    }
+}
+
+fn style_from_str(value: &str) -> Option<FontStyle> {
+    match value {
+        "Normal" => Some(FontStyle::Normal),
+        "Italic" => Some(FontStyle::Italic),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    #[test]
+    fn round_trip_font() {
+        test_util::test_xml_round_trip(&Font::default());
+    }
+
+    #[test]
+    fn round_trip_font_cached_face_id() {
+        test_util::test_xml_round_trip(&Font {
+            family: "rbxasset://fonts/families/Arial.json".to_owned(),
+            weight: FontWeight::Bold,
+            style: FontStyle::Italic,
+            cached_face_id: Some("rbxasset://fonts/arialbd.ttf".to_owned()),
+        });
+    }
+
+    #[test]
+    fn deserialize_font() {
+        test_util::test_xml_deserialize(
+            r#"
+                <Font name="FontFace">
+                    <Family><url>rbxasset://fonts/families/GothamSSm.json</url></Family>
+                    <Weight>700</Weight>
+                    <Style>Normal</Style>
+                    <CachedFaceId><url>rbxasset://fonts/GothamSSm-Bold.otf</url></CachedFaceId>
+                </Font>
+            "#,
+            &Font {
+                family: "rbxasset://fonts/families/GothamSSm.json".to_owned(),
+                weight: FontWeight::Bold,
+                style: FontStyle::Normal,
+                cached_face_id: Some("rbxasset://fonts/GothamSSm-Bold.otf".to_owned()),
+            },
+        );
+    }
+
+    #[test]
+    fn serialize_font() {
+        test_util::test_xml_serialize(
+            r#"
+                <Font name="foo">
+                    <Family><url>rbxasset://fonts/families/SourceSansPro.json</url></Family>
+                    <Weight>400</Weight>
+                    <Style>Normal</Style>
+                </Font>
+            "#,
+            &Font::default(),
+        );
+    }
+}
@@ -7,6 +7,7 @@
 //! 2. Add a 'mod' statement immediately below this comment
 //! 3. Add the type(s) to the declare_rbx_types! macro invocation
 
+mod axes;
 mod binary_string;
 mod bool;
 mod cframe;
@@ -14,6 +15,8 @@ mod color_sequence;
 mod colors;
 mod content;
 mod enumeration;
+mod faces;
+mod font;
 mod number_range;
 mod number_sequence;
 mod numbers;
@@ -21,17 +24,19 @@ mod physical_properties;
 mod ray;
 mod rect;
 mod referent;
+mod security_capabilities;
 mod shared_string;
 mod strings;
 mod udims;
+mod unique_id;
 mod vectors;
 
 use std::io::{Read, Write};
 
 use rbx_dom_weak::types::{
-    BinaryString, CFrame, Color3, Color3uint8, ColorSequence, Content, EnumValue, NumberRange,
-    NumberSequence, PhysicalProperties, Ray, Rect, Ref, UDim, UDim2, Variant, Vector2,
-    Vector2int16, Vector3, Vector3int16,
+    Axes, BinaryString, CFrame, Color3, Color3uint8, ColorSequence, Content, EnumValue, Faces,
+    Font, NumberRange, NumberSequence, PhysicalProperties, Ray, Rect, Ref, SecurityCapabilities,
+    UDim, UDim2, UniqueId, Variant, Vector2, Vector2int16, Vector3, Vector3int16,
 };
 
 use crate::{
@@ -44,6 +49,7 @@ use crate::{
 };
 
 use self::{
+    cframe::OptionalCFrame,
     referent::{read_ref, write_ref},
     shared_string::{read_shared_string, write_shared_string},
 };
@@ -91,8 +97,32 @@ macro_rules! declare_rbx_types {
 
                 // BrickColor values just encode as 32-bit ints, and have no
                 // unique appearance for reading.
-                // Variant::BrickColor(value) =>
-                //     self::numbers::Int32Type::write_xml(writer, xml_property_name, &(*value as i32)),
+                Variant::BrickColor(value) => {
+                    i32::from(value.to_number()).write_outer_xml(xml_property_name, writer)
+                }
+
+                // EnumItem values are written as plain EnumValues, since the
+                // name of the enum is already part of the property's type.
+                Variant::EnumItem(value) => {
+                    EnumValue::from_u32(value.value).write_outer_xml(xml_property_name, writer)
+                }
+
+                // Tags are stored as a BinaryString containing each tag
+                // separated by a null byte.
+                Variant::Tags(value) => {
+                    BinaryString::from(value.encode()).write_outer_xml(xml_property_name, writer)
+                }
+
+                // Attributes are stored as a BinaryString using the same
+                // format as binary files.
+                Variant::Attributes(value) => {
+                    let mut buffer = Vec::new();
+                    value
+                        .to_writer(&mut buffer)
+                        .map_err(|err| writer.error(EncodeErrorKind::InvalidAttributes(err)))?;
+
+                    BinaryString::from(buffer).write_outer_xml(xml_property_name, writer)
+                }
 
                 Variant::Ref(value) => write_ref(writer, xml_property_name, *value, state),
                 Variant::SharedString(value) => write_shared_string(writer, xml_property_name, value, state),
@@ -106,6 +136,7 @@ macro_rules! declare_rbx_types {
 }
 
 declare_rbx_types! {
+    Axes: Axes,
     BinaryString: BinaryString,
     Bool: bool,
     CFrame: CFrame,
@@ -115,17 +146,22 @@ declare_rbx_types! {
     Color3uint8: Color3uint8,
     Content: Content,
     EnumValue: EnumValue,
+    Faces: Faces,
     NumberRange: NumberRange,
     NumberSequence: NumberSequence,
+    OptionalCFrame: OptionalCFrame,
     Float32: f32,
     Float64: f64,
+    Font: Font,
     Int32: i32,
     Int64: i64,
     PhysicalProperties: PhysicalProperties,
     Ray: Ray,
     Rect: Rect,
+    SecurityCapabilities: SecurityCapabilities,
     UDim2: UDim2,
     UDim: UDim,
+    UniqueId: UniqueId,
     Vector2: Vector2,
     Vector2int16: Vector2int16,
     Vector3: Vector3,
@@ -7,15 +7,16 @@ use crate::{
     deserializer_core::XmlEventReader,
     error::{DecodeError, DecodeErrorKind, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
+    types::numbers::parse_xml_float,
 };
 
 impl XmlType for NumberRange {
     const XML_TAG_NAME: &'static str = "NumberRange";
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
-        writer.write_characters(self.min)?;
+        writer.write_float(self.min)?;
         writer.write(XmlWriteEvent::characters(" "))?;
-        writer.write_characters(self.max)?;
+        writer.write_float(self.max)?;
         writer.write(XmlWriteEvent::characters(" "))?;
 
         Ok(())
@@ -26,7 +27,7 @@ impl XmlType for NumberRange {
         let mut pieces = contents
             .split(" ")
             .filter(|slice| !slice.is_empty())
-            .map(|piece| piece.parse::<f32>().map_err(|e| reader.error(e)));
+            .map(|piece| parse_xml_float(piece).map_err(|e| reader.error(e)));
 
         let min = pieces
             .next()
@@ -59,6 +60,11 @@ mod test {
         });
     }
 
+    #[test]
+    fn round_trip_number_range_equal_bounds() {
+        test_util::test_xml_round_trip(&NumberRange { min: 5.0, max: 5.0 });
+    }
+
     #[test]
     fn deserialize_number_range() {
         test_util::test_xml_deserialize(
@@ -84,4 +90,33 @@ mod test {
             },
         );
     }
+
+    #[test]
+    fn non_finite_number_range() {
+        let source = r#"<NumberRange name="foo">-INF INF </NumberRange>"#;
+        let value = NumberRange {
+            min: std::f32::NEG_INFINITY,
+            max: std::f32::INFINITY,
+        };
+
+        test_util::test_xml_serialize(source, &value);
+        test_util::test_xml_deserialize(source, &value);
+
+        // NaN isn't equal to itself, so it can't be checked with
+        // test_xml_deserialize.
+        let source = r#"<NumberRange name="foo">NAN 0 </NumberRange>"#;
+        test_util::test_xml_serialize(
+            source,
+            &NumberRange {
+                min: std::f32::NAN,
+                max: 0.0,
+            },
+        );
+
+        let mut reader = XmlEventReader::from_source(source.as_bytes());
+        reader.next().unwrap().unwrap();
+        let value = NumberRange::read_outer_xml(&mut reader).unwrap();
+        assert!(value.min.is_nan());
+        assert_eq!(value.max, 0.0);
+    }
 }
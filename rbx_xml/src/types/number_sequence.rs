@@ -7,6 +7,7 @@ use crate::{
     deserializer_core::XmlEventReader,
     error::{DecodeError, DecodeErrorKind, EncodeError},
     serializer_core::{XmlEventWriter, XmlWriteEvent},
+    types::numbers::parse_xml_float,
 };
 
 impl XmlType for NumberSequence {
@@ -14,11 +15,11 @@ impl XmlType for NumberSequence {
 
     fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
         for keypoint in &self.keypoints {
-            writer.write_characters(keypoint.time)?;
+            writer.write_float(keypoint.time)?;
             writer.write(XmlWriteEvent::characters(" "))?;
-            writer.write_characters(keypoint.value)?;
+            writer.write_float(keypoint.value)?;
             writer.write(XmlWriteEvent::characters(" "))?;
-            writer.write_characters(keypoint.envelope)?;
+            writer.write_float(keypoint.envelope)?;
             writer.write(XmlWriteEvent::characters(" "))?;
         }
 
@@ -30,7 +31,7 @@ impl XmlType for NumberSequence {
         let mut pieces = contents
             .split(" ")
             .filter(|slice| !slice.is_empty())
-            .map(|piece| piece.parse::<f32>().map_err(|e| reader.error(e)));
+            .map(|piece| parse_xml_float(piece).map_err(|e| reader.error(e)));
         let mut keypoints = Vec::new();
 
         let wrong_length = || {
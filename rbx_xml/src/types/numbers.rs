@@ -1,4 +1,7 @@
-use std::io::{Read, Write};
+use std::{
+    io::{Read, Write},
+    num::ParseFloatError,
+};
 
 use crate::{
     core::XmlType,
@@ -7,6 +10,18 @@ use crate::{
     serializer_core::XmlEventWriter,
 };
 
+/// Parses one of several floats that are written into the same element, like
+/// the components of a `NumberRange`. These use the same spellings as `float`
+/// elements for non-finite values: `INF`, `-INF`, and `NAN`.
+pub(crate) fn parse_xml_float(value: &str) -> Result<f32, ParseFloatError> {
+    match value {
+        "INF" => Ok(std::f32::INFINITY),
+        "-INF" => Ok(std::f32::NEG_INFINITY),
+        "NAN" => Ok(std::f32::NAN),
+        number => number.parse(),
+    }
+}
+
 macro_rules! float_type {
     ($rust_type: ident, $xml_name: expr) => {
         impl XmlType for $rust_type {
@@ -16,15 +31,7 @@ macro_rules! float_type {
                 &self,
                 writer: &mut XmlEventWriter<W>,
             ) -> Result<(), EncodeError> {
-                if *self == std::$rust_type::INFINITY {
-                    writer.write_characters("INF")
-                } else if *self == std::$rust_type::NEG_INFINITY {
-                    writer.write_characters("-INF")
-                } else if self.is_nan() {
-                    writer.write_characters("NAN")
-                } else {
-                    writer.write_characters(self)
-                }
+                writer.write_float(*self)
             }
 
             fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
@@ -43,6 +43,22 @@ mod test {
         });
     }
 
+    #[test]
+    fn round_trip_zero_rect() {
+        test_util::test_xml_round_trip(&Rect {
+            min: Vector2::new(0.0, 0.0),
+            max: Vector2::new(0.0, 0.0),
+        });
+    }
+
+    #[test]
+    fn round_trip_negative_rect() {
+        test_util::test_xml_round_trip(&Rect {
+            min: Vector2::new(-50.0, -25.5),
+            max: Vector2::new(-10.0, -0.5),
+        });
+    }
+
     #[test]
     fn deserialize_rect() {
         test_util::test_xml_deserialize(
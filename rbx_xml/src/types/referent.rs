@@ -31,7 +31,9 @@ pub fn write_ref<W: Write>(
 ) -> Result<(), EncodeError> {
     writer.write(XmlWriteEvent::start_element(XML_TAG_NAME).attr("name", xml_property_name))?;
 
-    if value.is_none() {
+    // Refs to instances that aren't part of this file can't be resolved, so
+    // they're written as null refs.
+    if value.is_none() || !state.is_serialized(value) {
         writer.write(XmlWriteEvent::characters("null"))?;
     } else {
         writer.write_characters(state.map_id(value))?;
@@ -49,14 +51,22 @@ pub fn read_ref<R: Read>(
     state: &mut ParseState,
 ) -> Result<Ref, DecodeError> {
     let ref_contents = reader.read_tag_contents(XML_TAG_NAME)?;
+    let ref_contents = ref_contents.trim();
 
-    if ref_contents != "null" {
+    // Modern files write null refs as "null", but older files sometimes used
+    // "nil" or left the tag empty instead.
+    let is_null = match ref_contents {
+        "null" | "nil" | "" => true,
+        _ => false,
+    };
+
+    if !is_null {
         // We need to rewrite this property as part of a follow-up pass.
         //
         // We might not know which ID this referent points to yet, so instead of
         // trying to handle the case where we do here, we just let all referents
         // get written later.
-        state.add_referent_rewrite(id, property_name.to_owned(), ref_contents);
+        state.add_referent_rewrite(id, property_name.to_owned(), ref_contents.to_owned());
     }
 
     Ok(Ref::none())
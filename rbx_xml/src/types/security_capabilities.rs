@@ -0,0 +1,72 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::SecurityCapabilities;
+
+use crate::{
+    core::XmlType,
+    deserializer_core::XmlEventReader,
+    error::{DecodeError, EncodeError},
+    serializer_core::XmlEventWriter,
+};
+
+// SecurityCapabilities values are stored as their bitmask written as an
+// unsigned decimal number:
+//
+// <SecurityCapabilities name="Capabilities">9223372036854775809</SecurityCapabilities>
+impl XmlType for SecurityCapabilities {
+    const XML_TAG_NAME: &'static str = "SecurityCapabilities";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        writer.write_characters(self.bits())
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        let contents = reader.read_characters()?;
+
+        contents
+            .trim()
+            .parse()
+            .map(SecurityCapabilities::from_bits)
+            .map_err(|e| reader.error(e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    #[test]
+    fn round_trip_security_capabilities() {
+        test_util::test_xml_round_trip(&SecurityCapabilities::from_bits(0));
+        test_util::test_xml_round_trip(&SecurityCapabilities::from_bits(0x8000_0000_0000_0001));
+        test_util::test_xml_round_trip(&SecurityCapabilities::from_bits(std::u64::MAX));
+    }
+
+    #[test]
+    fn deserialize_security_capabilities() {
+        test_util::test_xml_deserialize(
+            r#"<SecurityCapabilities name="foo">9223372036854775809</SecurityCapabilities>"#,
+            &SecurityCapabilities::from_bits(0x8000_0000_0000_0001),
+        );
+    }
+
+    #[test]
+    fn serialize_security_capabilities() {
+        test_util::test_xml_serialize(
+            r#"<SecurityCapabilities name="foo">9223372036854775809</SecurityCapabilities>"#,
+            &SecurityCapabilities::from_bits(0x8000_0000_0000_0001),
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_security_capabilities() {
+        let mut reader = XmlEventReader::from_source(
+            r#"<SecurityCapabilities name="foo">-1</SecurityCapabilities>"#.as_bytes(),
+        );
+        reader.next().unwrap().unwrap();
+
+        assert!(SecurityCapabilities::read_outer_xml(&mut reader).is_err());
+    }
+}
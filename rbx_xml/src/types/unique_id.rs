@@ -0,0 +1,67 @@
+use std::io::{Read, Write};
+
+use rbx_dom_weak::types::UniqueId;
+
+use crate::{
+    core::XmlType,
+    deserializer_core::XmlEventReader,
+    error::{DecodeError, DecodeErrorKind, EncodeError},
+    serializer_core::XmlEventWriter,
+};
+
+// UniqueId values are stored as 32 hexadecimal characters containing the
+// random number, time, and index, in that order:
+//
+// <UniqueId name="foo">44b188dace632b4702e9c68d004815fc</UniqueId>
+impl XmlType for UniqueId {
+    const XML_TAG_NAME: &'static str = "UniqueId";
+
+    fn write_xml<W: Write>(&self, writer: &mut XmlEventWriter<W>) -> Result<(), EncodeError> {
+        writer.write_characters(self)
+    }
+
+    fn read_xml<R: Read>(reader: &mut XmlEventReader<R>) -> Result<Self, DecodeError> {
+        let contents = reader.read_characters()?;
+
+        UniqueId::from_hex(contents.trim())
+            .map_err(|_| reader.error(DecodeErrorKind::InvalidContent("invalid UniqueId")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::test_util;
+
+    #[test]
+    fn round_trip_unique_id() {
+        test_util::test_xml_round_trip(&UniqueId::new(0, 0, 0));
+        test_util::test_xml_round_trip(&UniqueId::new(0x0048_15fc, 0x02e9_c68d, -1));
+    }
+
+    #[test]
+    fn deserialize_unique_id() {
+        test_util::test_xml_deserialize(
+            r#"<UniqueId name="foo">44b188dace632b4702e9c68d004815fc</UniqueId>"#,
+            &UniqueId::new(0x0048_15fc, 0x02e9_c68d, 0x44b1_88da_ce63_2b47),
+        );
+    }
+
+    #[test]
+    fn serialize_unique_id() {
+        test_util::test_xml_serialize(
+            r#"<UniqueId name="foo">44b188dace632b4702e9c68d004815fc</UniqueId>"#,
+            &UniqueId::new(0x0048_15fc, 0x02e9_c68d, 0x44b1_88da_ce63_2b47),
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid_unique_id() {
+        let mut reader =
+            XmlEventReader::from_source(r#"<UniqueId name="foo">1234</UniqueId>"#.as_bytes());
+        reader.next().unwrap().unwrap();
+
+        assert!(UniqueId::read_outer_xml(&mut reader).is_err());
+    }
+}
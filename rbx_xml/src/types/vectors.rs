@@ -57,6 +57,11 @@ mod test {
         test_util::test_xml_round_trip(&Vector2int16::new(1234, 4567));
     }
 
+    #[test]
+    fn round_trip_vector2int16_negative() {
+        test_util::test_xml_round_trip(&Vector2int16::new(-1234, std::i16::MIN));
+    }
+
     #[test]
     fn round_trip_vector3() {
         test_util::test_xml_round_trip(&Vector3::new(123.0, 456.0, 7890.0));
@@ -66,4 +71,9 @@ mod test {
     fn round_trip_vector3int16() {
         test_util::test_xml_round_trip(&Vector3int16::new(1234, 4567, 8913));
     }
+
+    #[test]
+    fn round_trip_vector3int16_negative() {
+        test_util::test_xml_round_trip(&Vector3int16::new(-1234, std::i16::MIN, -1));
+    }
 }